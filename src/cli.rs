@@ -0,0 +1,180 @@
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use clap::{Parser, Subcommand, ValueEnum};
+
+use crate::service::codec::{gzip_base64_decode, gzip_base64_encode};
+use crate::service::file_list::{sort_files, FileSort};
+use crate::service::folder_scan::{scan_tree, FileNode};
+use crate::service::scan_filter::ScanOptions;
+use crate::service::json::format_json;
+use crate::util::file_util::human_readable_size;
+
+/// 命令行参数，不带子命令时启动图形界面
+#[derive(Parser)]
+#[command(name = "dev-tools", version, about = "开发中使用到的各种工具，不带子命令时启动图形界面")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// json 处理
+    Json {
+        #[command(subcommand)]
+        action: JsonAction,
+    },
+    /// gzip 压缩 + base64 编码的字符串处理
+    #[command(name = "gzip-b64")]
+    GzipB64 {
+        #[command(subcommand)]
+        action: GzipB64Action,
+    },
+    /// 统计文件夹下每个文件、文件夹的大小
+    FolderInfo {
+        /// 要统计的文件夹
+        #[arg(long)]
+        path: PathBuf,
+        /// 排序方式
+        #[arg(long, value_enum, default_value_t = SortBy::Size)]
+        sort: SortBy,
+        /// 以 json 格式输出
+        #[arg(long)]
+        json: bool,
+        /// 排除的 glob，不含 / 时匹配文件名，含 / 时匹配相对路径，可以传多次
+        #[arg(long, value_name = "PATTERN")]
+        exclude: Vec<String>,
+        /// 遵守 .gitignore、.ignore 文件，同时跳过 .git 文件夹
+        #[arg(long)]
+        gitignore: bool,
+        /// 不进入其他文件系统上的文件夹
+        #[arg(long)]
+        one_file_system: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum JsonAction {
+    /// 格式化 json
+    Format {
+        /// 输入文件，不传或传 - 时读取标准输入
+        input: Option<PathBuf>,
+        /// 输出压缩后的单行 json
+        #[arg(long)]
+        compact: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum GzipB64Action {
+    /// base64 解码后 gzip 解压
+    Decode {
+        /// 输入文件，不传或传 - 时读取标准输入
+        input: Option<PathBuf>,
+    },
+    /// gzip 压缩后 base64 编码
+    Encode {
+        /// 输入文件，不传或传 - 时读取标准输入
+        input: Option<PathBuf>,
+    },
+}
+
+/// 文件夹信息的排序方式
+#[derive(ValueEnum, Clone, Copy, PartialEq, Eq)]
+pub enum SortBy {
+    /// 按大小倒序
+    Size,
+    /// 按名称正序
+    Name,
+}
+
+type CliResult = Result<(), Box<dyn std::error::Error>>;
+
+/// 执行子命令，成功返回 0，失败时错误信息输出到标准错误并返回 1
+pub fn run(command: Command) -> ExitCode {
+    let result = match command {
+        Command::Json { action } => run_json(action),
+        Command::GzipB64 { action } => run_gzip_b64(action),
+        Command::FolderInfo { path, sort, json, exclude, gitignore, one_file_system } => {
+            let options = ScanOptions {
+                exclude_patterns: exclude,
+                respect_ignore_files: gitignore,
+                same_file_system: one_file_system,
+            };
+            run_folder_info(&path, sort, json, &options)
+        }
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("错误: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run_json(action: JsonAction) -> CliResult {
+    match action {
+        JsonAction::Format { input, compact } => {
+            let json_str = read_input(input.as_deref())?;
+            write_output(&format_json(&json_str, compact)?)
+        }
+    }
+}
+
+fn run_gzip_b64(action: GzipB64Action) -> CliResult {
+    match action {
+        GzipB64Action::Decode { input } => {
+            let text = read_input(input.as_deref())?;
+            write_output(&gzip_base64_decode(&text)?)
+        }
+        GzipB64Action::Encode { input } => {
+            let text = read_input(input.as_deref())?;
+            write_output(&gzip_base64_encode(&text)?)
+        }
+    }
+}
+
+fn run_folder_info(path: &Path, sort: SortBy, json: bool, options: &ScanOptions) -> CliResult {
+    let tree = scan_tree(path, options)?;
+    let mut files: Vec<_> = tree.children.iter().map(FileNode::to_file_info).collect();
+    if sort == SortBy::Name {
+        sort_files(&mut files, FileSort::NAME);
+    }
+    if json {
+        return write_output(&serde_json::to_string_pretty(&files)?);
+    }
+    let mut output = String::new();
+    for file in &files {
+        let file_type = if file.is_file { "文件" } else { "目录" };
+        output.push_str(&format!("{:>12}  {}  {}\n", human_readable_size(file.file_size), file_type, file.file_name));
+    }
+    let total_size: u64 = files.iter().map(|file| file.file_size).sum();
+    output.push_str(&format!("{:>12}  合计", human_readable_size(total_size)));
+    if tree.excluded_size > 0 {
+        output.push_str(&format!("\n{:>12}  已排除，未计入合计", human_readable_size(tree.excluded_size)));
+    }
+    write_output(&output)
+}
+
+/// 读取输入，未指定文件或文件为 - 时读取标准输入
+fn read_input(input: Option<&Path>) -> io::Result<String> {
+    match input {
+        Some(path) if path != Path::new("-") => fs::read_to_string(path),
+        _ => {
+            let mut text = String::new();
+            io::stdin().read_to_string(&mut text)?;
+            Ok(text)
+        }
+    }
+}
+
+fn write_output(text: &str) -> CliResult {
+    let mut stdout = io::stdout().lock();
+    writeln!(stdout, "{text}")?;
+    stdout.flush()?;
+    Ok(())
+}
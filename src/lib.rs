@@ -1,44 +1,62 @@
-use egui::Ui;
-use log::info;
+use std::path::PathBuf;
 
+use log::{debug, info};
+
+pub mod cli;
+pub mod notification;
+pub mod service;
+mod settings;
 mod tool;
 mod util;
+mod workspace;
 
-#[derive(PartialEq, Clone)]
-pub enum ToolType {
-    Unselected,
-    FolderInfo(tool::FolderInfoTool),
-    DecompressCompressStr(tool::DecompressCompressStrTool),
-    JsonFormat(tool::JsonFormatTool),
-}
+pub use tool::{Tool, ToolCategory, ToolClone, ToolDescriptor, ToolRegistry};
+use notification::NotificationCenter;
+pub use settings::AppSettings;
+pub use workspace::{TabState, ToolTab, Workspace, WorkspaceState};
 
-impl ToolType {
-    // 获得枚举的所有值
-    pub fn iter() -> Vec<ToolType> {
-        vec![ToolType::Unselected, ToolType::FolderInfo(tool::FolderInfoTool::new()), ToolType::DecompressCompressStr(tool::DecompressCompressStrTool::new())
-             , ToolType::JsonFormat(tool::JsonFormatTool::new())]
-    }
-
-    // 将枚举转换为字符串表示，用于显示
-    pub fn as_label(&self) -> &str {
-        match self {
-            ToolType::FolderInfo(_) => "文件夹信息",
-            ToolType::DecompressCompressStr(_) => "解压、压缩字符串",
-            ToolType::Unselected => { "请选择" }
-            ToolType::JsonFormat(_) => { "json格式化" }
-        }
-    }
-}
+/// 工作区在 eframe 存储中的 key
+const WORKSPACE_STORAGE_KEY: &str = "workspace";
+/// 设置在 eframe 存储中的 key
+const SETTINGS_STORAGE_KEY: &str = "settings";
+/// 当前工具不支持拖入的文件夹时，用于扫描的工具
+const FOLDER_TOOL_ID: &str = "folder_info";
 
 pub struct AppInstance {
-    tool_type: ToolType,
+    /// 可用的工具
+    registry: ToolRegistry,
+    /// 打开的工具标签
+    workspace: Workspace,
+    settings: AppSettings,
+    /// 全局消息
+    notification_center: NotificationCenter,
 }
 
 impl AppInstance {
     pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
+        Self::with_registry(cc, ToolRegistry::with_builtin_tools())
+    }
+
+    /// 使用自定义的工具注册表创建
+    pub fn with_registry(cc: &eframe::CreationContext<'_>, registry: ToolRegistry) -> Self {
         setup_custom_fonts(&cc.egui_ctx);
+        let mut workspace = Workspace::new();
+        let mut settings = AppSettings::default();
+        //恢复上次关闭时的状态
+        if let Some(storage) = cc.storage {
+            if let Some(saved_settings) = eframe::get_value(storage, SETTINGS_STORAGE_KEY) {
+                settings = saved_settings;
+            }
+            if let Some(workspace_state) = eframe::get_value(storage, WORKSPACE_STORAGE_KEY) {
+                workspace.restore_state(&registry, workspace_state);
+                info!("恢复工作区完成");
+            }
+        }
         Self {
-            tool_type: ToolType::Unselected,
+            registry,
+            workspace,
+            settings,
+            notification_center: NotificationCenter::new(),
         }
     }
 }
@@ -74,18 +92,47 @@ fn setup_custom_fonts(ctx: &egui::Context) {
 
 
 impl AppInstance {
-    /// 设置工具类型
-    pub fn change_tool_type(&mut self, tool_type: ToolType) {
-        self.tool_type = tool_type;
+    /// 在新标签中打开指定的工具
+    pub fn open_tool(&mut self, tool_id: &str) {
+        self.workspace.open(&self.registry, tool_id);
     }
 
-    /// 重新选择工具
-    pub fn reselect_tool(&mut self, ui: &mut Ui) {
-        if !ui.button("重新选择工具").clicked() {
-            //未点击
+    /// 拖入的文件、文件夹交给当前标签的工具处理，不支持时文件夹在新标签中扫描
+    fn handle_dropped_files(&mut self, ctx: &egui::Context) {
+        let paths: Vec<PathBuf> = ctx.input(|i| i.raw.dropped_files.iter().filter_map(|file| file.path.clone()).collect());
+        if paths.is_empty() {
+            return;
+        }
+        if let Some(tab) = self.workspace.active_tab_mut() {
+            if tab.tool.open_dropped(ctx, &paths) {
+                return;
+            }
+        }
+        if paths.iter().any(|path| path.is_dir()) {
+            self.open_tool(FOLDER_TOOL_ID);
+            if let Some(tab) = self.workspace.active_tab_mut() {
+                tab.tool.open_dropped(ctx, &paths);
+            }
             return;
         }
-        self.tool_type = ToolType::Unselected;
+        notification::warning(ctx, "当前工具不支持拖入的文件");
+    }
+
+    /// 拖动文件经过窗口时的提示
+    fn show_drop_hint(&self, ctx: &egui::Context) {
+        if ctx.input(|i| i.raw.hovered_files.is_empty()) {
+            return;
+        }
+        let painter = ctx.layer_painter(egui::LayerId::new(egui::Order::Foreground, egui::Id::new("drop_hint")));
+        let screen_rect = ctx.screen_rect();
+        painter.rect_filled(screen_rect, 0.0, egui::Color32::from_black_alpha(160));
+        painter.text(
+            screen_rect.center(),
+            egui::Align2::CENTER_CENTER,
+            "松开鼠标打开文件、文件夹",
+            egui::FontId::proportional(24.0),
+            egui::Color32::WHITE,
+        );
     }
 }
 
@@ -93,41 +140,49 @@ impl eframe::App for AppInstance {
     fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
         // 顶部面板
         egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
-            if self.tool_type == ToolType::Unselected {
-                //未选择工具时，选择工具
+            ui.horizontal(|ui| {
+                ui.menu_button("⚙", |ui| {
+                    self.settings.show(ui);
+                }).response.on_hover_text("设置");
+                self.notification_center.show_history_button(ui);
+                ui.separator();
+                if !self.workspace.is_empty() {
+                    self.workspace.show_tab_bar(ui, &self.registry);
+                    return;
+                }
+                //未打开任何工具时，选择工具
                 ui.heading("请选择要使用的工具");
+                let mut selected_tool_id = None;
                 egui::ComboBox::from_id_source("tool_type_check")
-                    .selected_text(self.tool_type.as_label())
+                    .selected_text("请选择")
                     .show_ui(ui, |ui| {
-                        for color in ToolType::iter() {
-                            ui.selectable_value(&mut self.tool_type, color.clone(), color.as_label());
-                        }
+                        selected_tool_id = workspace::show_tool_menu(ui, &self.registry);
                     });
-                return;
-            }
-            //设置全局的退出
-            self.reselect_tool(ui);
+                if let Some(tool_id) = selected_tool_id {
+                    self.open_tool(&tool_id);
+                }
+            });
         });
 
         egui::CentralPanel::default().show(ctx, |ui| {
-            let tool_type = &mut self.tool_type;
-            match tool_type {
-                ToolType::FolderInfo(folder_info) => {
-                    ui.horizontal(|ui| {
-                        folder_info.add_choose_folder_button(ui);
-                        folder_info.show_select_file_info(ui);
-                    });
-                    folder_info.show_sub_file_info(ui);
-                }
-                ToolType::DecompressCompressStr(decompress_str) => {
-                    decompress_str.show(ctx, ui);
-                }
-                ToolType::JsonFormat(json_format) => {
-                    json_format.show(ctx, ui, frame);
-                }
-                ToolType::Unselected => {}
+            if let Some(tab) = self.workspace.active_tab_mut() {
+                //不同标签的控件状态互不影响
+                ui.push_id(tab.id, |ui| {
+                    tab.tool.show(ctx, ui, frame);
+                });
             }
         },
         );
+
+        self.handle_dropped_files(ctx);
+        self.show_drop_hint(ctx);
+        self.notification_center.show(ctx);
+    }
+
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, SETTINGS_STORAGE_KEY, &self.settings);
+        let workspace_state = self.workspace.save_state(self.settings.persist_tool_state);
+        eframe::set_value(storage, WORKSPACE_STORAGE_KEY, &workspace_state);
+        debug!("保存工作区完成");
     }
-}
\ No newline at end of file
+}
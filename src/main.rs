@@ -1,10 +1,25 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-use std::{sync::Arc, thread::current};
+use std::{process::ExitCode, sync::Arc};
 
+use clap::Parser;
 use log::{error, info};
 const LOG4RS_CONFIG_PATH: &str = "config/log4rs.yaml";
-fn main() {
+fn main() -> ExitCode {
+    //带子命令时以命令行方式运行，不启动界面
+    let cli = match dev_tools::cli::Cli::try_parse() {
+        Ok(cli) => cli,
+        //帮助、版本信息和参数错误也要输出到启动它的终端
+        Err(e) => {
+            attach_parent_console();
+            e.exit();
+        }
+    };
+    if let Some(command) = cli.command {
+        attach_parent_console();
+        return dev_tools::cli::run(command);
+    }
+
     swtich_current_dir();
     let log4rs_init = log4rs::init_file(LOG4RS_CONFIG_PATH, Default::default());
     if log4rs_init.is_err() {
@@ -25,10 +40,28 @@ fn main() {
         Box::new(|_cc| Ok(Box::new(dev_tools::AppInstance::new(_cc)))),
     )
     .expect("运行异常");
+    ExitCode::SUCCESS
 }
 
 use std::env;
 
+///发布版在 Windows 上使用窗口子系统，没有控制台，命令行方式运行时附加到启动它的终端，否则输出会丢失
+#[cfg(all(windows, not(debug_assertions)))]
+fn attach_parent_console() {
+    const ATTACH_PARENT_PROCESS: u32 = u32::MAX;
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn AttachConsole(process_id: u32) -> i32;
+    }
+    //从资源管理器等没有控制台的进程启动时附加失败，忽略
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+#[cfg(not(all(windows, not(debug_assertions))))]
+fn attach_parent_console() {}
+
 ///切换当前目录
 fn swtich_current_dir() {
     let current_dir = env::current_dir();
@@ -0,0 +1,245 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use chrono::Local;
+use egui::{Align2, Color32, Context, Id, RichText, Ui};
+use log::{error, info, warn};
+
+/// 最多保留的历史消息数
+const MAX_HISTORY: usize = 200;
+/// 同时展示的弹窗数
+const MAX_TOASTS: usize = 5;
+
+/// 消息级别
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum NotificationLevel {
+    Info,
+    Success,
+    Warning,
+    Error,
+}
+
+impl NotificationLevel {
+    // 将枚举转换为字符串表示，用于显示
+    pub fn as_label(&self) -> &str {
+        match self {
+            NotificationLevel::Info => "信息",
+            NotificationLevel::Success => "成功",
+            NotificationLevel::Warning => "警告",
+            NotificationLevel::Error => "错误",
+        }
+    }
+
+    pub fn icon(&self) -> &str {
+        match self {
+            NotificationLevel::Info => "ℹ",
+            NotificationLevel::Success => "✔",
+            NotificationLevel::Warning => "⚠",
+            NotificationLevel::Error => "❌",
+        }
+    }
+
+    pub fn color(&self) -> Color32 {
+        match self {
+            NotificationLevel::Info => Color32::from_rgb(80, 150, 230),
+            NotificationLevel::Success => Color32::from_rgb(60, 170, 90),
+            NotificationLevel::Warning => Color32::from_rgb(230, 160, 40),
+            NotificationLevel::Error => Color32::from_rgb(220, 70, 70),
+        }
+    }
+
+    /// 弹窗自动关闭的时间，错误消息停留更久
+    pub fn duration(&self) -> Duration {
+        match self {
+            NotificationLevel::Info | NotificationLevel::Success => Duration::from_secs(3),
+            NotificationLevel::Warning => Duration::from_secs(5),
+            NotificationLevel::Error => Duration::from_secs(8),
+        }
+    }
+}
+
+/// 一条消息
+#[derive(Clone, Debug)]
+pub struct Notification {
+    pub level: NotificationLevel,
+    pub message: String,
+    /// 产生消息的时间，用于历史记录展示
+    pub time: String,
+}
+
+/// 待展示的消息队列，存放在 egui 的 [`Context`] 中，工具只要能拿到 ctx 就可以推送消息
+type NotificationQueue = Arc<Mutex<Vec<Notification>>>;
+
+fn queue_id() -> Id {
+    Id::new("notification_queue")
+}
+
+/// 推送一条消息，会在下一帧由 [`NotificationCenter`] 展示
+pub fn notify(ctx: &Context, level: NotificationLevel, message: impl Into<String>) {
+    let notification = Notification {
+        level,
+        message: message.into(),
+        time: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+    };
+    let queue = ctx.data_mut(|data| data.get_temp_mut_or_default::<NotificationQueue>(queue_id()).clone());
+    if let Ok(mut queue) = queue.lock() {
+        queue.push(notification);
+    }
+    ctx.request_repaint();
+}
+
+pub fn info(ctx: &Context, message: impl Into<String>) {
+    notify(ctx, NotificationLevel::Info, message);
+}
+
+pub fn success(ctx: &Context, message: impl Into<String>) {
+    notify(ctx, NotificationLevel::Success, message);
+}
+
+pub fn warning(ctx: &Context, message: impl Into<String>) {
+    notify(ctx, NotificationLevel::Warning, message);
+}
+
+pub fn error(ctx: &Context, message: impl Into<String>) {
+    notify(ctx, NotificationLevel::Error, message);
+}
+
+/// 正在展示的弹窗
+struct Toast {
+    id: u64,
+    notification: Notification,
+    shown_at: Instant,
+}
+
+/// 全局消息中心，负责弹窗展示和历史记录
+#[derive(Default)]
+pub struct NotificationCenter {
+    toasts: Vec<Toast>,
+    history: Vec<Notification>,
+    show_history: bool,
+    next_toast_id: u64,
+}
+
+impl NotificationCenter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 取出队列中的新消息并绘制弹窗，每帧调用一次
+    pub fn show(&mut self, ctx: &Context) {
+        self.receive(ctx);
+        self.show_toasts(ctx);
+        self.show_history_window(ctx);
+    }
+
+    /// 消息记录按钮，显示未读的弹窗数量
+    pub fn show_history_button(&mut self, ui: &mut Ui) {
+        let text = if self.toasts.is_empty() {
+            "🔔".to_string()
+        } else {
+            format!("🔔 {}", self.toasts.len())
+        };
+        if ui.button(text).on_hover_text("消息记录").clicked() {
+            self.show_history = !self.show_history;
+        }
+    }
+
+    fn receive(&mut self, ctx: &Context) {
+        let queue = ctx.data_mut(|data| data.get_temp_mut_or_default::<NotificationQueue>(queue_id()).clone());
+        let notifications: Vec<Notification> = match queue.lock() {
+            Ok(mut queue) => queue.drain(..).collect(),
+            Err(_) => return,
+        };
+        for notification in notifications {
+            match notification.level {
+                NotificationLevel::Error => error!("消息:{}", notification.message),
+                NotificationLevel::Warning => warn!("消息:{}", notification.message),
+                _ => info!("消息:{}", notification.message),
+            }
+            self.toasts.push(Toast {
+                id: self.next_toast_id,
+                notification: notification.clone(),
+                shown_at: Instant::now(),
+            });
+            self.next_toast_id += 1;
+            self.history.push(notification);
+        }
+        if self.toasts.len() > MAX_TOASTS {
+            self.toasts.drain(..self.toasts.len() - MAX_TOASTS);
+        }
+        if self.history.len() > MAX_HISTORY {
+            self.history.drain(..self.history.len() - MAX_HISTORY);
+        }
+    }
+
+    /// 在右下角堆叠展示弹窗，到时间自动关闭
+    fn show_toasts(&mut self, ctx: &Context) {
+        self.toasts.retain(|toast| toast.shown_at.elapsed() < toast.notification.level.duration());
+        let mut closed_ids = vec![];
+        let mut offset_y = -10.0;
+        for toast in self.toasts.iter().rev() {
+            let level = toast.notification.level;
+            let response = egui::Area::new(Id::new("notification_toast").with(toast.id))
+                .anchor(Align2::RIGHT_BOTTOM, egui::vec2(-10.0, offset_y))
+                .order(egui::Order::Foreground)
+                .interactable(true)
+                .show(ctx, |ui| {
+                    egui::Frame::popup(ui.style())
+                        .stroke(egui::Stroke::new(1.0, level.color()))
+                        .show(ui, |ui| {
+                            ui.set_max_width(320.0);
+                            ui.horizontal(|ui| {
+                                ui.label(RichText::new(level.icon()).color(level.color()));
+                                ui.label(RichText::new(level.as_label()).strong());
+                                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                    if ui.small_button("✖").clicked() {
+                                        closed_ids.push(toast.id);
+                                    }
+                                });
+                            });
+                            ui.label(&toast.notification.message);
+                        });
+                });
+            offset_y -= response.response.rect.height() + 6.0;
+
+            //到期时刷新界面，关闭弹窗
+            let remaining = level.duration().saturating_sub(toast.shown_at.elapsed());
+            ctx.request_repaint_after(remaining);
+        }
+        self.toasts.retain(|toast| !closed_ids.contains(&toast.id));
+    }
+
+    fn show_history_window(&mut self, ctx: &Context) {
+        if !self.show_history {
+            return;
+        }
+        let mut open = self.show_history;
+        let mut clear = false;
+        egui::Window::new("消息记录")
+            .open(&mut open)
+            .default_size(egui::vec2(420.0, 300.0))
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(format!("共 {} 条", self.history.len()));
+                    if ui.button("清空").clicked() {
+                        clear = true;
+                    }
+                });
+                ui.separator();
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for notification in self.history.iter().rev() {
+                        ui.horizontal_wrapped(|ui| {
+                            let level = notification.level;
+                            ui.label(RichText::new(level.icon()).color(level.color()));
+                            ui.label(RichText::new(&notification.time).weak());
+                            ui.label(&notification.message);
+                        });
+                    }
+                });
+            });
+        if clear {
+            self.history.clear();
+        }
+        self.show_history = open;
+    }
+}
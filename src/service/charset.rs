@@ -0,0 +1,78 @@
+use encoding_rs::Encoding;
+use thiserror::Error;
+
+/// 常用字符集，界面中作为可选项
+pub const COMMON_CHARSETS: [&str; 11] = [
+    "UTF-8",
+    "GBK",
+    "GB18030",
+    "Big5",
+    "Shift_JIS",
+    "EUC-JP",
+    "EUC-KR",
+    "ISO-8859-1",
+    "windows-1252",
+    "UTF-16LE",
+    "UTF-16BE",
+];
+
+/// 字符集转换错误
+#[derive(Error, Debug)]
+pub enum CharsetError {
+    #[error("不支持的字符集:{0}")]
+    Unknown(String),
+    #[error("内容不是有效的 {0} 文本")]
+    Malformed(String),
+    #[error("部分字符无法用 {0} 表示")]
+    Unmappable(String),
+}
+
+/// 按字符集名称解码为文本，名称不区分大小写，支持 gbk、latin1 等别名
+pub fn decode_charset(bytes: &[u8], charset: &str) -> Result<String, CharsetError> {
+    let encoding = find_charset(charset)?;
+    match encoding.decode_without_bom_handling_and_without_replacement(bytes) {
+        Some(text) => Ok(text.into_owned()),
+        None => Err(CharsetError::Malformed(encoding.name().to_string())),
+    }
+}
+
+/// 文本转换为指定字符集，有无法表示的字符时报错
+pub fn encode_charset(text: &str, charset: &str) -> Result<Vec<u8>, CharsetError> {
+    let encoding = find_charset(charset)?;
+    //encoding_rs 编码 UTF-16 时输出的是 UTF-8，需要单独处理
+    if encoding == encoding_rs::UTF_16LE {
+        return Ok(text.encode_utf16().flat_map(u16::to_le_bytes).collect());
+    }
+    if encoding == encoding_rs::UTF_16BE {
+        return Ok(text.encode_utf16().flat_map(u16::to_be_bytes).collect());
+    }
+    let (bytes, _, unmappable) = encoding.encode(text);
+    if unmappable {
+        return Err(CharsetError::Unmappable(encoding.name().to_string()));
+    }
+    Ok(bytes.into_owned())
+}
+
+fn find_charset(charset: &str) -> Result<&'static Encoding, CharsetError> {
+    Encoding::for_label(charset.trim().as_bytes()).ok_or_else(|| CharsetError::Unknown(charset.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gbk_round_trip() {
+        let bytes = encode_charset("中文", "gbk").unwrap();
+        assert_eq!(bytes, vec![0xd6, 0xd0, 0xce, 0xc4]);
+        assert_eq!(decode_charset(&bytes, "GBK").unwrap(), "中文");
+        assert_eq!(encode_charset("a", "utf-16be").unwrap(), vec![0, b'a']);
+    }
+
+    #[test]
+    fn errors_are_reported() {
+        assert!(matches!(encode_charset("😀", "gbk"), Err(CharsetError::Unmappable(_))));
+        assert!(matches!(decode_charset(b"a", "not-a-charset"), Err(CharsetError::Unknown(_))));
+        assert!(matches!(decode_charset(&[0xff, 0xfe, 0xfd], "UTF-8"), Err(CharsetError::Malformed(_))));
+    }
+}
@@ -0,0 +1,407 @@
+use std::io::{Read, Write};
+use std::ops::RangeInclusive;
+use std::string::FromUtf8Error;
+
+use base64::alphabet;
+use base64::engine::general_purpose::{self, GeneralPurpose, GeneralPurposeConfig};
+use base64::engine::DecodePaddingMode;
+use base64::Engine;
+use data_encoding::{BASE32, BASE32_NOPAD, HEXLOWER, HEXLOWER_PERMISSIVE};
+use flate2::read::{DeflateDecoder, GzDecoder, ZlibDecoder};
+use flate2::write::{DeflateEncoder, GzEncoder, ZlibEncoder};
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// 编解码错误
+#[derive(Error, Debug)]
+pub enum CodecError {
+    #[error("base64 解码失败:{0}")]
+    Base64(#[from] base64::DecodeError),
+    #[error("解压、压缩失败:{0}")]
+    Io(#[from] std::io::Error),
+    #[error("结果不是有效的 UTF-8 文本:{0}")]
+    Utf8(#[from] FromUtf8Error),
+    #[error("lz4 压缩失败:{0}")]
+    Lz4(#[from] lz4_flex::frame::Error),
+    #[error("hex、base32 解码失败:{0}")]
+    Encoding(#[from] data_encoding::DecodeError),
+    #[error("base85 解码失败:{0}")]
+    Base85(#[from] z85::DecodeError),
+    #[error("解压结果超过 {} MiB 上限，可能是压缩炸弹", .0 / 1024 / 1024)]
+    TooLarge(u64),
+}
+
+/// 解压结果的大小上限，防止很小的输入解压出过大的数据占满内存
+pub const MAX_DECOMPRESSED_SIZE: u64 = 256 * 1024 * 1024;
+
+/// 解码时不要求 base64 填充
+const TOLERANT_BASE64_CONFIG: GeneralPurposeConfig = GeneralPurposeConfig::new()
+    .with_decode_padding_mode(DecodePaddingMode::Indifferent)
+    .with_decode_allow_trailing_bits(true);
+const TOLERANT_BASE64: GeneralPurpose = GeneralPurpose::new(&alphabet::STANDARD, TOLERANT_BASE64_CONFIG);
+const TOLERANT_BASE64_URL_SAFE: GeneralPurpose = GeneralPurpose::new(&alphabet::URL_SAFE, TOLERANT_BASE64_CONFIG);
+
+/// 压缩后数据的文本编码
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub enum TextEncoding {
+    #[default]
+    Base64,
+    /// 使用 - 和 _ 的 base64
+    Base64UrlSafe,
+    /// 不带 = 填充的 base64
+    Base64NoPad,
+    Base64UrlSafeNoPad,
+    /// 小写 hex，解码时不区分大小写
+    Hex,
+    Base32,
+    /// ZeroMQ 的 Z85
+    Base85,
+}
+
+impl TextEncoding {
+    pub fn iter() -> impl Iterator<Item = TextEncoding> {
+        [
+            TextEncoding::Base64,
+            TextEncoding::Base64UrlSafe,
+            TextEncoding::Base64NoPad,
+            TextEncoding::Base64UrlSafeNoPad,
+            TextEncoding::Hex,
+            TextEncoding::Base32,
+            TextEncoding::Base85,
+        ]
+        .into_iter()
+    }
+
+    pub fn as_label(&self) -> &str {
+        match self {
+            TextEncoding::Base64 => "base64",
+            TextEncoding::Base64UrlSafe => "base64 (URL 安全)",
+            TextEncoding::Base64NoPad => "base64 (无填充)",
+            TextEncoding::Base64UrlSafeNoPad => "base64 (URL 安全、无填充)",
+            TextEncoding::Hex => "hex",
+            TextEncoding::Base32 => "base32",
+            TextEncoding::Base85 => "base85 (Z85)",
+        }
+    }
+}
+
+/// 按指定编码转为文本
+pub fn encode_bytes(bytes: &[u8], encoding: TextEncoding) -> String {
+    match encoding {
+        TextEncoding::Base64 => general_purpose::STANDARD.encode(bytes),
+        TextEncoding::Base64UrlSafe => general_purpose::URL_SAFE.encode(bytes),
+        TextEncoding::Base64NoPad => general_purpose::STANDARD_NO_PAD.encode(bytes),
+        TextEncoding::Base64UrlSafeNoPad => general_purpose::URL_SAFE_NO_PAD.encode(bytes),
+        TextEncoding::Hex => HEXLOWER.encode(bytes),
+        TextEncoding::Base32 => BASE32.encode(bytes),
+        TextEncoding::Base85 => z85::encode(bytes),
+    }
+}
+
+/// 按指定编码解码文本
+///
+/// 忽略所有空白、换行和 data URI 前缀(data:...,)，base64 和 base32 的填充可有可无，hex 可以带 0x 前缀
+pub fn decode_bytes(text: &str, encoding: TextEncoding) -> Result<Vec<u8>, CodecError> {
+    let text = strip_data_uri(text.trim());
+    let text: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+    let bytes = match encoding {
+        TextEncoding::Base64 | TextEncoding::Base64NoPad => TOLERANT_BASE64.decode(&text)?,
+        TextEncoding::Base64UrlSafe | TextEncoding::Base64UrlSafeNoPad => TOLERANT_BASE64_URL_SAFE.decode(&text)?,
+        TextEncoding::Hex => {
+            let text = text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")).unwrap_or(&text);
+            HEXLOWER_PERMISSIVE.decode(text.as_bytes())?
+        }
+        TextEncoding::Base32 => BASE32_NOPAD.decode(text.trim_end_matches('=').to_ascii_uppercase().as_bytes())?,
+        TextEncoding::Base85 => z85::decode(&text)?,
+    };
+    Ok(bytes)
+}
+
+/// 去掉 data:[mime][;base64], 前缀
+fn strip_data_uri(text: &str) -> &str {
+    if !text.starts_with("data:") {
+        return text;
+    }
+    match text.split_once(',') {
+        Some((_, data)) => data,
+        None => text,
+    }
+}
+
+/// 压缩算法
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub enum CompressionCodec {
+    #[default]
+    Gzip,
+    Zlib,
+    /// 不带头部的原始 deflate 数据
+    Deflate,
+    Zstd,
+    Brotli,
+    /// lz4 frame 格式
+    Lz4,
+    Bzip2,
+    Xz,
+}
+
+impl CompressionCodec {
+    pub fn iter() -> impl Iterator<Item = CompressionCodec> {
+        [
+            CompressionCodec::Gzip,
+            CompressionCodec::Zlib,
+            CompressionCodec::Deflate,
+            CompressionCodec::Zstd,
+            CompressionCodec::Brotli,
+            CompressionCodec::Lz4,
+            CompressionCodec::Bzip2,
+            CompressionCodec::Xz,
+        ]
+        .into_iter()
+    }
+
+    pub fn as_label(&self) -> &str {
+        match self {
+            CompressionCodec::Gzip => "gzip",
+            CompressionCodec::Zlib => "zlib",
+            CompressionCodec::Deflate => "deflate",
+            CompressionCodec::Zstd => "zstd",
+            CompressionCodec::Brotli => "brotli",
+            CompressionCodec::Lz4 => "lz4",
+            CompressionCodec::Bzip2 => "bzip2",
+            CompressionCodec::Xz => "xz",
+        }
+    }
+
+    /// 支持的压缩级别，None 表示不支持调整
+    pub fn level_range(&self) -> Option<RangeInclusive<u32>> {
+        match self {
+            CompressionCodec::Gzip | CompressionCodec::Zlib | CompressionCodec::Deflate | CompressionCodec::Xz => Some(0..=9),
+            CompressionCodec::Zstd => Some(1..=22),
+            CompressionCodec::Brotli => Some(0..=11),
+            CompressionCodec::Lz4 => None,
+            CompressionCodec::Bzip2 => Some(1..=9),
+        }
+    }
+
+    /// 各算法命令行工具的默认级别
+    pub fn default_level(&self) -> u32 {
+        match self {
+            CompressionCodec::Gzip | CompressionCodec::Zlib | CompressionCodec::Deflate | CompressionCodec::Xz => 6,
+            CompressionCodec::Zstd => 3,
+            CompressionCodec::Brotli => 11,
+            CompressionCodec::Lz4 => 0,
+            CompressionCodec::Bzip2 => 9,
+        }
+    }
+
+    /// 把级别限制在支持的范围内
+    pub fn clamp_level(&self, level: u32) -> u32 {
+        match self.level_range() {
+            Some(range) => level.clamp(*range.start(), *range.end()),
+            None => 0,
+        }
+    }
+}
+
+/// 按指定算法压缩，级别超出范围时取最接近的值
+pub fn compress(bytes: &[u8], codec: CompressionCodec, level: u32) -> Result<Vec<u8>, CodecError> {
+    let level = codec.clamp_level(level);
+    let compressed = match codec {
+        CompressionCodec::Gzip => {
+            let mut encoder = GzEncoder::new(Vec::new(), Compression::new(level));
+            encoder.write_all(bytes)?;
+            encoder.finish()?
+        }
+        CompressionCodec::Zlib => {
+            let mut encoder = ZlibEncoder::new(Vec::new(), Compression::new(level));
+            encoder.write_all(bytes)?;
+            encoder.finish()?
+        }
+        CompressionCodec::Deflate => {
+            let mut encoder = DeflateEncoder::new(Vec::new(), Compression::new(level));
+            encoder.write_all(bytes)?;
+            encoder.finish()?
+        }
+        CompressionCodec::Zstd => zstd::encode_all(bytes, level as i32)?,
+        CompressionCodec::Brotli => {
+            let mut encoder = brotli::CompressorWriter::new(Vec::new(), 4096, level, 22);
+            encoder.write_all(bytes)?;
+            encoder.into_inner()
+        }
+        CompressionCodec::Lz4 => {
+            let mut encoder = lz4_flex::frame::FrameEncoder::new(Vec::new());
+            encoder.write_all(bytes)?;
+            encoder.finish()?
+        }
+        CompressionCodec::Bzip2 => {
+            let mut encoder = bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::new(level));
+            encoder.write_all(bytes)?;
+            encoder.finish()?
+        }
+        CompressionCodec::Xz => {
+            let mut encoder = xz2::write::XzEncoder::new(Vec::new(), level);
+            encoder.write_all(bytes)?;
+            encoder.finish()?
+        }
+    };
+    Ok(compressed)
+}
+
+/// 按指定算法解压，结果超过 [`MAX_DECOMPRESSED_SIZE`] 时返回错误
+pub fn decompress(bytes: &[u8], codec: CompressionCodec) -> Result<Vec<u8>, CodecError> {
+    decompress_with_limit(bytes, codec, MAX_DECOMPRESSED_SIZE)
+}
+
+fn decompress_with_limit(bytes: &[u8], codec: CompressionCodec, limit: u64) -> Result<Vec<u8>, CodecError> {
+    match codec {
+        CompressionCodec::Gzip => read_limited(GzDecoder::new(bytes), limit),
+        CompressionCodec::Zlib => read_limited(ZlibDecoder::new(bytes), limit),
+        CompressionCodec::Deflate => read_limited(DeflateDecoder::new(bytes), limit),
+        CompressionCodec::Zstd => read_limited(zstd::Decoder::new(bytes)?, limit),
+        CompressionCodec::Brotli => read_limited(brotli::Decompressor::new(bytes, 4096), limit),
+        CompressionCodec::Lz4 => read_limited(lz4_flex::frame::FrameDecoder::new(bytes), limit),
+        CompressionCodec::Bzip2 => read_limited(bzip2::read::MultiBzDecoder::new(bytes), limit),
+        CompressionCodec::Xz => read_limited(xz2::read::XzDecoder::new_multi_decoder(bytes), limit),
+    }
+}
+
+/// 最多多读一个字节，用来判断是否超过上限
+fn read_limited(reader: impl Read, limit: u64) -> Result<Vec<u8>, CodecError> {
+    let mut decompressed = vec![];
+    reader.take(limit + 1).read_to_end(&mut decompressed)?;
+    if decompressed.len() as u64 > limit {
+        return Err(CodecError::TooLarge(limit));
+    }
+    Ok(decompressed)
+}
+
+/// 按指定编码解码后按指定算法解压，结果不一定是文本
+pub fn decode_compressed_bytes(text: &str, encoding: TextEncoding, codec: CompressionCodec) -> Result<Vec<u8>, CodecError> {
+    decompress(&decode_bytes(text, encoding)?, codec)
+}
+
+/// 按指定编码解码后按指定算法解压为文本
+pub fn decode_compressed_text(text: &str, encoding: TextEncoding, codec: CompressionCodec) -> Result<String, CodecError> {
+    Ok(String::from_utf8(decode_compressed_bytes(text, encoding, codec)?)?)
+}
+
+/// 按指定算法压缩后按指定编码转为文本
+pub fn encode_compressed_bytes(bytes: &[u8], encoding: TextEncoding, codec: CompressionCodec, level: u32) -> Result<String, CodecError> {
+    Ok(encode_bytes(&compress(bytes, codec, level)?, encoding))
+}
+
+/// 文本按指定算法压缩后按指定编码转为文本
+pub fn encode_compressed_text(text: &str, encoding: TextEncoding, codec: CompressionCodec, level: u32) -> Result<String, CodecError> {
+    encode_compressed_bytes(text.as_bytes(), encoding, codec, level)
+}
+
+/// base64 解码后 gzip 解压，忽略首尾空白
+pub fn gzip_base64_decode(text: &str) -> Result<String, CodecError> {
+    decode_compressed_text(text, TextEncoding::Base64, CompressionCodec::Gzip)
+}
+
+/// gzip 压缩后 base64 编码
+pub fn gzip_base64_encode(text: &str) -> Result<String, CodecError> {
+    encode_compressed_text(text, TextEncoding::Base64, CompressionCodec::Gzip, CompressionCodec::Gzip.default_level())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_then_decode_returns_original_text() {
+        let text = "hello 世界\n{\"a\":1}";
+        let encoded = gzip_base64_encode(text).unwrap();
+        assert_eq!(gzip_base64_decode(&encoded).unwrap(), text);
+    }
+
+    #[test]
+    fn decode_ignores_surrounding_whitespace() {
+        let encoded = gzip_base64_encode("abc").unwrap();
+        assert_eq!(gzip_base64_decode(&format!("  {encoded}\n")).unwrap(), "abc");
+    }
+
+    #[test]
+    fn decode_rejects_invalid_base64() {
+        assert!(matches!(gzip_base64_decode("不是base64"), Err(CodecError::Base64(_))));
+    }
+
+    #[test]
+    fn every_codec_round_trips_at_its_level_bounds() {
+        let text = "hello 世界 ".repeat(100);
+        for codec in CompressionCodec::iter() {
+            let levels = match codec.level_range() {
+                Some(range) => vec![*range.start(), codec.default_level(), *range.end()],
+                None => vec![0],
+            };
+            for level in levels {
+                let encoded = encode_compressed_text(&text, TextEncoding::Base64, codec, level).unwrap();
+                assert_eq!(decode_compressed_text(&encoded, TextEncoding::Base64, codec).unwrap(), text, "{codec:?} level {level}");
+            }
+        }
+    }
+
+    #[test]
+    fn levels_out_of_range_are_clamped() {
+        assert_eq!(CompressionCodec::Zstd.clamp_level(0), 1);
+        assert_eq!(CompressionCodec::Gzip.clamp_level(30), 9);
+        assert_eq!(CompressionCodec::Lz4.clamp_level(5), 0);
+        let compressed = compress(b"abc", CompressionCodec::Bzip2, 100).unwrap();
+        assert_eq!(decompress(&compressed, CompressionCodec::Bzip2).unwrap(), b"abc");
+    }
+
+    #[test]
+    fn every_encoding_round_trips() {
+        let bytes: Vec<u8> = (0..=255).collect();
+        for encoding in TextEncoding::iter() {
+            for len in [0, 1, 2, 3, 4, 5, 255] {
+                let encoded = encode_bytes(&bytes[..len], encoding);
+                assert_eq!(decode_bytes(&encoded, encoding).unwrap(), &bytes[..len], "{encoding:?} len {len}");
+            }
+        }
+        assert!(!encode_bytes(b"ab", TextEncoding::Base64NoPad).ends_with('='));
+        assert!(!encode_bytes(&[0xfb, 0xff], TextEncoding::Base64UrlSafe).contains(['+', '/']));
+    }
+
+    #[test]
+    fn decode_is_tolerant_of_whitespace_padding_and_data_uri() {
+        assert_eq!(decode_bytes("data:application/gzip;base64,aGVs\nbG8", TextEncoding::Base64).unwrap(), b"hello");
+        assert_eq!(decode_bytes(" aGVsbG8= ", TextEncoding::Base64NoPad).unwrap(), b"hello");
+        assert_eq!(decode_bytes("0x68 65 6C\r\n6c 6f", TextEncoding::Hex).unwrap(), b"hello");
+        assert_eq!(decode_bytes("nbswy3dp", TextEncoding::Base32).unwrap(), b"hello");
+        assert!(matches!(decode_bytes("xyz", TextEncoding::Hex), Err(CodecError::Encoding(_))));
+        assert!(matches!(decode_bytes("ab", TextEncoding::Base85), Err(CodecError::Base85(_))));
+    }
+
+    #[test]
+    fn binary_payload_decodes_to_bytes() {
+        let bytes = vec![0x08, 0x96, 0x01, 0xff];
+        let encoded = encode_compressed_bytes(&bytes, TextEncoding::Hex, CompressionCodec::Zstd, 3).unwrap();
+        assert_eq!(decode_compressed_bytes(&encoded, TextEncoding::Hex, CompressionCodec::Zstd).unwrap(), bytes);
+        assert!(matches!(decode_compressed_text(&encoded, TextEncoding::Hex, CompressionCodec::Zstd), Err(CodecError::Utf8(_))));
+    }
+
+    #[test]
+    fn decompress_with_wrong_codec_fails() {
+        let compressed = compress(b"abc", CompressionCodec::Zstd, 3).unwrap();
+        assert!(decompress(&compressed, CompressionCodec::Xz).is_err());
+        assert!(decompress(&compressed, CompressionCodec::Gzip).is_err());
+    }
+
+    #[test]
+    fn decode_rejects_data_that_is_not_gzip() {
+        let encoded = general_purpose::STANDARD.encode("plain text");
+        assert!(matches!(gzip_base64_decode(&encoded), Err(CodecError::Io(_))));
+    }
+
+    #[test]
+    fn decompress_stops_at_limit() {
+        for codec in CompressionCodec::iter() {
+            let compressed = compress(&[0; 4096], codec, codec.default_level()).unwrap();
+            assert_eq!(decompress_with_limit(&compressed, codec, 4096).unwrap().len(), 4096);
+            assert!(matches!(decompress_with_limit(&compressed, codec, 4095), Err(CodecError::TooLarge(4095))), "{codec:?}");
+        }
+    }
+}
@@ -0,0 +1,217 @@
+use std::fmt;
+
+use thiserror::Error;
+
+use crate::service::codec::{decode_bytes, decompress, CodecError, CompressionCodec, TextEncoding};
+
+/// 最多解码的层数，避免异常数据无限解码
+const MAX_LAYERS: usize = 8;
+
+/// 依次尝试的文本编码，hex 的字符同时也是合法的 base64、base32，需要放在前面
+const CANDIDATE_ENCODINGS: [TextEncoding; 5] = [
+    TextEncoding::Hex,
+    TextEncoding::Base64,
+    TextEncoding::Base64UrlSafe,
+    TextEncoding::Base32,
+    TextEncoding::Base85,
+];
+
+/// 自动识别错误
+#[derive(Error, Debug)]
+pub enum DetectError {
+    #[error("未识别出编码或压缩格式")]
+    Undetected,
+    #[error("识别为:{chain} 后解码失败:{source}")]
+    Decode {
+        chain: String,
+        source: CodecError,
+    },
+}
+
+/// 识别出的一层编码或压缩
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Layer {
+    Encoding(TextEncoding),
+    Compression(CompressionCodec),
+}
+
+impl fmt::Display for Layer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Layer::Encoding(encoding) => f.write_str(encoding.as_label()),
+            Layer::Compression(codec) => f.write_str(codec.as_label()),
+        }
+    }
+}
+
+/// 自动识别的结果
+#[derive(Debug)]
+pub struct AutoDecoded {
+    /// 从外到内依次解开的层
+    pub layers: Vec<Layer>,
+    /// 解开后的内容，不一定是 UTF-8 文本
+    pub bytes: Vec<u8>,
+}
+
+impl AutoDecoded {
+    /// 第一个文本编码
+    pub fn encoding(&self) -> Option<TextEncoding> {
+        self.layers.iter().find_map(|layer| match layer {
+            Layer::Encoding(encoding) => Some(*encoding),
+            Layer::Compression(_) => None,
+        })
+    }
+
+    /// 第一个压缩算法
+    pub fn codec(&self) -> Option<CompressionCodec> {
+        self.layers.iter().find_map(|layer| match layer {
+            Layer::Compression(codec) => Some(*codec),
+            Layer::Encoding(_) => None,
+        })
+    }
+}
+
+/// 格式化为 base64 → gzip 的形式
+pub fn format_chain(layers: &[Layer]) -> String {
+    layers.iter().map(Layer::to_string).collect::<Vec<_>>().join(" → ")
+}
+
+/// 根据开头的魔数识别压缩格式，deflate 和 brotli 没有魔数，无法识别
+pub fn detect_compression(bytes: &[u8]) -> Option<CompressionCodec> {
+    match bytes {
+        [0x1f, 0x8b, ..] => Some(CompressionCodec::Gzip),
+        [0x28, 0xb5, 0x2f, 0xfd, ..] => Some(CompressionCodec::Zstd),
+        [0xfd, b'7', b'z', b'X', b'Z', 0x00, ..] => Some(CompressionCodec::Xz),
+        [b'B', b'Z', b'h', b'1'..=b'9', ..] => Some(CompressionCodec::Bzip2),
+        [0x04, 0x22, 0x4d, 0x18, ..] => Some(CompressionCodec::Lz4),
+        //zlib 头部：低 4 位为 8(deflate)，没有预设字典，两个字节组成的数能被 31 整除
+        [cmf, flg, ..] if cmf & 0x0f == 8 && cmf >> 4 <= 7 && flg & 0x20 == 0 && (u16::from(*cmf) << 8 | u16::from(*flg)) % 31 == 0 => {
+            Some(CompressionCodec::Zlib)
+        }
+        _ => None,
+    }
+}
+
+/// 逐层识别并解开编码和压缩，直到得到普通文本或无法继续识别的二进制数据
+///
+/// 压缩格式按魔数识别；文本编码按顺序尝试，解码结果是压缩数据或可读文本时才认为识别正确，
+/// base32、base85 只在解码结果是压缩数据时识别
+pub fn auto_decode(input: &[u8]) -> Result<AutoDecoded, DetectError> {
+    let mut layers = vec![];
+    let mut bytes = input.to_vec();
+    while layers.len() < MAX_LAYERS {
+        let Some((layer, decoded)) = next_layer(&bytes).map_err(|source| DetectError::Decode {
+            chain: format_chain(&layers),
+            source,
+        })?
+        else {
+            break;
+        };
+        layers.push(layer);
+        bytes = decoded;
+    }
+    if layers.is_empty() {
+        return Err(DetectError::Undetected);
+    }
+    Ok(AutoDecoded { layers, bytes })
+}
+
+/// 识别并解开一层，已经是普通文本时返回 None
+fn next_layer(bytes: &[u8]) -> Result<Option<(Layer, Vec<u8>)>, CodecError> {
+    if let Some(codec) = detect_compression(bytes) {
+        match decompress(bytes, codec) {
+            Ok(decompressed) => return Ok(Some((Layer::Compression(codec), decompressed))),
+            Err(e) if std::str::from_utf8(bytes).is_err() => return Err(e),
+            //文本恰好以魔数开头，继续按文本编码尝试
+            Err(_) => {}
+        }
+    }
+    let Ok(text) = std::str::from_utf8(bytes) else {
+        return Ok(None);
+    };
+    for encoding in CANDIDATE_ENCODINGS {
+        let Ok(decoded) = decode_bytes(text, encoding) else {
+            continue;
+        };
+        if decoded.is_empty() {
+            continue;
+        }
+        //base32、base85 的字符集太宽，普通单词也能解码出可读文本，只在解码出压缩数据时认为识别正确
+        let may_be_text = !matches!(encoding, TextEncoding::Base32 | TextEncoding::Base85);
+        if detect_compression(&decoded).is_some() || (may_be_text && is_readable_text(&decoded)) {
+            return Ok(Some((Layer::Encoding(encoding), decoded)));
+        }
+    }
+    Ok(None)
+}
+
+/// 是否是不含控制字符的 UTF-8 文本，空白除外
+fn is_readable_text(bytes: &[u8]) -> bool {
+    std::str::from_utf8(bytes).is_ok_and(|text| text.chars().all(|c| !c.is_control() || c.is_whitespace()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::service::codec::{compress, encode_bytes};
+
+    fn encoded(text: &str, codec: CompressionCodec, encoding: TextEncoding) -> String {
+        encode_bytes(&compress(text.as_bytes(), codec, codec.default_level()).unwrap(), encoding)
+    }
+
+    #[test]
+    fn detects_magic_bytes_of_every_codec_with_magic() {
+        for codec in CompressionCodec::iter() {
+            let compressed = compress(b"hello hello hello", codec, codec.default_level()).unwrap();
+            let expected = match codec {
+                CompressionCodec::Deflate | CompressionCodec::Brotli => None,
+                _ => Some(codec),
+            };
+            assert_eq!(detect_compression(&compressed), expected, "{codec:?}");
+        }
+        assert_eq!(detect_compression(b"plain text"), None);
+    }
+
+    #[test]
+    fn decodes_encoding_then_compression() {
+        let text = "{\"a\":\"你好\"}";
+        for (codec, encoding) in [
+            (CompressionCodec::Gzip, TextEncoding::Base64),
+            (CompressionCodec::Zstd, TextEncoding::Hex),
+            (CompressionCodec::Zlib, TextEncoding::Base64UrlSafeNoPad),
+            (CompressionCodec::Xz, TextEncoding::Base32),
+            (CompressionCodec::Bzip2, TextEncoding::Base85),
+        ] {
+            let decoded = auto_decode(encoded(text, codec, encoding).as_bytes()).unwrap();
+            assert_eq!(decoded.bytes, text.as_bytes(), "{codec:?} {encoding:?}");
+            assert_eq!(decoded.codec(), Some(codec));
+        }
+    }
+
+    #[test]
+    fn decodes_nested_layers_and_raw_compressed_bytes() {
+        let inner = encoded("hello", CompressionCodec::Gzip, TextEncoding::Base64);
+        let outer = encode_bytes(&compress(inner.as_bytes(), CompressionCodec::Zstd, 3).unwrap(), TextEncoding::Hex);
+        let decoded = auto_decode(outer.as_bytes()).unwrap();
+        assert_eq!(decoded.bytes, b"hello");
+        assert_eq!(format_chain(&decoded.layers), "hex → zstd → base64 → gzip");
+
+        let plain_base64 = encode_bytes("{\"a\":1}".as_bytes(), TextEncoding::Base64);
+        assert_eq!(auto_decode(plain_base64.as_bytes()).unwrap().bytes, b"{\"a\":1}");
+
+        let binary = encode_bytes(&compress(&[0xff, 0x00, 0xfe], CompressionCodec::Gzip, 6).unwrap(), TextEncoding::Base64);
+        assert_eq!(auto_decode(binary.as_bytes()).unwrap().bytes, vec![0xff, 0x00, 0xfe]);
+
+        let raw = compress(b"hello", CompressionCodec::Lz4, 0).unwrap();
+        let decoded = auto_decode(&raw).unwrap();
+        assert_eq!(decoded.layers, vec![Layer::Compression(CompressionCodec::Lz4)]);
+    }
+
+    #[test]
+    fn plain_text_is_not_detected() {
+        assert!(matches!(auto_decode(b"hello world"), Err(DetectError::Undetected)));
+        assert!(matches!(auto_decode(b"test"), Err(DetectError::Undetected)));
+        assert!(matches!(auto_decode(b"hello"), Err(DetectError::Undetected)));
+        assert!(matches!(auto_decode(&[0x1f, 0x8b, 0x00]), Err(DetectError::Decode { .. })));
+    }
+}
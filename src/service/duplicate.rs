@@ -0,0 +1,481 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Arc;
+use std::thread;
+
+use log::{error, info};
+use thiserror::Error;
+
+use crate::service::file_ops::{move_to_trash, FileOpError};
+
+/// 计算部分哈希时读取的字节数
+const PARTIAL_HASH_SIZE: usize = 4096;
+
+/// 重复文件处理错误
+#[derive(Error, Debug)]
+pub enum DuplicateError {
+    #[error("读取文件:{path:?} 失败:{source}")]
+    Read {
+        path: PathBuf,
+        source: io::Error,
+    },
+    #[error("处理文件:{path:?} 失败:{source}")]
+    Write {
+        path: PathBuf,
+        source: io::Error,
+    },
+    #[error("文件:{path:?} 在查找后被修改，与保留的文件内容不再相同")]
+    ContentChanged {
+        path: PathBuf,
+    },
+    #[error(transparent)]
+    Trash(#[from] FileOpError),
+}
+
+/// 一组内容完全相同的文件
+#[derive(PartialEq, Clone, Debug)]
+pub struct DuplicateGroup {
+    /// 单个文件的字节数
+    pub size: u64,
+    /// 完整内容的哈希
+    pub hash: String,
+    /// 按路径排序
+    pub files: Vec<PathBuf>,
+}
+
+impl DuplicateGroup {
+    /// 只保留一个文件时可以释放的空间
+    pub fn reclaimable(&self) -> u64 {
+        self.size * self.files.len().saturating_sub(1) as u64
+    }
+}
+
+/// 查找进度，工作线程更新，界面线程读取
+#[derive(Default, Debug)]
+pub struct DuplicateProgress {
+    /// 大小相同、需要计算哈希的文件数
+    candidates: AtomicU64,
+    /// 已计算哈希的次数，部分哈希和完整哈希各算一次
+    hashed: AtomicU64,
+    cancelled: AtomicBool,
+}
+
+impl DuplicateProgress {
+    pub fn candidates(&self) -> u64 {
+        self.candidates.load(Ordering::Relaxed)
+    }
+
+    pub fn hashed(&self) -> u64 {
+        self.hashed.load(Ordering::Relaxed)
+    }
+
+    /// 请求取消查找，工作线程会尽快退出
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// 后台查找的句柄
+pub struct DuplicateSearchHandle {
+    receiver: Receiver<Vec<DuplicateGroup>>,
+    progress: Arc<DuplicateProgress>,
+}
+
+impl DuplicateSearchHandle {
+    pub fn progress(&self) -> &DuplicateProgress {
+        &self.progress
+    }
+
+    pub fn cancel(&self) {
+        self.progress.cancel();
+    }
+
+    /// 查找完成时返回结果，不阻塞
+    pub fn poll_result(&self) -> Option<Vec<DuplicateGroup>> {
+        self.receiver.try_recv().ok()
+    }
+}
+
+/// 在后台线程查找重复文件，完成后调用 on_update，取消时不返回结果
+pub fn find_duplicates_in_background(files: Vec<(PathBuf, u64)>, on_update: impl Fn() + Send + 'static) -> DuplicateSearchHandle {
+    let (sender, receiver) = mpsc::channel();
+    let progress = Arc::new(DuplicateProgress::default());
+    let worker_progress = progress.clone();
+    thread::spawn(move || {
+        info!("开始查找重复文件，共 {} 个文件", files.len());
+        let groups = find_duplicates(files, &worker_progress);
+        if worker_progress.is_cancelled() {
+            info!("已取消查找重复文件");
+            return;
+        }
+        info!("查找重复文件结束，共 {} 组", groups.len());
+        //界面已关闭时接收端不存在，忽略发送失败
+        let _ = sender.send(groups);
+        on_update();
+    });
+    DuplicateSearchHandle {
+        receiver,
+        progress,
+    }
+}
+
+/// 查找重复文件：先按大小分组，再按开头部分内容的哈希分组，最后按完整内容的哈希分组
+///
+/// 空文件、读取失败的文件会被跳过，同一文件的多个硬链接只算一个，结果按可释放空间倒序排列
+pub fn find_duplicates(files: Vec<(PathBuf, u64)>, progress: &DuplicateProgress) -> Vec<DuplicateGroup> {
+    let mut by_size: HashMap<u64, Vec<PathBuf>> = HashMap::new();
+    for (path, size) in files {
+        if size > 0 {
+            by_size.entry(size).or_default().push(path);
+        }
+    }
+    by_size.retain(|_, paths| paths.len() > 1);
+    progress.candidates.store(by_size.values().map(|paths| paths.len() as u64).sum(), Ordering::Relaxed);
+
+    let mut groups = vec![];
+    for (size, paths) in by_size {
+        let paths = remove_hard_links(paths);
+        for (hash, paths) in group_by_hash(paths, progress, partial_hash) {
+            //文件不超过部分哈希的长度时，部分哈希就是完整内容的哈希
+            let full_hash_groups = if size as usize <= PARTIAL_HASH_SIZE {
+                vec![(hash, paths)]
+            } else {
+                group_by_hash(paths, progress, full_hash)
+            };
+            for (hash, mut paths) in full_hash_groups {
+                paths.sort();
+                groups.push(DuplicateGroup { size, hash, files: paths });
+            }
+        }
+    }
+    if progress.is_cancelled() {
+        return vec![];
+    }
+    groups.sort_by(|a, b| b.reclaimable().cmp(&a.reclaimable()).then_with(|| a.files.cmp(&b.files)));
+    groups
+}
+
+/// 按哈希分组，只返回包含多个文件的组
+fn group_by_hash(paths: Vec<PathBuf>, progress: &DuplicateProgress, hash: fn(&Path) -> Result<String, DuplicateError>) -> Vec<(String, Vec<PathBuf>)> {
+    let mut by_hash: HashMap<String, Vec<PathBuf>> = HashMap::new();
+    for path in paths {
+        if progress.is_cancelled() {
+            return vec![];
+        }
+        progress.hashed.fetch_add(1, Ordering::Relaxed);
+        match hash(&path) {
+            Ok(hash) => by_hash.entry(hash).or_default().push(path),
+            Err(e) => error!("{e}"),
+        }
+    }
+    by_hash.into_iter().filter(|(_, paths)| paths.len() > 1).collect()
+}
+
+/// 去掉指向同一文件的硬链接，它们不占用额外空间
+fn remove_hard_links(paths: Vec<PathBuf>) -> Vec<PathBuf> {
+    let mut seen = HashSet::new();
+    paths.into_iter()
+        .filter(|path| match file_id(path) {
+            Some(id) => seen.insert(id),
+            None => true,
+        })
+        .collect()
+}
+
+#[cfg(unix)]
+fn file_id(path: &Path) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn file_id(_path: &Path) -> Option<(u64, u64)> {
+    None
+}
+
+/// 文件开头部分内容的哈希
+fn partial_hash(path: &Path) -> Result<String, DuplicateError> {
+    let mut file = open(path)?;
+    let mut buffer = [0u8; PARTIAL_HASH_SIZE];
+    let mut read = 0;
+    while read < buffer.len() {
+        match file.read(&mut buffer[read..]) {
+            Ok(0) => break,
+            Ok(size) => read += size,
+            Err(source) => return Err(read_error(path, source)),
+        }
+    }
+    Ok(blake3::hash(&buffer[..read]).to_hex().to_string())
+}
+
+/// 完整内容的哈希
+pub fn full_hash(path: &Path) -> Result<String, DuplicateError> {
+    let mut hasher = blake3::Hasher::new();
+    let file = open(path)?;
+    hasher.update_reader(file).map_err(|source| read_error(path, source))?;
+    Ok(hasher.finalize().to_hex().to_string())
+}
+
+fn open(path: &Path) -> Result<File, DuplicateError> {
+    File::open(path).map_err(|source| read_error(path, source))
+}
+
+fn read_error(path: &Path, source: io::Error) -> DuplicateError {
+    DuplicateError::Read {
+        path: path.to_path_buf(),
+        source,
+    }
+}
+
+/// 对重复文件的处理方式
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum ResolveKind {
+    /// 移到回收站，可以撤销
+    Trash,
+    HardLink,
+}
+
+impl ResolveKind {
+    pub fn as_label(&self) -> &str {
+        match self {
+            ResolveKind::Trash => "移到回收站",
+            ResolveKind::HardLink => "替换为硬链接",
+        }
+    }
+}
+
+/// 处理结果，取消时只包含已处理的文件
+#[derive(Default, Debug)]
+pub struct ResolveOutcome {
+    /// 已成功处理的重复文件
+    pub resolved: Vec<PathBuf>,
+    pub errors: Vec<DuplicateError>,
+}
+
+/// 处理进度，工作线程更新，界面线程读取
+#[derive(Default, Debug)]
+pub struct ResolveProgress {
+    /// 已确认内容并处理完的文件数，包括失败的
+    processed: AtomicU64,
+    cancelled: AtomicBool,
+}
+
+impl ResolveProgress {
+    pub fn processed(&self) -> u64 {
+        self.processed.load(Ordering::Relaxed)
+    }
+
+    /// 请求取消处理，正在处理的文件完成后退出
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// 后台处理的句柄
+pub struct ResolveHandle {
+    receiver: Receiver<ResolveOutcome>,
+    progress: Arc<ResolveProgress>,
+}
+
+impl ResolveHandle {
+    pub fn progress(&self) -> &ResolveProgress {
+        &self.progress
+    }
+
+    pub fn cancel(&self) {
+        self.progress.cancel();
+    }
+
+    /// 处理完成或取消后返回结果，不阻塞
+    pub fn poll_result(&self) -> Option<ResolveOutcome> {
+        self.receiver.try_recv().ok()
+    }
+}
+
+/// 在后台线程处理重复文件，完成后调用 on_update，取消时也返回已处理的部分
+pub fn resolve_duplicates_in_background(
+    kind: ResolveKind,
+    kept: PathBuf,
+    duplicates: Vec<PathBuf>,
+    on_update: impl Fn() + Send + 'static,
+) -> ResolveHandle {
+    let (sender, receiver) = mpsc::channel();
+    let progress = Arc::new(ResolveProgress::default());
+    let worker_progress = progress.clone();
+    thread::spawn(move || {
+        let outcome = resolve_duplicates(kind, &kept, &duplicates, &worker_progress);
+        info!("已{} {} 个重复文件，保留:{kept:?}", kind.as_label(), outcome.resolved.len());
+        //界面已关闭时接收端不存在，忽略发送失败
+        let _ = sender.send(outcome);
+        on_update();
+    });
+    ResolveHandle {
+        receiver,
+        progress,
+    }
+}
+
+/// 把重复文件移到回收站或替换为指向 kept 的硬链接
+///
+/// 保留的文件只计算一次哈希，每个重复文件处理前再次确认内容相同，避免查找后文件被修改导致误删
+pub fn resolve_duplicates(kind: ResolveKind, kept: &Path, duplicates: &[PathBuf], progress: &ResolveProgress) -> ResolveOutcome {
+    let mut outcome = ResolveOutcome::default();
+    let kept_hash = match full_hash(kept) {
+        Ok(hash) => hash,
+        Err(e) => {
+            outcome.errors.push(e);
+            return outcome;
+        }
+    };
+    for duplicate in duplicates {
+        if progress.is_cancelled() {
+            break;
+        }
+        let result = ensure_same_content(&kept_hash, duplicate).and_then(|()| match kind {
+            ResolveKind::Trash => move_to_trash(duplicate).map_err(DuplicateError::from),
+            ResolveKind::HardLink => replace_with_hard_link(kept, duplicate),
+        });
+        match result {
+            Ok(()) => outcome.resolved.push(duplicate.clone()),
+            Err(e) => outcome.errors.push(e),
+        }
+        progress.processed.fetch_add(1, Ordering::Relaxed);
+    }
+    outcome
+}
+
+fn ensure_same_content(kept_hash: &str, duplicate: &Path) -> Result<(), DuplicateError> {
+    if full_hash(duplicate)? != kept_hash {
+        return Err(DuplicateError::ContentChanged {
+            path: duplicate.to_path_buf(),
+        });
+    }
+    Ok(())
+}
+
+/// 把重复文件替换为指向 kept 的硬链接，两个文件需要在同一个文件系统
+///
+/// 先在同目录创建临时硬链接再覆盖，失败时原文件保持不变
+fn replace_with_hard_link(kept: &Path, duplicate: &Path) -> Result<(), DuplicateError> {
+    let write_error = |source| DuplicateError::Write {
+        path: duplicate.to_path_buf(),
+        source,
+    };
+    let file_name = duplicate.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+    let temp_path = duplicate.with_file_name(format!(".{file_name}.{}.link", uuid::Uuid::new_v4()));
+    fs::hard_link(kept, &temp_path).map_err(write_error)?;
+    if let Err(e) = fs::rename(&temp_path, duplicate) {
+        let _ = fs::remove_file(&temp_path);
+        return Err(write_error(e));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn files_of(dir: &Path, names: &[&str]) -> Vec<(PathBuf, u64)> {
+        names.iter()
+            .map(|name| {
+                let path = dir.join(name);
+                let size = fs::metadata(&path).unwrap().len();
+                (path, size)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn find_duplicates_groups_identical_content() {
+        let dir = tempfile::tempdir().unwrap();
+        let large = "x".repeat(PARTIAL_HASH_SIZE * 2);
+        fs::write(dir.path().join("a.bin"), &large).unwrap();
+        fs::write(dir.path().join("b.bin"), &large).unwrap();
+        //大小相同、开头相同，结尾不同
+        fs::write(dir.path().join("c.bin"), format!("{}y", &large[1..])).unwrap();
+        fs::write(dir.path().join("d.txt"), "hello").unwrap();
+        fs::write(dir.path().join("e.txt"), "hello").unwrap();
+        fs::write(dir.path().join("f.txt"), "world").unwrap();
+        fs::write(dir.path().join("empty1"), "").unwrap();
+        fs::write(dir.path().join("empty2"), "").unwrap();
+
+        let names = ["a.bin", "b.bin", "c.bin", "d.txt", "e.txt", "f.txt", "empty1", "empty2"];
+        let groups = find_duplicates(files_of(dir.path(), &names), &DuplicateProgress::default());
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].files, vec![dir.path().join("a.bin"), dir.path().join("b.bin")]);
+        assert_eq!(groups[0].reclaimable(), large.len() as u64);
+        assert_eq!(groups[1].files, vec![dir.path().join("d.txt"), dir.path().join("e.txt")]);
+    }
+
+    #[test]
+    fn find_duplicates_returns_nothing_when_cancelled() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.txt"), "same").unwrap();
+        fs::write(dir.path().join("b.txt"), "same").unwrap();
+        let progress = DuplicateProgress::default();
+        progress.cancel();
+        assert!(find_duplicates(files_of(dir.path(), &["a.txt", "b.txt"]), &progress).is_empty());
+    }
+
+    #[test]
+    fn replace_with_hard_link_keeps_content() {
+        let dir = tempfile::tempdir().unwrap();
+        let kept = dir.path().join("a.txt");
+        let duplicate = dir.path().join("b.txt");
+        fs::write(&kept, "same").unwrap();
+        fs::write(&duplicate, "same").unwrap();
+        let outcome = resolve_duplicates(ResolveKind::HardLink, &kept, std::slice::from_ref(&duplicate), &ResolveProgress::default());
+        assert_eq!(outcome.resolved, vec![duplicate.clone()]);
+        assert_eq!(fs::read_to_string(&duplicate).unwrap(), "same");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 2);
+        //已经是硬链接，不再算重复
+        let groups = find_duplicates(files_of(dir.path(), &["a.txt", "b.txt"]), &DuplicateProgress::default());
+        if cfg!(unix) {
+            assert!(groups.is_empty());
+        }
+    }
+
+    #[test]
+    fn resolve_duplicates_skips_changed_content() {
+        let dir = tempfile::tempdir().unwrap();
+        let kept = dir.path().join("a.txt");
+        let changed = dir.path().join("b.txt");
+        let same = dir.path().join("c.txt");
+        fs::write(&kept, "same").unwrap();
+        fs::write(&changed, "diff").unwrap();
+        fs::write(&same, "same").unwrap();
+        let progress = ResolveProgress::default();
+        let outcome = resolve_duplicates(ResolveKind::HardLink, &kept, &[changed.clone(), same.clone()], &progress);
+        assert!(matches!(outcome.errors[..], [DuplicateError::ContentChanged { .. }]));
+        assert_eq!(outcome.resolved, vec![same.clone()]);
+        assert_eq!(progress.processed(), 2);
+        assert_eq!(fs::read_to_string(&changed).unwrap(), "diff");
+    }
+
+    #[test]
+    fn resolve_duplicates_stops_when_cancelled() {
+        let dir = tempfile::tempdir().unwrap();
+        let kept = dir.path().join("a.txt");
+        let duplicate = dir.path().join("b.txt");
+        fs::write(&kept, "same").unwrap();
+        fs::write(&duplicate, "same").unwrap();
+        let progress = ResolveProgress::default();
+        progress.cancel();
+        let outcome = resolve_duplicates(ResolveKind::Trash, &kept, std::slice::from_ref(&duplicate), &progress);
+        assert!(outcome.resolved.is_empty());
+        assert!(duplicate.exists());
+    }
+}
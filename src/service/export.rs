@@ -0,0 +1,176 @@
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::service::folder_scan::FileInfo;
+use crate::util::file_util::human_readable_size;
+
+/// 导出错误
+#[derive(Error, Debug)]
+pub enum ExportError {
+    #[error("生成 CSV 失败:{0}")]
+    Csv(#[from] csv::Error),
+    #[error("写入 CSV 失败:{0}")]
+    Io(#[from] std::io::Error),
+    #[error("生成 JSON 失败:{0}")]
+    Json(#[from] serde_json::Error),
+    #[error("导出内容不是 UTF-8:{0}")]
+    Utf8(#[from] std::string::FromUtf8Error),
+}
+
+/// 导出格式
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+    Markdown,
+}
+
+impl ExportFormat {
+    pub fn iter() -> impl Iterator<Item = ExportFormat> {
+        [ExportFormat::Csv, ExportFormat::Json, ExportFormat::Markdown].into_iter()
+    }
+
+    pub fn as_label(&self) -> &str {
+        match self {
+            ExportFormat::Csv => "CSV",
+            ExportFormat::Json => "JSON",
+            ExportFormat::Markdown => "Markdown",
+        }
+    }
+
+    /// 保存文件时使用的扩展名
+    pub fn extension(&self) -> &str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+            ExportFormat::Markdown => "md",
+        }
+    }
+}
+
+/// 导出的一行，比 [`FileInfo`] 多一列便于阅读的大小
+#[derive(Serialize)]
+struct ExportRow<'a> {
+    name: &'a str,
+    path: &'a str,
+    is_file: bool,
+    size: u64,
+    size_human: String,
+    /// 占用的磁盘空间
+    allocated_size: u64,
+    modified: &'a str,
+}
+
+impl<'a> From<&'a FileInfo> for ExportRow<'a> {
+    fn from(file: &'a FileInfo) -> Self {
+        Self {
+            name: &file.file_name,
+            path: &file.file_path,
+            is_file: file.is_file,
+            size: file.file_size,
+            size_human: human_readable_size(file.file_size),
+            allocated_size: file.allocated_size,
+            modified: &file.update_time,
+        }
+    }
+}
+
+/// 把文件列表转换为指定格式的文本
+pub fn export_files(files: &[FileInfo], format: ExportFormat) -> Result<String, ExportError> {
+    let rows: Vec<ExportRow> = files.iter().map(ExportRow::from).collect();
+    match format {
+        ExportFormat::Csv => to_csv(&rows),
+        ExportFormat::Json => Ok(serde_json::to_string_pretty(&rows)?),
+        ExportFormat::Markdown => Ok(to_markdown(&rows)),
+    }
+}
+
+fn to_csv(rows: &[ExportRow]) -> Result<String, ExportError> {
+    let mut writer = csv::Writer::from_writer(vec![]);
+    //没有数据时也输出表头
+    if rows.is_empty() {
+        writer.write_record(["name", "path", "is_file", "size", "size_human", "allocated_size", "modified"])?;
+    }
+    for row in rows {
+        writer.serialize(row)?;
+    }
+    let bytes = writer.into_inner().map_err(|e| e.into_error())?;
+    Ok(String::from_utf8(bytes)?)
+}
+
+fn to_markdown(rows: &[ExportRow]) -> String {
+    let mut markdown = String::from("| 文件名 | 文件路径 | 文件 | 大小(字节) | 大小 | 占用空间(字节) | 修改时间 |\n");
+    markdown.push_str("| --- | --- | --- | ---: | ---: | ---: | --- |\n");
+    for row in rows {
+        markdown.push_str(&format!(
+            "| {} | {} | {} | {} | {} | {} | {} |\n",
+            escape_markdown(row.name),
+            escape_markdown(row.path),
+            if row.is_file { "是" } else { "否" },
+            row.size,
+            row.size_human,
+            row.allocated_size,
+            row.modified
+        ));
+    }
+    markdown
+}
+
+/// 表格单元格中的 | 和换行会破坏表格
+fn escape_markdown(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('|', "\\|").replace(['\r', '\n'], " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_files() -> Vec<FileInfo> {
+        vec![
+            FileInfo {
+                file_name: "a,b.txt".to_string(),
+                file_path: "/data/a,b.txt".to_string(),
+                file_size: 2048,
+                allocated_size: 4096,
+                update_time: "2024-01-02 03:04:05".to_string(),
+                is_file: true,
+            },
+            FileInfo {
+                file_name: "x|y".to_string(),
+                file_path: "/data/x|y".to_string(),
+                file_size: 0,
+                allocated_size: 0,
+                update_time: "--".to_string(),
+                is_file: false,
+            },
+        ]
+    }
+
+    #[test]
+    fn export_csv_quotes_fields() {
+        let csv = export_files(&test_files(), ExportFormat::Csv).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "name,path,is_file,size,size_human,allocated_size,modified");
+        assert_eq!(lines[1], "\"a,b.txt\",\"/data/a,b.txt\",true,2048,2.00 KiB,4096,2024-01-02 03:04:05");
+        assert_eq!(lines.len(), 3);
+        assert_eq!(export_files(&[], ExportFormat::Csv).unwrap().lines().count(), 1);
+    }
+
+    #[test]
+    fn export_json_contains_all_columns() {
+        let json = export_files(&test_files(), ExportFormat::Json).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value[0]["size"], 2048);
+        assert_eq!(value[0]["size_human"], "2.00 KiB");
+        assert_eq!(value[1]["is_file"], false);
+        assert_eq!(value[1]["modified"], "--");
+    }
+
+    #[test]
+    fn export_markdown_escapes_pipes() {
+        let markdown = export_files(&test_files(), ExportFormat::Markdown).unwrap();
+        let lines: Vec<&str> = markdown.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[3], "| x\\|y | /data/x\\|y | 否 | 0 | 0.00 B | 0 | -- |");
+    }
+}
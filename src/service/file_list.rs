@@ -0,0 +1,259 @@
+use std::cmp::Ordering;
+
+use crate::service::folder_scan::{FileInfo, FileNode};
+
+/// 排序列
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum SortColumn {
+    Name,
+    Path,
+    /// 文件夹在前，文件按扩展名
+    Type,
+    Size,
+    /// 占用的磁盘空间
+    AllocatedSize,
+    ModifiedTime,
+}
+
+impl SortColumn {
+    /// 第一次点击该列时的排序方向，大小、时间默认从大到小
+    pub fn default_descending(&self) -> bool {
+        matches!(self, SortColumn::Size | SortColumn::AllocatedSize | SortColumn::ModifiedTime)
+    }
+}
+
+/// 文件列表排序方式，排序值相同时始终按名称正序
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct FileSort {
+    pub column: SortColumn,
+    pub descending: bool,
+}
+
+impl FileSort {
+    /// 按大小倒序
+    pub const SIZE_DESC: FileSort = FileSort { column: SortColumn::Size, descending: true };
+    /// 按名称正序
+    pub const NAME: FileSort = FileSort { column: SortColumn::Name, descending: false };
+
+    /// 点击列头：同一列切换方向，其他列使用默认方向
+    pub fn toggle(&mut self, column: SortColumn) {
+        if self.column == column {
+            self.descending = !self.descending;
+            return;
+        }
+        self.column = column;
+        self.descending = column.default_descending();
+    }
+
+    pub fn compare_nodes(&self, a: &FileNode, b: &FileNode) -> Ordering {
+        let ordering = match self.column {
+            SortColumn::Name => a.name.cmp(&b.name),
+            SortColumn::Path => a.path.cmp(&b.path),
+            SortColumn::Type => compare_type(&a.name, a.is_file, &b.name, b.is_file),
+            SortColumn::Size => a.size.cmp(&b.size),
+            SortColumn::AllocatedSize => a.allocated_size.cmp(&b.allocated_size),
+            SortColumn::ModifiedTime => a.modified.cmp(&b.modified),
+        };
+        self.apply_direction(ordering).then_with(|| a.name.cmp(&b.name))
+    }
+
+    pub fn compare_files(&self, a: &FileInfo, b: &FileInfo) -> Ordering {
+        let ordering = match self.column {
+            SortColumn::Name => a.file_name.cmp(&b.file_name),
+            SortColumn::Path => a.file_path.cmp(&b.file_path),
+            SortColumn::Type => compare_type(&a.file_name, a.is_file, &b.file_name, b.is_file),
+            SortColumn::Size => a.file_size.cmp(&b.file_size),
+            SortColumn::AllocatedSize => a.allocated_size.cmp(&b.allocated_size),
+            //格式为 yyyy-MM-dd HH:mm:ss，按字符串比较即可
+            SortColumn::ModifiedTime => a.update_time.cmp(&b.update_time),
+        };
+        self.apply_direction(ordering).then_with(|| a.file_name.cmp(&b.file_name))
+    }
+
+    fn apply_direction(&self, ordering: Ordering) -> Ordering {
+        if self.descending {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    }
+}
+
+/// 排序文件列表
+pub fn sort_files(files: &mut [FileInfo], sort: FileSort) {
+    files.sort_by(|a, b| sort.compare_files(a, b));
+}
+
+/// 排序文件树节点，只排序当前层
+pub fn sort_nodes(nodes: &mut [FileNode], sort: FileSort) {
+    nodes.sort_by(|a, b| sort.compare_nodes(a, b));
+}
+
+/// 类型展示文字：文件夹、扩展名，没有扩展名的文件显示为 文件
+pub fn file_type_label(name: &str, is_file: bool) -> String {
+    if !is_file {
+        return "文件夹".to_string();
+    }
+    file_extension(name).unwrap_or_else(|| "文件".to_string())
+}
+
+/// 小写的扩展名，没有扩展名时返回 None
+pub fn file_extension(name: &str) -> Option<String> {
+    match name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() && !extension.is_empty() => Some(extension.to_lowercase()),
+        _ => None,
+    }
+}
+
+fn compare_type(a_name: &str, a_is_file: bool, b_name: &str, b_is_file: bool) -> Ordering {
+    a_is_file.cmp(&b_is_file)
+        .then_with(|| file_extension(a_name).cmp(&file_extension(b_name)))
+}
+
+/// 按类型过滤
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum EntryKindFilter {
+    All,
+    FilesOnly,
+    DirsOnly,
+}
+
+/// 文件列表过滤条件
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct FileFilter {
+    /// 文件名关键字或扩展名，多个条件用空格、逗号分隔，满足任一即可，例如 `*.log .txt report`
+    pub pattern: String,
+    pub kind: EntryKindFilter,
+}
+
+impl Default for FileFilter {
+    fn default() -> Self {
+        Self {
+            pattern: "".to_string(),
+            kind: EntryKindFilter::All,
+        }
+    }
+}
+
+impl FileFilter {
+    /// 是否没有任何过滤条件
+    pub fn is_empty(&self) -> bool {
+        self.pattern.trim().is_empty() && self.kind == EntryKindFilter::All
+    }
+
+    pub fn matches(&self, name: &str, is_file: bool) -> bool {
+        let kind_matches = match self.kind {
+            EntryKindFilter::All => true,
+            EntryKindFilter::FilesOnly => is_file,
+            EntryKindFilter::DirsOnly => !is_file,
+        };
+        if !kind_matches {
+            return false;
+        }
+        let name = name.to_lowercase();
+        let mut terms = self.pattern
+            .split(|c: char| c.is_whitespace() || c == ',' || c == '，')
+            .filter(|term| !term.is_empty())
+            .peekable();
+        if terms.peek().is_none() {
+            return true;
+        }
+        terms.any(|term| {
+            let term = term.to_lowercase();
+            //*.log、.log 按扩展名匹配，其他按文件名包含匹配
+            match term.strip_prefix('*').unwrap_or(&term).strip_prefix('.') {
+                Some(extension) if !extension.is_empty() => name.ends_with(&format!(".{extension}")),
+                _ => name.contains(term.trim_matches('*')),
+            }
+        })
+    }
+
+    pub fn matches_node(&self, node: &FileNode) -> bool {
+        self.matches(&node.name, node.is_file)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(name: &str, size: u64, update_time: &str, is_file: bool) -> FileInfo {
+        FileInfo {
+            file_name: name.to_string(),
+            file_path: format!("/data/{name}"),
+            file_size: size,
+            allocated_size: size,
+            update_time: update_time.to_string(),
+            is_file,
+        }
+    }
+
+    fn names(files: &[FileInfo]) -> Vec<&str> {
+        files.iter().map(|file| file.file_name.as_str()).collect()
+    }
+
+    fn test_files() -> Vec<FileInfo> {
+        vec![
+            file("b.log", 10, "2024-01-02 00:00:00", true),
+            file("target", 300, "2024-01-01 00:00:00", false),
+            file("a.txt", 10, "2024-03-01 00:00:00", true),
+            file("README", 5, "--", true),
+        ]
+    }
+
+    #[test]
+    fn sort_by_size_desc_then_name() {
+        let mut files = test_files();
+        sort_files(&mut files, FileSort::SIZE_DESC);
+        assert_eq!(names(&files), vec!["target", "a.txt", "b.log", "README"]);
+    }
+
+    #[test]
+    fn sort_by_modified_time() {
+        let mut files = test_files();
+        sort_files(&mut files, FileSort { column: SortColumn::ModifiedTime, descending: false });
+        assert_eq!(names(&files), vec!["README", "target", "b.log", "a.txt"]);
+    }
+
+    #[test]
+    fn sort_by_type_puts_dirs_first_then_extension() {
+        let mut files = test_files();
+        sort_files(&mut files, FileSort { column: SortColumn::Type, descending: false });
+        assert_eq!(names(&files), vec!["target", "README", "b.log", "a.txt"]);
+    }
+
+    #[test]
+    fn toggle_switches_direction_on_same_column() {
+        let mut sort = FileSort::NAME;
+        sort.toggle(SortColumn::Name);
+        assert!(sort.descending);
+        sort.toggle(SortColumn::Size);
+        assert_eq!(sort, FileSort::SIZE_DESC);
+    }
+
+    #[test]
+    fn filter_matches_extension_and_keyword() {
+        let filter = FileFilter { pattern: "*.LOG, read".to_string(), kind: EntryKindFilter::All };
+        assert!(filter.matches("b.log", true));
+        assert!(filter.matches("README", true));
+        assert!(!filter.matches("a.txt", true));
+        assert!(!filter.matches("catalog", true));
+    }
+
+    #[test]
+    fn filter_by_kind() {
+        let files_only = FileFilter { pattern: "".to_string(), kind: EntryKindFilter::FilesOnly };
+        assert!(files_only.matches("a.txt", true));
+        assert!(!files_only.matches("target", false));
+        let dirs_only = FileFilter { pattern: "tar".to_string(), kind: EntryKindFilter::DirsOnly };
+        assert!(dirs_only.matches("target", false));
+        assert!(!dirs_only.matches("tar.gz", true));
+    }
+
+    #[test]
+    fn file_type_label_uses_extension() {
+        assert_eq!(file_type_label("a.TXT", true), "txt");
+        assert_eq!(file_type_label(".gitignore", true), "文件");
+        assert_eq!(file_type_label("src", false), "文件夹");
+    }
+}
@@ -0,0 +1,480 @@
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsString;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Arc;
+use std::thread;
+
+use log::info;
+use md5::Md5;
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+/// 计算哈希时每次读取的字节数
+const HASH_BUFFER_SIZE: usize = 64 * 1024;
+
+/// 文件操作错误
+#[derive(Error, Debug)]
+pub enum FileOpError {
+    #[error("文件名:{0} 无效，不能为空，也不能包含路径分隔符")]
+    InvalidName(String),
+    #[error("{0:?} 已存在")]
+    AlreadyExists(PathBuf),
+    #[error("重命名:{path:?} 失败:{source}")]
+    Rename {
+        path: PathBuf,
+        source: io::Error,
+    },
+    #[error("移动:{path:?} 到回收站失败:{source}")]
+    Trash {
+        path: PathBuf,
+        source: trash::Error,
+    },
+    #[error("移动:{path:?} 到回收站失败:{message}")]
+    TrashAll {
+        path: PathBuf,
+        message: String,
+    },
+    #[error("{0:?} 不存在")]
+    NotFound(PathBuf),
+    #[error("从回收站恢复:{path:?} 失败:{message}")]
+    Restore {
+        path: PathBuf,
+        message: String,
+    },
+    #[error("读取文件:{path:?} 失败:{source}")]
+    Read {
+        path: PathBuf,
+        source: io::Error,
+    },
+}
+
+/// 在同一文件夹下重命名文件或文件夹，返回新路径，不会覆盖已存在的文件
+pub fn rename_entry(path: &Path, new_name: &str) -> Result<PathBuf, FileOpError> {
+    let new_name = new_name.trim();
+    if new_name.is_empty() || new_name == "." || new_name == ".." || new_name.contains(['/', '\\']) {
+        return Err(FileOpError::InvalidName(new_name.to_string()));
+    }
+    let new_path = path.with_file_name(new_name);
+    if new_path == path {
+        return Ok(new_path);
+    }
+    //只改大小写时，不区分大小写的文件系统上新路径指向原文件本身，不算已存在
+    if new_path.symlink_metadata().is_ok() && !is_same_entry(path, &new_path) {
+        return Err(FileOpError::AlreadyExists(new_path));
+    }
+    std::fs::rename(path, &new_path).map_err(|source| FileOpError::Rename {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    info!("已重命名:{path:?} 为:{new_path:?}");
+    Ok(new_path)
+}
+
+/// 两个路径是否指向同一个文件、文件夹，不跟随符号链接
+#[cfg(unix)]
+fn is_same_entry(a: &Path, b: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    match (a.symlink_metadata(), b.symlink_metadata()) {
+        (Ok(a), Ok(b)) => a.dev() == b.dev() && a.ino() == b.ino(),
+        _ => false,
+    }
+}
+
+#[cfg(not(unix))]
+fn is_same_entry(a: &Path, b: &Path) -> bool {
+    match (std::fs::canonicalize(a), std::fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+/// 移动到系统回收站
+pub fn move_to_trash(path: &Path) -> Result<(), FileOpError> {
+    trash::delete(path).map_err(|source| FileOpError::Trash {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    info!("已移动到回收站:{path:?}");
+    Ok(())
+}
+
+/// 批量移到回收站或从回收站恢复的结果
+#[derive(Default, Debug)]
+pub struct TrashOutcome {
+    /// 已处理的路径
+    pub done: Vec<PathBuf>,
+    /// 恢复失败但仍在回收站中，可以稍后再次恢复的路径
+    pub retry: Vec<PathBuf>,
+    pub errors: Vec<FileOpError>,
+}
+
+/// 后台批量处理回收站的句柄
+pub struct TrashHandle {
+    receiver: Receiver<TrashOutcome>,
+}
+
+impl TrashHandle {
+    /// 处理完成时返回结果，不阻塞
+    pub fn poll_result(&self) -> Option<TrashOutcome> {
+        self.receiver.try_recv().ok()
+    }
+}
+
+fn run_in_background(work: impl FnOnce() -> TrashOutcome + Send + 'static, on_update: impl Fn() + Send + 'static) -> TrashHandle {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        //界面已关闭时接收端不存在，忽略发送失败
+        let _ = sender.send(work());
+        on_update();
+    });
+    TrashHandle { receiver }
+}
+
+/// 在后台线程把多个文件、文件夹一次移到回收站，完成后调用 on_update
+pub fn move_all_to_trash_in_background(paths: Vec<PathBuf>, on_update: impl Fn() + Send + 'static) -> TrashHandle {
+    run_in_background(move || move_all_to_trash(&paths), on_update)
+}
+
+/// 在后台线程从回收站恢复多个文件、文件夹，完成后调用 on_update
+pub fn restore_all_from_trash_in_background(paths: Vec<PathBuf>, on_update: impl Fn() + Send + 'static) -> TrashHandle {
+    run_in_background(move || restore_all_from_trash(&paths), on_update)
+}
+
+/// 一次把多个文件、文件夹移到回收站，上级文件夹也在列表中的路径会随上级一起移走
+pub fn move_all_to_trash(paths: &[PathBuf]) -> TrashOutcome {
+    let mut outcome = TrashOutcome::default();
+    //调用前已经不存在的路径不能算作移走
+    let (paths, missing): (Vec<PathBuf>, Vec<PathBuf>) = without_nested(paths).into_iter().partition(|path| path.symlink_metadata().is_ok());
+    outcome.errors.extend(missing.into_iter().map(FileOpError::NotFound));
+    if paths.is_empty() {
+        return outcome;
+    }
+    let result = trash::delete_all(&paths);
+    //失败时可能已经移走了一部分，没有移走的都报告错误
+    let (done, remaining): (Vec<PathBuf>, Vec<PathBuf>) = paths.into_iter().partition(|path| path.symlink_metadata().is_err());
+    if let Err(e) = result {
+        outcome.errors.extend(remaining.into_iter().map(|path| FileOpError::TrashAll {
+            path,
+            message: e.to_string(),
+        }));
+    }
+    info!("已移动 {} 项到回收站", done.len());
+    outcome.done = done;
+    outcome
+}
+
+/// 去掉上级文件夹也在列表中的路径
+fn without_nested(paths: &[PathBuf]) -> Vec<PathBuf> {
+    let all: HashSet<&Path> = paths.iter().map(PathBuf::as_path).collect();
+    paths.iter()
+        .filter(|path| !path.ancestors().skip(1).any(|ancestor| all.contains(ancestor)))
+        .cloned()
+        .collect()
+}
+
+/// 从回收站恢复到原位置，回收站中有多个同路径的项目时恢复最后删除的
+///
+/// 只读取一次回收站列表，再一次恢复所有找到的项目
+#[cfg(any(target_os = "windows", all(unix, not(target_os = "macos"), not(target_os = "ios"), not(target_os = "android"))))]
+pub fn restore_all_from_trash(paths: &[PathBuf]) -> TrashOutcome {
+    let restore_error = |path: &Path, message: String| FileOpError::Restore {
+        path: path.to_path_buf(),
+        message,
+    };
+    let mut outcome = TrashOutcome::default();
+    let items = match trash::os_limited::list() {
+        Ok(items) => items,
+        Err(e) => {
+            outcome.errors.extend(paths.iter().map(|path| restore_error(path, e.to_string())));
+            outcome.retry = paths.to_vec();
+            return outcome;
+        }
+    };
+    let mut latest: HashMap<PathBuf, trash::TrashItem> = HashMap::new();
+    for item in items {
+        let original_path = item.original_path();
+        if !paths.contains(&original_path) {
+            continue;
+        }
+        if latest.get(&original_path).is_none_or(|other| other.time_deleted < item.time_deleted) {
+            latest.insert(original_path, item);
+        }
+    }
+    let (found, missing): (Vec<&PathBuf>, Vec<&PathBuf>) = paths.iter().partition(|path| latest.contains_key(*path));
+    for path in missing {
+        outcome.errors.push(restore_error(path, "回收站中找不到，可能已被清空".to_string()));
+    }
+    //没有恢复的项目和原因
+    let mut failed: HashMap<PathBuf, String> = HashMap::new();
+    let mut items: Vec<trash::TrashItem> = latest.into_values().collect();
+    while !items.is_empty() {
+        let batch: Vec<(OsString, PathBuf)> = items.iter().map(|item| (item.id.clone(), item.original_path())).collect();
+        match trash::os_limited::restore_all(items) {
+            Ok(()) => break,
+            //遇到原位置已存在的项目时中止，跳过该项目继续恢复其余项目
+            Err(trash::Error::RestoreCollision { path, mut remaining_items }) => {
+                if remaining_items.is_empty() {
+                    break;
+                }
+                let collided = remaining_items.remove(0);
+                failed.insert(collided.original_path(), format!("原位置:{path:?} 已存在同名文件或文件夹"));
+                items = remaining_items;
+            }
+            Err(trash::Error::RestoreTwins { path, items: twins }) => {
+                for item in twins {
+                    failed.insert(item.original_path(), format!("回收站中有多个:{path:?}"));
+                }
+                break;
+            }
+            //不知道恢复到哪一项，重新读取回收站列表，仍在回收站中的算作失败
+            Err(e) => {
+                let still_trashed: HashSet<OsString> = match trash::os_limited::list() {
+                    Ok(items) => items.into_iter().map(|item| item.id).collect(),
+                    Err(_) => batch.iter().map(|(id, _)| id.clone()).collect(),
+                };
+                for (id, path) in batch {
+                    if still_trashed.contains(&id) {
+                        failed.insert(path, e.to_string());
+                    }
+                }
+                break;
+            }
+        }
+    }
+    for path in found {
+        match failed.remove(path) {
+            Some(message) => {
+                outcome.errors.push(restore_error(path, message));
+                outcome.retry.push(path.clone());
+            }
+            None => outcome.done.push(path.clone()),
+        }
+    }
+    info!("已从回收站恢复 {} 项", outcome.done.len());
+    outcome
+}
+
+#[cfg(not(any(target_os = "windows", all(unix, not(target_os = "macos"), not(target_os = "ios"), not(target_os = "android")))))]
+pub fn restore_all_from_trash(paths: &[PathBuf]) -> TrashOutcome {
+    TrashOutcome {
+        done: vec![],
+        retry: vec![],
+        errors: paths.iter()
+            .map(|path| FileOpError::Restore {
+                path: path.clone(),
+                message: "当前系统不支持自动恢复，请在废纸篓中手动放回".to_string(),
+            })
+            .collect(),
+    }
+}
+
+/// 文件内容的常用哈希，均为小写十六进制
+#[derive(PartialEq, Clone, Debug)]
+pub struct FileHashes {
+    pub md5: String,
+    pub sha1: String,
+    pub sha256: String,
+    pub blake3: String,
+}
+
+impl FileHashes {
+    /// 算法名称和哈希值
+    pub fn entries(&self) -> [(&str, &str); 4] {
+        [
+            ("MD5", &self.md5),
+            ("SHA-1", &self.sha1),
+            ("SHA-256", &self.sha256),
+            ("BLAKE3", &self.blake3),
+        ]
+    }
+}
+
+/// 哈希计算进度，工作线程更新，界面线程读取
+#[derive(Default, Debug)]
+pub struct HashProgress {
+    bytes_read: AtomicU64,
+    cancelled: AtomicBool,
+}
+
+impl HashProgress {
+    pub fn bytes_read(&self) -> u64 {
+        self.bytes_read.load(Ordering::Relaxed)
+    }
+
+    /// 请求取消计算，工作线程会尽快退出
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// 后台计算哈希的句柄
+pub struct HashHandle {
+    receiver: Receiver<Result<FileHashes, FileOpError>>,
+    progress: Arc<HashProgress>,
+}
+
+impl HashHandle {
+    pub fn progress(&self) -> &HashProgress {
+        &self.progress
+    }
+
+    pub fn cancel(&self) {
+        self.progress.cancel();
+    }
+
+    /// 计算完成时返回结果，不阻塞
+    pub fn poll_result(&self) -> Option<Result<FileHashes, FileOpError>> {
+        self.receiver.try_recv().ok()
+    }
+}
+
+/// 在后台线程计算文件哈希，完成后调用 on_update，取消时不返回结果
+pub fn hash_file_in_background(path: PathBuf, on_update: impl Fn() + Send + 'static) -> HashHandle {
+    let (sender, receiver) = mpsc::channel();
+    let progress = Arc::new(HashProgress::default());
+    let worker_progress = progress.clone();
+    thread::spawn(move || {
+        let result = hash_file(&path, &worker_progress);
+        if worker_progress.is_cancelled() {
+            return;
+        }
+        //界面已关闭时接收端不存在，忽略发送失败
+        let _ = sender.send(result);
+        on_update();
+    });
+    HashHandle {
+        receiver,
+        progress,
+    }
+}
+
+/// 读取一遍文件，同时计算所有哈希，取消时返回已读取部分的哈希
+pub fn hash_file(path: &Path, progress: &HashProgress) -> Result<FileHashes, FileOpError> {
+    let read_error = |source| FileOpError::Read {
+        path: path.to_path_buf(),
+        source,
+    };
+    let mut file = File::open(path).map_err(read_error)?;
+    let mut md5 = Md5::new();
+    let mut sha1 = Sha1::new();
+    let mut sha256 = Sha256::new();
+    let mut blake3 = blake3::Hasher::new();
+    let mut buffer = vec![0; HASH_BUFFER_SIZE];
+    while !progress.is_cancelled() {
+        let read = match file.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(read_error(e)),
+        };
+        let chunk = &buffer[..read];
+        md5.update(chunk);
+        sha1.update(chunk);
+        sha256.update(chunk);
+        blake3.update(chunk);
+        progress.bytes_read.fetch_add(read as u64, Ordering::Relaxed);
+    }
+    Ok(FileHashes {
+        md5: to_hex(&md5.finalize()),
+        sha1: to_hex(&sha1.finalize()),
+        sha256: to_hex(&sha256.finalize()),
+        blake3: blake3.finalize().to_hex().to_string(),
+    })
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn rename_entry_keeps_folder_and_refuses_to_overwrite() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.txt"), "a").unwrap();
+        fs::write(dir.path().join("b.txt"), "b").unwrap();
+
+        let renamed = rename_entry(&dir.path().join("a.txt"), " c.txt ").unwrap();
+        assert_eq!(renamed, dir.path().join("c.txt"));
+        assert_eq!(fs::read_to_string(&renamed).unwrap(), "a");
+
+        assert!(matches!(rename_entry(&renamed, "b.txt"), Err(FileOpError::AlreadyExists(_))));
+        assert!(matches!(rename_entry(&renamed, "sub/d.txt"), Err(FileOpError::InvalidName(_))));
+        assert!(matches!(rename_entry(&renamed, ".."), Err(FileOpError::InvalidName(_))));
+        assert_eq!(fs::read_to_string(dir.path().join("b.txt")).unwrap(), "b");
+    }
+
+    #[test]
+    fn case_only_rename_does_not_overwrite_other_file() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.txt"), "lower").unwrap();
+        fs::write(dir.path().join("A.txt"), "upper").unwrap();
+        //不区分大小写的文件系统上两个名称是同一个文件，无法构造该场景
+        if fs::read_to_string(dir.path().join("a.txt")).unwrap() != "lower" {
+            return;
+        }
+        assert!(matches!(rename_entry(&dir.path().join("a.txt"), "A.txt"), Err(FileOpError::AlreadyExists(_))));
+        assert_eq!(fs::read_to_string(dir.path().join("A.txt")).unwrap(), "upper");
+        assert_eq!(fs::read_to_string(dir.path().join("a.txt")).unwrap(), "lower");
+    }
+
+    #[test]
+    fn nested_paths_are_trashed_with_their_parent() {
+        let paths = [PathBuf::from("/a/b"), PathBuf::from("/a"), PathBuf::from("/a/b/c"), PathBuf::from("/ab")];
+        assert_eq!(without_nested(&paths), vec![PathBuf::from("/a"), PathBuf::from("/ab")]);
+    }
+
+    #[test]
+    fn hash_file_computes_all_digests() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("abc.txt");
+        fs::write(&path, "abc").unwrap();
+        let progress = HashProgress::default();
+        let hashes = hash_file(&path, &progress).unwrap();
+        assert_eq!(hashes.md5, "900150983cd24fb0d6963f7d28e17f72");
+        assert_eq!(hashes.sha1, "a9993e364706816aba3e25717850c26c9cd0d89d");
+        assert_eq!(hashes.sha256, "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+        assert_eq!(hashes.blake3, "6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85");
+        assert_eq!(progress.bytes_read(), 3);
+        assert!(matches!(hash_file(&dir.path().join("missing"), &progress), Err(FileOpError::Read { .. })));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn restore_keeps_items_whose_original_path_was_recreated() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = dir.path().join("a.txt");
+        let b = dir.path().join("b.txt");
+        fs::write(&a, "a").unwrap();
+        fs::write(&b, "b").unwrap();
+        let paths = vec![a.clone(), b.clone(), dir.path().join("missing.txt")];
+        let trashed = move_all_to_trash(&paths);
+        assert_eq!(trashed.done, vec![a.clone(), b.clone()]);
+        assert!(matches!(trashed.errors.as_slice(), [FileOpError::NotFound(_)]));
+
+        fs::write(&a, "new").unwrap();
+        let restored = restore_all_from_trash(&[a.clone(), b.clone()]);
+        assert_eq!(restored.done, vec![b.clone()]);
+        assert_eq!(restored.retry, vec![a.clone()]);
+        assert!(matches!(restored.errors.as_slice(), [FileOpError::Restore { path, .. }] if *path == a));
+        assert_eq!(fs::read_to_string(&a).unwrap(), "new");
+
+        fs::remove_file(&a).unwrap();
+        let restored = restore_all_from_trash(&restored.retry);
+        assert_eq!(restored.done, vec![a.clone()]);
+        assert_eq!(fs::read_to_string(&a).unwrap(), "a");
+    }
+}
@@ -0,0 +1,841 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::{self, DirEntry, Metadata};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use chrono::{DateTime, Local};
+use log::{error, info};
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::service::file_list::{sort_nodes, FileSort};
+use crate::service::scan_filter::{ExcludeReason, IgnoreRules, ScanFilter, ScanOptions};
+
+/// 文件夹扫描错误
+#[derive(Error, Debug)]
+pub enum ScanError {
+    #[error("读取文件夹:{path:?} 失败:{source}")]
+    ReadDir {
+        path: PathBuf,
+        source: io::Error,
+    },
+    #[error("排除规则:{pattern} 格式错误:{source}")]
+    InvalidPattern {
+        pattern: String,
+        source: globset::Error,
+    },
+}
+
+/// 文件、文件夹信息
+#[derive(PartialEq, Clone, Serialize, Debug)]
+pub struct FileInfo {
+    pub file_name: String,
+    pub file_path: String,
+    /// 字节数，文件夹为所有子、孙文件大小之和
+    pub file_size: u64,
+    /// 实际占用的磁盘空间
+    pub allocated_size: u64,
+    pub update_time: String,
+    pub is_file: bool,
+}
+
+/// 扫描得到的文件树节点，文件夹节点包含所有子节点，一次扫描后可以任意展开、下钻
+#[derive(PartialEq, Clone, Debug)]
+pub struct FileNode {
+    pub name: String,
+    pub path: PathBuf,
+    /// 字节数，文件夹为所有子、孙文件大小之和
+    pub size: u64,
+    /// 实际占用的磁盘空间，文件夹为所有子、孙文件之和
+    pub allocated_size: u64,
+    /// 被排除规则跳过的子孙文件大小之和，不包含在 size 中
+    pub excluded_size: u64,
+    pub modified: Option<SystemTime>,
+    pub is_file: bool,
+    /// 子节点，按大小倒序排列
+    pub children: Vec<FileNode>,
+}
+
+/// 按大小倒序插入到已排序的子节点中，不重新排序全部子节点
+fn insert_by_size(children: &mut Vec<FileNode>, child: FileNode) {
+    let index = children.partition_point(|node| FileSort::SIZE_DESC.compare_nodes(node, &child).is_le());
+    children.insert(index, child);
+}
+
+impl FileNode {
+    /// 创建没有子节点的文件夹节点，用于逐个接收后台扫描的结果
+    pub fn new_dir(path: &Path) -> Self {
+        Self {
+            name: path.file_name().map_or_else(|| path.to_string_lossy().to_string(), |name| name.to_string_lossy().to_string()),
+            path: path.to_path_buf(),
+            size: 0,
+            allocated_size: 0,
+            excluded_size: 0,
+            modified: fs::metadata(path).and_then(|metadata| metadata.modified()).ok(),
+            is_file: false,
+            children: vec![],
+        }
+    }
+
+    /// 添加子节点，累加大小并保持子节点按大小倒序
+    pub fn add_child(&mut self, child: FileNode) {
+        self.size += child.size;
+        self.allocated_size += child.allocated_size;
+        self.excluded_size += child.excluded_size;
+        insert_by_size(&mut self.children, child);
+    }
+
+    /// 根据路径查找当前节点或子孙节点
+    pub fn find(&self, path: &Path) -> Option<&FileNode> {
+        let relative_path = path.strip_prefix(&self.path).ok()?;
+        let mut node = self;
+        for component in relative_path.components() {
+            node = node.children.iter().find(|child| child.path.file_name() == Some(component.as_os_str()))?;
+        }
+        Some(node)
+    }
+
+    /// 移除子孙节点，同时从所有上级文件夹中减去它的大小
+    pub fn remove(&mut self, path: &Path) -> Option<FileNode> {
+        let relative_path = path.strip_prefix(&self.path).ok()?;
+        let mut components = relative_path.components();
+        let name = components.next()?.as_os_str();
+        let index = self.children.iter().position(|child| child.path.file_name() == Some(name))?;
+        let removed = if components.next().is_none() {
+            self.children.remove(index)
+        } else {
+            self.children[index].remove(path)?
+        };
+        self.size = self.size.saturating_sub(removed.size);
+        self.allocated_size = self.allocated_size.saturating_sub(removed.allocated_size);
+        self.excluded_size = self.excluded_size.saturating_sub(removed.excluded_size);
+        Some(removed)
+    }
+
+    /// 把节点放到所在的文件夹下，替换同路径的节点并更新上级文件夹大小，所在文件夹不在树中时返回 false
+    pub fn insert(&mut self, node: FileNode) -> bool {
+        let Some(parent) = node.path.parent() else {
+            return false;
+        };
+        if parent == self.path {
+            self.remove(&node.path);
+            self.add_child(node);
+            return true;
+        }
+        let Some(name) = parent.strip_prefix(&self.path).ok().and_then(|relative_path| relative_path.components().next()) else {
+            return false;
+        };
+        let Some(index) = self.children.iter().position(|child| !child.is_file && child.path.file_name() == Some(name.as_os_str())) else {
+            return false;
+        };
+        let child = &mut self.children[index];
+        let (size, allocated_size, excluded_size) = (child.size, child.allocated_size, child.excluded_size);
+        if !child.insert(node) {
+            return false;
+        }
+        self.size = self.size - size + child.size;
+        self.allocated_size = self.allocated_size - allocated_size + child.allocated_size;
+        self.excluded_size = self.excluded_size - excluded_size + child.excluded_size;
+        //大小变化后只移动这个子节点
+        let child = self.children.remove(index);
+        insert_by_size(&mut self.children, child);
+        true
+    }
+
+    /// 修改路径和名称，子孙节点的路径一起修改
+    pub fn set_path(&mut self, path: PathBuf) {
+        if let Some(name) = path.file_name() {
+            self.name = name.to_string_lossy().to_string();
+        }
+        for child in &mut self.children {
+            if let Some(name) = child.path.file_name() {
+                child.set_path(path.join(name));
+            }
+        }
+        self.path = path;
+    }
+
+    /// 收集所有子孙文件的路径和大小，不包含文件夹
+    pub fn collect_files(&self) -> Vec<(PathBuf, u64)> {
+        let mut files = vec![];
+        self.collect_files_into(&mut files);
+        files
+    }
+
+    fn collect_files_into(&self, files: &mut Vec<(PathBuf, u64)>) {
+        for child in &self.children {
+            if child.is_file {
+                files.push((child.path.clone(), child.size));
+            } else {
+                child.collect_files_into(files);
+            }
+        }
+    }
+
+    /// 转换为列表展示使用的文件信息
+    pub fn to_file_info(&self) -> FileInfo {
+        FileInfo {
+            file_name: self.name.clone(),
+            file_path: self.path.to_string_lossy().to_string(),
+            file_size: self.size,
+            allocated_size: self.allocated_size,
+            update_time: format_time(self.modified),
+            is_file: self.is_file,
+        }
+    }
+}
+
+/// 格式化修改时间，没有时间时返回 --
+pub fn format_time(time: Option<SystemTime>) -> String {
+    time.map_or("--".to_string(), |time| {
+        // 将 SystemTime 转换为 DateTime<Local>
+        let datetime: DateTime<Local> = time.into();
+
+        // 格式化输出
+        datetime.format("%Y-%m-%d %H:%M:%S").to_string()
+    })
+}
+
+/// 扫描进度，工作线程更新，界面线程读取
+#[derive(Default, Debug)]
+pub struct ScanProgress {
+    dirs_visited: AtomicU64,
+    files_visited: AtomicU64,
+    bytes_counted: AtomicU64,
+    entries_excluded: AtomicU64,
+    bytes_excluded: AtomicU64,
+    issues: AtomicU64,
+    current_path: Mutex<String>,
+    cancelled: AtomicBool,
+}
+
+impl ScanProgress {
+    /// 已扫描的文件夹数
+    pub fn dirs_visited(&self) -> u64 {
+        self.dirs_visited.load(Ordering::Relaxed)
+    }
+
+    /// 已扫描的文件数
+    pub fn files_visited(&self) -> u64 {
+        self.files_visited.load(Ordering::Relaxed)
+    }
+
+    /// 已统计的字节数
+    pub fn bytes_counted(&self) -> u64 {
+        self.bytes_counted.load(Ordering::Relaxed)
+    }
+
+    /// 被排除的文件、文件夹数
+    pub fn entries_excluded(&self) -> u64 {
+        self.entries_excluded.load(Ordering::Relaxed)
+    }
+
+    /// 被排除的字节数，其他文件系统上的文件夹不统计大小
+    pub fn bytes_excluded(&self) -> u64 {
+        self.bytes_excluded.load(Ordering::Relaxed)
+    }
+
+    /// 遇到的读取错误数
+    pub fn issue_count(&self) -> u64 {
+        self.issues.load(Ordering::Relaxed)
+    }
+
+    /// 正在扫描的路径
+    pub fn current_path(&self) -> String {
+        self.current_path.lock().map(|path| path.clone()).unwrap_or_default()
+    }
+
+    /// 请求取消扫描，工作线程会尽快退出
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    fn visit_dir(&self, path: &Path) {
+        self.dirs_visited.fetch_add(1, Ordering::Relaxed);
+        if let Ok(mut current_path) = self.current_path.lock() {
+            *current_path = path.to_string_lossy().to_string();
+        }
+    }
+
+    fn visit_file(&self, size: u64) {
+        self.files_visited.fetch_add(1, Ordering::Relaxed);
+        self.bytes_counted.fetch_add(size, Ordering::Relaxed);
+    }
+
+    fn visit_excluded(&self, size: u64) {
+        self.entries_excluded.fetch_add(1, Ordering::Relaxed);
+        self.bytes_excluded.fetch_add(size, Ordering::Relaxed);
+    }
+}
+
+/// 扫描中遇到的非致命错误，例如没有权限、扫描过程中文件被删除
+#[derive(PartialEq, Clone, Debug)]
+pub struct ScanIssue {
+    pub path: PathBuf,
+    pub message: String,
+}
+
+/// 后台扫描过程中产生的事件
+#[derive(Debug)]
+pub enum ScanEvent {
+    /// 一个子文件、子文件夹统计完成，包含它的所有子孙节点
+    Entry(FileNode),
+    /// 一个子文件、子文件夹被排除，包含它的大小
+    Excluded(u64),
+    /// 读取某个文件、文件夹失败，已跳过
+    Issue(ScanIssue),
+    /// 扫描完成，包含已统计的硬链接，用于之后局部更新
+    Finished(HardLinks),
+    /// 扫描被取消
+    Cancelled,
+    /// 扫描失败
+    Failed(ScanError),
+}
+
+/// 有多个硬链接的文件大小记在哪个路径上，同一文件的其他硬链接大小记为 0
+#[derive(Clone, Default)]
+pub struct HardLinks {
+    counted: HashMap<(u64, u64), PathBuf>,
+}
+
+impl HardLinks {
+    /// path 上的硬链接是否需要统计大小，第一次遇到的路径负责统计
+    fn should_count(&mut self, id: (u64, u64), path: &Path) -> bool {
+        match self.counted.get(&id) {
+            Some(counted) => counted == path,
+            None => {
+                self.counted.insert(id, path.to_path_buf());
+                true
+            }
+        }
+    }
+
+    /// 文件、文件夹被删除或移走后，其中的硬链接改由之后遇到的其他路径统计
+    pub fn forget(&mut self, path: &Path) {
+        self.counted.retain(|_, counted| !counted.starts_with(path));
+    }
+}
+
+impl fmt::Debug for HardLinks {
+    /// 只输出数量，避免日志过长
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HardLinks").field("count", &self.counted.len()).finish()
+    }
+}
+
+/// 后台扫描的句柄，通过它接收扫描结果、查看进度、取消扫描
+pub struct ScanHandle {
+    receiver: Receiver<ScanEvent>,
+    progress: Arc<ScanProgress>,
+}
+
+impl ScanHandle {
+    pub fn progress(&self) -> &ScanProgress {
+        &self.progress
+    }
+
+    pub fn cancel(&self) {
+        self.progress.cancel();
+    }
+
+    /// 取出目前已产生的事件，不阻塞
+    pub fn poll_events(&self) -> Vec<ScanEvent> {
+        self.receiver.try_iter().collect()
+    }
+}
+
+/// 在后台线程扫描文件夹，每统计完一个子文件、子文件夹就发送一次 [`ScanEvent::Entry`]
+///
+/// on_update 在有新事件时调用，界面可以在这里请求重绘
+pub fn scan_folder_in_background(path: PathBuf, options: ScanOptions, on_update: impl Fn() + Send + 'static) -> ScanHandle {
+    let (sender, receiver) = mpsc::channel();
+    let progress = Arc::new(ScanProgress::default());
+    let worker_progress = progress.clone();
+    thread::spawn(move || {
+        info!("开始扫描:{path:?}");
+        let send = |sender: &Sender<ScanEvent>, event: ScanEvent| {
+            //界面已关闭时接收端不存在，忽略发送失败
+            let _ = sender.send(event);
+            on_update();
+        };
+        let result = ScanFilter::new(&path, &options).and_then(|filter| {
+            scan_folder_with_progress(&path, &filter, &worker_progress, |event| send(&sender, event))
+        });
+        let event = match result {
+            Err(e) => ScanEvent::Failed(e),
+            Ok(_) if worker_progress.is_cancelled() => ScanEvent::Cancelled,
+            Ok(hard_links) => ScanEvent::Finished(hard_links),
+        };
+        info!("扫描结束:{path:?}, {event:?}");
+        send(&sender, event);
+    });
+    ScanHandle {
+        receiver,
+        progress,
+    }
+}
+
+/// 读取文件夹下的直接子文件、子文件夹信息，按大小倒序、名称正序排列
+pub fn scan_folder(path: &Path, options: &ScanOptions) -> Result<Vec<FileInfo>, ScanError> {
+    let tree = scan_tree(path, options)?;
+    Ok(tree.children.iter().map(FileNode::to_file_info).collect())
+}
+
+/// 扫描文件夹，返回包含所有子孙节点的文件树
+pub fn scan_tree(path: &Path, options: &ScanOptions) -> Result<FileNode, ScanError> {
+    let filter = ScanFilter::new(path, options)?;
+    let progress = ScanProgress::default();
+    let mut root = FileNode::new_dir(path);
+    scan_folder_with_progress(path, &filter, &progress, |event| match event {
+        ScanEvent::Entry(node) => root.add_child(node),
+        ScanEvent::Excluded(size) => root.excluded_size += size,
+        _ => {}
+    })?;
+    Ok(root)
+}
+
+/// 重新统计扫描文件夹下的一项，用于文件变化后局部更新文件树
+///
+/// hard_links 为扫描时已统计的硬链接，已在其他路径统计过的硬链接大小仍记为 0；
+/// 路径不存在、读取失败或它本身、上级文件夹被排除时返回 None
+pub fn rescan_entry(path: &Path, filter: &ScanFilter, hard_links: &mut HardLinks) -> Option<FileNode> {
+    let rules = filter.rules_for_entry(path)?;
+    let metadata = fs::symlink_metadata(path).ok()?;
+    if filter.exclusion(path, &metadata, &rules).is_some() {
+        return None;
+    }
+    let progress = ScanProgress::default();
+    let mut walker = Walker::new(filter, &progress, std::mem::take(hard_links));
+    let node = walker.build_node(path.to_path_buf(), &metadata, &rules);
+    *hard_links = walker.hard_links;
+    Some(node)
+}
+
+/// 扫描文件夹的直接子文件、子文件夹，每统计完一个发送一次 [`ScanEvent::Entry`] 或 [`ScanEvent::Excluded`]
+///
+/// 遇到的读取错误通过 [`ScanEvent::Issue`] 发送，对应的文件、文件夹会被跳过，返回已统计的硬链接
+fn scan_folder_with_progress(path: &Path, filter: &ScanFilter, progress: &ScanProgress, mut on_event: impl FnMut(ScanEvent)) -> Result<HardLinks, ScanError> {
+    //读取文件
+    let entries = fs::read_dir(path).map_err(|source| ScanError::ReadDir {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    progress.visit_dir(path);
+    let mut walker = Walker::new(filter, progress, HardLinks::default());
+    let rules = filter.rules_for_dir(path, &IgnoreRules::default());
+
+    //便利文件
+    for entry in entries {
+        if progress.is_cancelled() {
+            return Ok(walker.hard_links);
+        }
+        let event = walker.visit_entry(path, entry, &rules);
+        for issue in walker.issues.drain(..) {
+            on_event(ScanEvent::Issue(issue));
+        }
+        if progress.is_cancelled() {
+            //统计到一半的文件夹大小不准确，不再返回
+            return Ok(walker.hard_links);
+        }
+        match event {
+            Some(Visited::Node(node)) => on_event(ScanEvent::Entry(node)),
+            Some(Visited::Excluded(size)) => on_event(ScanEvent::Excluded(size)),
+            None => {}
+        }
+    }
+    Ok(walker.hard_links)
+}
+
+/// 统计一个目录项的结果
+enum Visited {
+    Node(FileNode),
+    /// 被排除，包含它的大小
+    Excluded(u64),
+}
+
+/// 递归统计文件夹，只在一个线程中使用
+///
+/// 使用不跟随符号链接的元信息，符号链接按链接本身统计，不会进入链接指向的文件夹，也就不会出现循环
+struct Walker<'a> {
+    filter: &'a ScanFilter,
+    progress: &'a ScanProgress,
+    /// 已统计过的硬链接文件，同一个文件的其他硬链接大小记为 0
+    hard_links: HardLinks,
+    /// 还未发送的错误
+    issues: Vec<ScanIssue>,
+}
+
+impl<'a> Walker<'a> {
+    fn new(filter: &'a ScanFilter, progress: &'a ScanProgress, hard_links: HardLinks) -> Self {
+        Self {
+            filter,
+            progress,
+            hard_links,
+            issues: vec![],
+        }
+    }
+
+    fn record_issue(&mut self, path: &Path, e: &io::Error) {
+        error!("读取:{path:?} 失败:{e}");
+        self.progress.issues.fetch_add(1, Ordering::Relaxed);
+        self.issues.push(ScanIssue {
+            path: path.to_path_buf(),
+            message: e.to_string(),
+        });
+    }
+
+    /// 统计文件夹下的一项，读取失败时记录错误并返回 None
+    fn visit_entry(&mut self, dir: &Path, entry: io::Result<DirEntry>, rules: &IgnoreRules) -> Option<Visited> {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                self.record_issue(dir, &e);
+                return None;
+            }
+        };
+        let path = entry.path();
+        let metadata = match entry.metadata() {
+            Ok(metadata) => metadata,
+            Err(e) => {
+                self.record_issue(&path, &e);
+                return None;
+            }
+        };
+        if let Some(reason) = self.filter.exclusion(&path, &metadata, rules) {
+            return Some(Visited::Excluded(self.excluded_size(&path, &metadata, reason)));
+        }
+        Some(Visited::Node(self.build_node(path, &metadata, rules)))
+    }
+
+    /// 构建节点，文件夹会递归统计所有子、孙文件，读取失败的部分记录错误后跳过
+    fn build_node(&mut self, path: PathBuf, metadata: &Metadata, parent_rules: &IgnoreRules) -> FileNode {
+        let mut node = FileNode {
+            name: path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default(),
+            path,
+            size: 0,
+            allocated_size: 0,
+            excluded_size: 0,
+            modified: metadata.modified().ok(),
+            is_file: !metadata.is_dir(),
+            children: vec![],
+        };
+        if node.is_file {
+            if self.is_counted_link(&node.path, metadata) {
+                //同一文件的其他硬链接已经统计过
+                return node;
+            }
+            node.size = metadata.len();
+            node.allocated_size = allocated_size(metadata);
+            self.progress.visit_file(node.size);
+            return node;
+        }
+
+        let entries = match fs::read_dir(&node.path) {
+            Ok(entries) => entries,
+            Err(e) => {
+                self.record_issue(&node.path, &e);
+                return node;
+            }
+        };
+        self.progress.visit_dir(&node.path);
+        let rules = self.filter.rules_for_dir(&node.path, parent_rules);
+        for entry in entries {
+            if self.progress.is_cancelled() {
+                break;
+            }
+            match self.visit_entry(&node.path, entry, &rules) {
+                Some(Visited::Node(child)) => {
+                    node.size += child.size;
+                    node.allocated_size += child.allocated_size;
+                    node.excluded_size += child.excluded_size;
+                    node.children.push(child);
+                }
+                Some(Visited::Excluded(size)) => node.excluded_size += size,
+                None => {}
+            }
+        }
+        sort_nodes(&mut node.children, FileSort::SIZE_DESC);
+        node
+    }
+
+    /// 有多个硬链接的文件只在第一次遇到的路径上统计
+    fn is_counted_link(&mut self, path: &Path, metadata: &Metadata) -> bool {
+        match hard_link_id(metadata) {
+            Some(id) => !self.hard_links.should_count(id, path),
+            None => false,
+        }
+    }
+
+    /// 统计被排除的文件、文件夹大小，不生成节点，其他文件系统上的文件夹不统计
+    fn excluded_size(&mut self, path: &Path, metadata: &Metadata, reason: ExcludeReason) -> u64 {
+        let size = match reason {
+            ExcludeReason::OtherFileSystem => 0,
+            ExcludeReason::Pattern | ExcludeReason::IgnoreFile => self.measure_size(path, metadata),
+        };
+        self.progress.visit_excluded(size);
+        size
+    }
+
+    fn measure_size(&mut self, path: &Path, metadata: &Metadata) -> u64 {
+        if !metadata.is_dir() {
+            return if self.is_counted_link(path, metadata) { 0 } else { metadata.len() };
+        }
+        let entries = match fs::read_dir(path) {
+            Ok(entries) => entries,
+            Err(e) => {
+                self.record_issue(path, &e);
+                return 0;
+            }
+        };
+        let mut size = 0;
+        for entry in entries {
+            if self.progress.is_cancelled() {
+                break;
+            }
+            let entry_metadata = entry.and_then(|entry| entry.metadata().map(|metadata| (entry.path(), metadata)));
+            match entry_metadata {
+                Ok((entry_path, entry_metadata)) => size += self.measure_size(&entry_path, &entry_metadata),
+                Err(e) => self.record_issue(path, &e),
+            }
+        }
+        size
+    }
+}
+
+/// 文件实际占用的磁盘空间，按块分配，稀疏文件可能小于文件大小，其他系统上等于文件大小
+#[cfg(unix)]
+fn allocated_size(metadata: &Metadata) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+    //st_blocks 的单位固定为 512 字节
+    metadata.blocks() * 512
+}
+
+#[cfg(not(unix))]
+fn allocated_size(metadata: &Metadata) -> u64 {
+    metadata.len()
+}
+
+/// 有多个硬链接的文件返回 (设备号, inode)
+#[cfg(unix)]
+fn hard_link_id(metadata: &Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    (metadata.nlink() > 1).then(|| (metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn hard_link_id(_metadata: &Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_folder() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.txt"), "12345").unwrap();
+        fs::create_dir_all(dir.path().join("sub/inner")).unwrap();
+        fs::write(dir.path().join("sub/b.txt"), "1234567890").unwrap();
+        fs::write(dir.path().join("sub/inner/c.txt"), "123").unwrap();
+        dir
+    }
+
+    #[test]
+    fn scan_folder_lists_children_sorted_by_size() {
+        let dir = create_test_folder();
+        let files = scan_folder(dir.path(), &ScanOptions::default()).unwrap();
+        let names: Vec<&str> = files.iter().map(|file| file.file_name.as_str()).collect();
+        assert_eq!(names, vec!["sub", "a.txt"]);
+        assert_eq!(files[0].file_size, 13);
+        assert!(!files[0].is_file);
+        assert_eq!(files[1].file_size, 5);
+        assert!(files[1].is_file);
+    }
+
+    #[test]
+    fn scan_folder_reports_missing_folder() {
+        let dir = tempfile::tempdir().unwrap();
+        let result = scan_folder(&dir.path().join("missing"), &ScanOptions::default());
+        assert!(matches!(result, Err(ScanError::ReadDir { .. })));
+    }
+
+    #[test]
+    fn scan_tree_keeps_nested_children_sorted_by_size() {
+        let dir = create_test_folder();
+        let tree = scan_tree(dir.path(), &ScanOptions::default()).unwrap();
+        assert_eq!(tree.size, 18);
+        let sub = &tree.children[0];
+        assert_eq!(sub.name, "sub");
+        let names: Vec<&str> = sub.children.iter().map(|node| node.name.as_str()).collect();
+        assert_eq!(names, vec!["b.txt", "inner"]);
+        assert_eq!(sub.children[1].children[0].size, 3);
+    }
+
+    #[test]
+    fn find_returns_nested_node_by_path() {
+        let dir = create_test_folder();
+        let tree = scan_tree(dir.path(), &ScanOptions::default()).unwrap();
+        let inner = tree.find(&dir.path().join("sub").join("inner")).unwrap();
+        assert_eq!(inner.size, 3);
+        assert_eq!(tree.find(dir.path()).unwrap().size, 18);
+        assert!(tree.find(&dir.path().join("missing")).is_none());
+    }
+
+    #[test]
+    fn background_scan_streams_entries_then_finishes() {
+        let dir = create_test_folder();
+        let handle = scan_folder_in_background(dir.path().to_path_buf(), ScanOptions::default(), || {});
+        let mut entries = vec![];
+        loop {
+            match handle.receiver.recv().unwrap() {
+                ScanEvent::Entry(node) => entries.push(node),
+                ScanEvent::Finished(_) => break,
+                event => panic!("unexpected event:{event:?}"),
+            }
+        }
+        assert_eq!(entries.len(), 2);
+        assert_eq!(handle.progress().bytes_counted(), 18);
+        assert_eq!(handle.progress().files_visited(), 3);
+        assert_eq!(handle.progress().dirs_visited(), 3);
+    }
+
+    #[test]
+    fn cancelled_scan_stops_without_more_entries() {
+        let dir = create_test_folder();
+        let progress = ScanProgress::default();
+        progress.cancel();
+        let mut events = vec![];
+        let filter = ScanFilter::new(dir.path(), &ScanOptions::default()).unwrap();
+        scan_folder_with_progress(dir.path(), &filter, &progress, |event| events.push(event)).unwrap();
+        assert!(events.is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn hard_links_are_counted_once_and_symlinks_not_followed() {
+        let dir = create_test_folder();
+        fs::hard_link(dir.path().join("a.txt"), dir.path().join("sub/a-link.txt")).unwrap();
+        //指向上级文件夹的符号链接，跟随时会无限循环
+        std::os::unix::fs::symlink(dir.path(), dir.path().join("sub/loop")).unwrap();
+        let tree = scan_tree(dir.path(), &ScanOptions::default()).unwrap();
+        let link_size = fs::symlink_metadata(dir.path().join("sub/loop")).unwrap().len();
+        assert_eq!(tree.size, 18 + link_size);
+        let link = tree.find(&dir.path().join("sub/loop")).unwrap();
+        assert!(link.is_file);
+        assert!(link.children.is_empty());
+        assert!(tree.allocated_size > 0);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn rescan_keeps_hard_links_counted_once() {
+        let dir = create_test_folder();
+        fs::hard_link(dir.path().join("a.txt"), dir.path().join("sub/a-link.txt")).unwrap();
+        let filter = ScanFilter::new(dir.path(), &ScanOptions::default()).unwrap();
+        let mut root = FileNode::new_dir(dir.path());
+        let mut hard_links = scan_folder_with_progress(dir.path(), &filter, &ScanProgress::default(), |event| {
+            if let ScanEvent::Entry(node) = event {
+                root.add_child(node);
+            }
+        }).unwrap();
+        let paths = [dir.path().join("a.txt"), dir.path().join("sub/a-link.txt")];
+        let sizes: Vec<u64> = paths.iter().map(|path| root.find(path).unwrap().size).collect();
+        assert_eq!(sizes.iter().filter(|size| **size == 0).count(), 1);
+        //重新统计时每个路径保持扫描时的大小，不会重复计算
+        for (path, size) in paths.iter().zip(sizes) {
+            assert_eq!(rescan_entry(path, &filter, &mut hard_links).unwrap().size, size);
+        }
+        //统计大小的路径被删除后，由其他硬链接统计
+        let (counted, other) = if root.find(&paths[0]).unwrap().size > 0 { (&paths[0], &paths[1]) } else { (&paths[1], &paths[0]) };
+        fs::remove_file(counted).unwrap();
+        hard_links.forget(counted);
+        fs::hard_link(other, dir.path().join("third.txt")).unwrap();
+        assert!(rescan_entry(other, &filter, &mut hard_links).unwrap().size > 0);
+        assert_eq!(rescan_entry(&dir.path().join("third.txt"), &filter, &mut hard_links).unwrap().size, 0);
+    }
+
+    #[test]
+    fn remove_and_insert_update_ancestor_sizes() {
+        let dir = create_test_folder();
+        let mut tree = scan_tree(dir.path(), &ScanOptions::default()).unwrap();
+        let mut removed = tree.remove(&dir.path().join("sub/inner")).unwrap();
+        assert_eq!(removed.size, 3);
+        assert_eq!(tree.size, 15);
+        assert_eq!(tree.find(&dir.path().join("sub")).unwrap().size, 10);
+        assert!(tree.remove(&dir.path().join("sub/inner")).is_none());
+
+        //模拟重命名后放回
+        removed.set_path(dir.path().join("sub/renamed"));
+        assert_eq!(removed.name, "renamed");
+        assert_eq!(removed.children[0].path, dir.path().join("sub/renamed/c.txt"));
+        assert!(tree.insert(removed));
+        assert_eq!(tree.size, 18);
+        assert!(tree.find(&dir.path().join("sub/renamed/c.txt")).is_some());
+        assert!(!tree.insert(FileNode::new_dir(&dir.path().join("missing/x"))));
+    }
+
+    #[test]
+    fn rescan_entry_skips_excluded_paths() {
+        let dir = create_test_folder();
+        fs::create_dir_all(dir.path().join("target/debug")).unwrap();
+        fs::write(dir.path().join("target/debug/app"), "1234").unwrap();
+        let options = ScanOptions {
+            exclude_patterns: vec!["target".to_string()],
+            ..ScanOptions::default()
+        };
+        let filter = ScanFilter::new(dir.path(), &options).unwrap();
+
+        let node = rescan_entry(&dir.path().join("sub"), &filter, &mut HardLinks::default()).unwrap();
+        assert_eq!(node.size, 13);
+        assert_eq!(node.children.len(), 2);
+        assert!(rescan_entry(&dir.path().join("target/debug/app"), &filter, &mut HardLinks::default()).is_none());
+        assert!(rescan_entry(&dir.path().join("missing.txt"), &filter, &mut HardLinks::default()).is_none());
+    }
+
+    #[test]
+    fn excluded_entries_are_counted_separately() {
+        let dir = create_test_folder();
+        fs::create_dir_all(dir.path().join("target/debug")).unwrap();
+        fs::write(dir.path().join("target/debug/app"), "1234567").unwrap();
+        fs::write(dir.path().join("sub/inner/d.log"), "12").unwrap();
+        let options = ScanOptions {
+            exclude_patterns: vec!["target".to_string(), "*.log".to_string()],
+            ..ScanOptions::default()
+        };
+        let tree = scan_tree(dir.path(), &options).unwrap();
+        assert_eq!(tree.size, 18);
+        assert_eq!(tree.excluded_size, 9);
+        assert!(tree.find(&dir.path().join("target")).is_none());
+        assert_eq!(tree.find(&dir.path().join("sub")).unwrap().excluded_size, 2);
+    }
+
+    #[test]
+    fn add_and_insert_keep_children_sorted_by_size() {
+        let dir = create_test_folder();
+        let mut root = FileNode::new_dir(dir.path());
+        for (name, size) in [("b", 5), ("c", 9), ("a", 5), ("d", 1)] {
+            let mut node = FileNode::new_dir(&dir.path().join(name));
+            node.size = size;
+            root.add_child(node);
+        }
+        let names = |root: &FileNode| root.children.iter().map(|node| node.name.clone()).collect::<Vec<_>>();
+        assert_eq!(names(&root), vec!["c", "a", "b", "d"]);
+        //文件夹变大后移到前面
+        let mut file = FileNode::new_dir(&dir.path().join("d").join("big"));
+        file.size = 20;
+        file.is_file = true;
+        assert!(root.insert(file));
+        assert_eq!(names(&root), vec!["d", "c", "a", "b"]);
+        assert_eq!(root.size, 40);
+    }
+}
@@ -0,0 +1,317 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::{Arc, Mutex, Weak};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use log::{error, info};
+use notify::event::ModifyKind;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher, WatcherKind};
+use thiserror::Error;
+
+use crate::service::folder_scan::{rescan_entry, FileNode, HardLinks, ScanError};
+use crate::service::scan_filter::{IgnoreRules, ScanFilter, ScanOptions};
+
+/// 收到第一个事件后等待更多事件的时间，批量处理连续的修改
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// 文件夹监控错误
+#[derive(Error, Debug)]
+pub enum WatchError {
+    #[error("监控文件夹:{path:?} 失败:{source}")]
+    Watch {
+        path: PathBuf,
+        source: notify::Error,
+    },
+    #[error(transparent)]
+    Scan(#[from] ScanError),
+}
+
+/// 需要应用到文件树上的变化
+#[derive(Debug)]
+pub enum WatchChange {
+    /// 新增或修改的文件、文件夹，已重新统计，替换文件树中同路径的节点
+    Updated(FileNode),
+    /// 已删除或移走
+    Removed(PathBuf),
+    /// 系统丢弃了部分事件，需要重新扫描
+    RescanNeeded,
+    /// 注册监控失败，例如 inotify 监控数量达到上限，监控已停止
+    Failed(WatchError),
+}
+
+/// 文件夹监控，drop 时停止监控
+pub struct FolderWatcher {
+    /// 只用于保持监控，后台线程只持有弱引用，drop 后后台线程也会退出
+    _watcher: Arc<Mutex<RecommendedWatcher>>,
+    receiver: Receiver<WatchChange>,
+}
+
+impl FolderWatcher {
+    /// 取出目前已产生的变化，不阻塞
+    pub fn poll_changes(&self) -> Vec<WatchChange> {
+        self.receiver.try_iter().collect()
+    }
+}
+
+/// 递归监控文件夹，变化的文件、文件夹在后台按扫描选项重新统计，有新变化时调用 on_update
+///
+/// hard_links 为扫描时已统计的硬链接，重新统计时不会重复计算它们的大小
+///
+/// 注册监控在后台进行，不会阻塞界面，失败时发送 [`WatchChange::Failed`]
+pub fn watch_folder(root: PathBuf, options: &ScanOptions, mut hard_links: HardLinks, on_update: impl Fn() + Send + 'static) -> Result<FolderWatcher, WatchError> {
+    let filter = ScanFilter::new(&root, options)?;
+    let (event_sender, event_receiver) = mpsc::channel();
+    let watcher = notify::recommended_watcher(event_sender).map_err(|source| WatchError::Watch {
+        path: root.clone(),
+        source,
+    })?;
+    let watcher = Arc::new(Mutex::new(watcher));
+    let worker_watcher = Arc::downgrade(&watcher);
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        info!("开始监控:{root:?}");
+        if let Err(e) = register_dirs(&worker_watcher, &root, &root, &filter) {
+            let _ = sender.send(WatchChange::Failed(e));
+            on_update();
+            return;
+        }
+        while let Some(batch) = next_batch(&event_receiver) {
+            let changes = match batch {
+                Batch::Changed(changed) => collect_changes(&root, &filter, &mut hard_links, changed),
+                Batch::Rescan => vec![WatchChange::RescanNeeded],
+            };
+            if changes.is_empty() {
+                continue;
+            }
+            //新的文件夹需要单独注册监控
+            let registered = changes.iter()
+                .filter_map(|change| match change {
+                    WatchChange::Updated(node) if !node.is_file => Some(&node.path),
+                    _ => None,
+                })
+                .try_for_each(|dir| register_dirs(&worker_watcher, &root, dir, &filter));
+            let failed = registered.is_err();
+            let mut changes = changes.into_iter().chain(registered.err().map(WatchChange::Failed));
+            //界面已关闭时接收端不存在，停止监控
+            if changes.try_for_each(|change| sender.send(change)).is_err() {
+                break;
+            }
+            on_update();
+            if failed {
+                break;
+            }
+        }
+        info!("停止监控:{root:?}");
+    });
+    Ok(FolderWatcher {
+        _watcher: watcher,
+        receiver,
+    })
+}
+
+/// 注册 dir 的监控，界面已停止监控时直接返回
+///
+/// Windows、macOS 原生支持递归监控，只注册一次扫描文件夹；
+/// 其他系统(inotify)递归注册需要逐个添加子文件夹，这里只添加未被排除的文件夹，避免进入 target、node_modules 等
+fn register_dirs(watcher: &Weak<Mutex<RecommendedWatcher>>, root: &Path, dir: &Path, filter: &ScanFilter) -> Result<(), WatchError> {
+    let native_recursive = matches!(RecommendedWatcher::kind(), WatcherKind::Fsevent | WatcherKind::ReadDirectoryChangesWatcher);
+    let (dirs, mode) = if native_recursive {
+        if dir != root {
+            return Ok(());
+        }
+        (vec![root.to_path_buf()], RecursiveMode::Recursive)
+    } else {
+        (dirs_to_watch(root, dir, filter), RecursiveMode::NonRecursive)
+    };
+    for dir in dirs {
+        let Some(watcher) = watcher.upgrade() else {
+            return Ok(());
+        };
+        let mut watcher = watcher.lock().unwrap_or_else(|e| e.into_inner());
+        match watcher.watch(&dir, mode) {
+            Ok(()) => {}
+            //注册前已被删除
+            Err(notify::Error { kind: notify::ErrorKind::PathNotFound, .. }) => {}
+            Err(source) => return Err(WatchError::Watch { path: dir, source }),
+        }
+    }
+    Ok(())
+}
+
+/// dir 和它下面所有未被排除的文件夹，不跟随符号链接
+fn dirs_to_watch(root: &Path, dir: &Path, filter: &ScanFilter) -> Vec<PathBuf> {
+    let parent_rules = if dir == root {
+        IgnoreRules::default()
+    } else {
+        let Some(rules) = filter.rules_for_entry(dir) else {
+            return vec![];
+        };
+        let Ok(metadata) = fs::symlink_metadata(dir) else {
+            return vec![];
+        };
+        if filter.exclusion(dir, &metadata, &rules).is_some() {
+            return vec![];
+        }
+        rules
+    };
+    let mut dirs = vec![];
+    let mut pending = vec![(dir.to_path_buf(), parent_rules)];
+    while let Some((dir, parent_rules)) = pending.pop() {
+        let rules = filter.rules_for_dir(&dir, &parent_rules);
+        if let Ok(entries) = fs::read_dir(&dir) {
+            for entry in entries.flatten() {
+                let Ok(metadata) = fs::symlink_metadata(entry.path()) else {
+                    continue;
+                };
+                if metadata.is_dir() && filter.exclusion(&entry.path(), &metadata, &rules).is_none() {
+                    pending.push((entry.path(), rules.clone()));
+                }
+            }
+        }
+        dirs.push(dir);
+    }
+    dirs
+}
+
+/// 一批事件
+enum Batch {
+    /// 变化的路径和是否需要完整统计
+    Changed(BTreeMap<PathBuf, bool>),
+    Rescan,
+}
+
+/// 等待下一批事件，监控已停止时返回 None
+fn next_batch(events: &Receiver<notify::Result<Event>>) -> Option<Batch> {
+    let first = events.recv().ok()?;
+    let deadline = Instant::now() + DEBOUNCE;
+    let mut changed = BTreeMap::new();
+    let mut rescan = false;
+    let mut handle = |event: notify::Result<Event>| match event {
+        Ok(event) if event.need_rescan() => rescan = true,
+        Ok(event) => add_event(&mut changed, event),
+        Err(e) => error!("监控文件夹出错:{e}"),
+    };
+    handle(first);
+    loop {
+        let timeout = deadline.saturating_duration_since(Instant::now());
+        match events.recv_timeout(timeout) {
+            Ok(event) => handle(event),
+            Err(RecvTimeoutError::Timeout) => break,
+            Err(RecvTimeoutError::Disconnected) => return None,
+        }
+    }
+    Some(if rescan { Batch::Rescan } else { Batch::Changed(changed) })
+}
+
+/// 记录事件涉及的路径，新建、删除、重命名的文件夹需要完整统计，其他修改只影响文件
+fn add_event(changed: &mut BTreeMap<PathBuf, bool>, event: Event) {
+    let structural = match event.kind {
+        EventKind::Access(_) => return,
+        EventKind::Create(_) | EventKind::Remove(_) | EventKind::Modify(ModifyKind::Name(_)) => true,
+        EventKind::Modify(_) | EventKind::Any | EventKind::Other => false,
+    };
+    for path in event.paths {
+        let entry = changed.entry(path).or_insert(false);
+        *entry |= structural;
+    }
+}
+
+/// 重新统计变化的路径，已完整统计的文件夹下的路径会被跳过
+fn collect_changes(root: &Path, filter: &ScanFilter, hard_links: &mut HardLinks, changed: BTreeMap<PathBuf, bool>) -> Vec<WatchChange> {
+    //先处理删除，移到其他位置的硬链接改由新路径统计
+    for path in changed.keys() {
+        if path.symlink_metadata().is_err() {
+            hard_links.forget(path);
+        }
+    }
+    let mut changes = vec![];
+    let mut rescanned_dir: Option<PathBuf> = None;
+    //BTreeMap 按路径排序，上级文件夹在子孙之前
+    for (path, structural) in changed {
+        if path == root || !path.starts_with(root) {
+            continue;
+        }
+        if rescanned_dir.as_ref().is_some_and(|dir| path.starts_with(dir)) {
+            continue;
+        }
+        let Ok(metadata) = path.symlink_metadata() else {
+            changes.push(WatchChange::Removed(path));
+            continue;
+        };
+        if metadata.is_dir() && !structural {
+            //只是文件夹的修改时间等变化，子孙文件的变化有单独的事件
+            continue;
+        }
+        match rescan_entry(&path, filter, hard_links) {
+            Some(node) => {
+                if !node.is_file {
+                    rescanned_dir = Some(path);
+                }
+                changes.push(WatchChange::Updated(node));
+            }
+            //被排除
+            None => changes.push(WatchChange::Removed(path)),
+        }
+    }
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn changes_are_collected_once_per_new_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("new/inner")).unwrap();
+        fs::write(dir.path().join("new/inner/a.txt"), "123").unwrap();
+        fs::write(dir.path().join("b.txt"), "12345").unwrap();
+        let filter = ScanFilter::new(dir.path(), &ScanOptions::default()).unwrap();
+
+        let mut changed = BTreeMap::new();
+        changed.insert(dir.path().join("new"), true);
+        changed.insert(dir.path().join("new/inner/a.txt"), true);
+        changed.insert(dir.path().join("b.txt"), false);
+        changed.insert(dir.path().join("gone.txt"), true);
+        changed.insert(dir.path().to_path_buf(), false);
+        let changes = collect_changes(dir.path(), &filter, &mut HardLinks::default(), changed);
+
+        assert_eq!(changes.len(), 3);
+        assert!(matches!(&changes[0], WatchChange::Updated(node) if node.name == "b.txt" && node.size == 5));
+        assert!(matches!(&changes[1], WatchChange::Removed(path) if path.ends_with("gone.txt")));
+        assert!(matches!(&changes[2], WatchChange::Updated(node) if node.name == "new" && node.size == 3));
+    }
+
+    #[test]
+    fn excluded_dirs_are_not_watched() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("src/target")).unwrap();
+        fs::create_dir_all(dir.path().join("target/debug")).unwrap();
+        fs::create_dir_all(dir.path().join("web/node_modules/a")).unwrap();
+        let options = ScanOptions {
+            exclude_patterns: vec!["target".to_string(), "node_modules".to_string()],
+            ..ScanOptions::default()
+        };
+        let filter = ScanFilter::new(dir.path(), &options).unwrap();
+        let mut dirs = dirs_to_watch(dir.path(), dir.path(), &filter);
+        dirs.sort();
+        assert_eq!(dirs, vec![dir.path().to_path_buf(), dir.path().join("src"), dir.path().join("web")]);
+        assert_eq!(dirs_to_watch(dir.path(), &dir.path().join("web"), &filter), vec![dir.path().join("web")]);
+        assert!(dirs_to_watch(dir.path(), &dir.path().join("target/debug"), &filter).is_empty());
+    }
+
+    #[test]
+    fn modified_dirs_without_structural_change_are_skipped() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("sub")).unwrap();
+        let filter = ScanFilter::new(dir.path(), &ScanOptions::default()).unwrap();
+        let mut changed = BTreeMap::new();
+        changed.insert(dir.path().join("sub"), false);
+        assert!(collect_changes(dir.path(), &filter, &mut HardLinks::default(), changed).is_empty());
+    }
+}
@@ -0,0 +1,66 @@
+/// 每行显示的字节数
+pub const BYTES_PER_LINE: usize = 16;
+
+/// hex 查看器中的一行
+#[derive(PartialEq, Eq, Debug)]
+pub struct HexLine {
+    /// 8 位 hex 偏移
+    pub offset: String,
+    /// 每个字节两位 hex，中间多一个空格分成两组，不足一行时用空格补齐
+    pub hex: String,
+    /// 可打印的 ASCII 字符原样显示，其他显示为 .
+    pub ascii: String,
+}
+
+/// 总行数
+pub fn line_count(len: usize) -> usize {
+    len.div_ceil(BYTES_PER_LINE)
+}
+
+/// 生成指定行，只处理需要显示的行，大数据也不需要一次生成全部内容
+pub fn hex_line(bytes: &[u8], line: usize) -> HexLine {
+    let start = (line * BYTES_PER_LINE).min(bytes.len());
+    let chunk = &bytes[start..(start + BYTES_PER_LINE).min(bytes.len())];
+    let mut hex = String::with_capacity(BYTES_PER_LINE * 3 + 1);
+    for index in 0..BYTES_PER_LINE {
+        if index == BYTES_PER_LINE / 2 {
+            hex.push(' ');
+        }
+        match chunk.get(index) {
+            Some(byte) => hex.push_str(&format!("{byte:02x}")),
+            None => hex.push_str("  "),
+        }
+        if index + 1 < BYTES_PER_LINE {
+            hex.push(' ');
+        }
+    }
+    let ascii = chunk.iter().map(|&byte| if byte.is_ascii_graphic() || byte == b' ' { byte as char } else { '.' }).collect();
+    HexLine {
+        offset: format!("{start:08x}"),
+        hex,
+        ascii,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lines_have_offset_hex_and_ascii_columns() {
+        let bytes: Vec<u8> = (0x41..0x41 + 18).chain([0x00, 0xff]).collect();
+        assert_eq!(line_count(bytes.len()), 2);
+        assert_eq!(line_count(0), 0);
+
+        let first = hex_line(&bytes, 0);
+        assert_eq!(first.offset, "00000000");
+        assert_eq!(first.hex, "41 42 43 44 45 46 47 48  49 4a 4b 4c 4d 4e 4f 50");
+        assert_eq!(first.ascii, "ABCDEFGHIJKLMNOP");
+
+        let last = hex_line(&bytes, 1);
+        assert_eq!(last.offset, "00000010");
+        assert_eq!(last.hex.len(), first.hex.len());
+        assert!(last.hex.starts_with("51 52 00 ff   "));
+        assert_eq!(last.ascii, "QR..");
+    }
+}
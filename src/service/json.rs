@@ -0,0 +1,51 @@
+use serde_json::Value;
+use thiserror::Error;
+
+/// json 处理错误
+#[derive(Error, Debug)]
+pub enum JsonError {
+    #[error("json 解析失败:{0}")]
+    Parse(serde_json::Error),
+    #[error("json 序列化失败:{0}")]
+    Serialize(serde_json::Error),
+}
+
+/// 解析 json 字符串
+pub fn parse_json(json_str: &str) -> Result<Value, JsonError> {
+    serde_json::from_str(json_str).map_err(JsonError::Parse)
+}
+
+/// 将 json 转换为字符串，compact 为 true 时输出压缩后的单行 json
+pub fn to_json_string(json_data: &Value, compact: bool) -> Result<String, JsonError> {
+    let json_str = if compact {
+        serde_json::to_string(json_data)
+    } else {
+        serde_json::to_string_pretty(json_data)
+    };
+    json_str.map_err(JsonError::Serialize)
+}
+
+/// 格式化 json 字符串，compact 为 true 时输出压缩后的单行 json
+pub fn format_json(json_str: &str, compact: bool) -> Result<String, JsonError> {
+    to_json_string(&parse_json(json_str)?, compact)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_json_pretty_prints() {
+        assert_eq!(format_json(r#"{"a":[1,2]}"#, false).unwrap(), "{\n  \"a\": [\n    1,\n    2\n  ]\n}");
+    }
+
+    #[test]
+    fn format_json_compact_removes_whitespace() {
+        assert_eq!(format_json("{ \"a\" : 1,\n \"b\" : null }", true).unwrap(), r#"{"a":1,"b":null}"#);
+    }
+
+    #[test]
+    fn format_json_reports_parse_error() {
+        assert!(matches!(format_json("{", false), Err(JsonError::Parse(_))));
+    }
+}
@@ -0,0 +1,19 @@
+//! 与界面无关的工具核心逻辑，图形界面和命令行共用同一套实现
+
+pub mod charset;
+pub mod codec;
+pub mod codec_detect;
+pub mod duplicate;
+pub mod export;
+pub mod file_list;
+pub mod file_ops;
+pub mod folder_scan;
+pub mod folder_watch;
+pub mod hex_dump;
+pub mod json;
+pub mod pipeline;
+pub mod report;
+pub mod scan_filter;
+pub mod snapshot;
+pub mod treemap;
+pub mod type_stats;
@@ -0,0 +1,378 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+
+use md5::Md5;
+use percent_encoding::{percent_decode, percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+use crate::service::charset::{decode_charset, encode_charset, CharsetError};
+use crate::service::codec::{compress, decode_bytes, decompress, encode_bytes, CodecError, CompressionCodec, TextEncoding};
+use crate::service::codec_detect::{auto_decode, DetectError};
+use crate::service::json::{format_json, JsonError};
+
+/// URL 编码时保留的字符，与 encodeURIComponent 一致
+const URL_COMPONENT: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.')
+    .remove(b'!')
+    .remove(b'~')
+    .remove(b'*')
+    .remove(b'\'')
+    .remove(b'(')
+    .remove(b')');
+
+/// 流水线执行错误
+#[derive(Error, Debug)]
+pub enum PipelineError {
+    #[error(transparent)]
+    Codec(#[from] CodecError),
+    #[error(transparent)]
+    Detect(#[from] DetectError),
+    #[error(transparent)]
+    Json(#[from] JsonError),
+    #[error("输入不是有效的 UTF-8 文本")]
+    NotText,
+    #[error(transparent)]
+    Charset(#[from] CharsetError),
+}
+
+/// 配方读写错误
+#[derive(Error, Debug)]
+pub enum RecipeError {
+    #[error("读写配方文件:{path:?} 失败:{source}")]
+    Io {
+        path: PathBuf,
+        source: io::Error,
+    },
+    #[error("配方文件:{path:?} 格式错误:{source}")]
+    Format {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+}
+
+/// 哈希算法，结果为小写 hex 文本
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum HashAlgorithm {
+    Md5,
+    Sha1,
+    Sha256,
+    Blake3,
+}
+
+impl HashAlgorithm {
+    pub fn iter() -> impl Iterator<Item = HashAlgorithm> {
+        [HashAlgorithm::Md5, HashAlgorithm::Sha1, HashAlgorithm::Sha256, HashAlgorithm::Blake3].into_iter()
+    }
+
+    pub fn as_label(&self) -> &str {
+        match self {
+            HashAlgorithm::Md5 => "MD5",
+            HashAlgorithm::Sha1 => "SHA-1",
+            HashAlgorithm::Sha256 => "SHA-256",
+            HashAlgorithm::Blake3 => "BLAKE3",
+        }
+    }
+
+    fn hash(&self, bytes: &[u8]) -> Vec<u8> {
+        match self {
+            HashAlgorithm::Md5 => Md5::digest(bytes).to_vec(),
+            HashAlgorithm::Sha1 => Sha1::digest(bytes).to_vec(),
+            HashAlgorithm::Sha256 => Sha256::digest(bytes).to_vec(),
+            HashAlgorithm::Blake3 => blake3::hash(bytes).as_bytes().to_vec(),
+        }
+    }
+}
+
+/// 流水线中的一个操作，输入、输出都是字节，文本操作要求输入是 UTF-8
+#[derive(PartialEq, Eq, Clone, Debug, Serialize, Deserialize)]
+pub enum Operation {
+    /// URL 解码，+ 当作空格
+    UrlDecode,
+    UrlEncode,
+    Decode(TextEncoding),
+    Encode(TextEncoding),
+    Decompress(CompressionCodec),
+    Compress {
+        codec: CompressionCodec,
+        level: u32,
+    },
+    /// 自动识别编码和压缩格式并解开
+    AutoDecode,
+    Hash(HashAlgorithm),
+    /// json 格式化，compact 为 true 时压缩为单行
+    JsonFormat {
+        compact: bool,
+    },
+    /// 按指定字符集解码为 UTF-8 文本
+    DecodeCharset(String),
+    /// UTF-8 文本转换为指定字符集
+    EncodeCharset(String),
+}
+
+impl Operation {
+    /// 所有操作类型的默认参数，用于添加操作
+    pub fn templates() -> Vec<Operation> {
+        vec![
+            Operation::UrlDecode,
+            Operation::UrlEncode,
+            Operation::Decode(TextEncoding::Base64),
+            Operation::Encode(TextEncoding::Base64),
+            Operation::Decompress(CompressionCodec::Gzip),
+            Operation::Compress {
+                codec: CompressionCodec::Gzip,
+                level: CompressionCodec::Gzip.default_level(),
+            },
+            Operation::AutoDecode,
+            Operation::Hash(HashAlgorithm::Sha256),
+            Operation::JsonFormat { compact: false },
+            Operation::DecodeCharset("GBK".to_string()),
+            Operation::EncodeCharset("GBK".to_string()),
+        ]
+    }
+
+    /// 操作类型名称，不含参数
+    pub fn as_label(&self) -> &str {
+        match self {
+            Operation::UrlDecode => "URL 解码",
+            Operation::UrlEncode => "URL 编码",
+            Operation::Decode(_) => "文本解码",
+            Operation::Encode(_) => "文本编码",
+            Operation::Decompress(_) => "解压",
+            Operation::Compress { .. } => "压缩",
+            Operation::AutoDecode => "自动识别解码",
+            Operation::Hash(_) => "哈希",
+            Operation::JsonFormat { .. } => "json 格式化",
+            Operation::DecodeCharset(_) => "按字符集解码",
+            Operation::EncodeCharset(_) => "转换为字符集",
+        }
+    }
+
+    pub fn apply(&self, input: &[u8]) -> Result<Vec<u8>, PipelineError> {
+        let output = match self {
+            Operation::UrlDecode => {
+                let input: Vec<u8> = input.iter().map(|&byte| if byte == b'+' { b' ' } else { byte }).collect();
+                percent_decode(&input).collect()
+            }
+            Operation::UrlEncode => percent_encode(input, URL_COMPONENT).to_string().into_bytes(),
+            Operation::Decode(encoding) => decode_bytes(as_text(input)?, *encoding)?,
+            Operation::Encode(encoding) => encode_bytes(input, *encoding).into_bytes(),
+            Operation::Decompress(codec) => decompress(input, *codec)?,
+            Operation::Compress { codec, level } => compress(input, *codec, *level)?,
+            Operation::AutoDecode => auto_decode(input)?.bytes,
+            Operation::Hash(algorithm) => encode_bytes(&algorithm.hash(input), TextEncoding::Hex).into_bytes(),
+            Operation::JsonFormat { compact } => format_json(as_text(input)?, *compact)?.into_bytes(),
+            Operation::DecodeCharset(charset) => decode_charset(input, charset)?.into_bytes(),
+            Operation::EncodeCharset(charset) => encode_charset(as_text(input)?, charset)?,
+        };
+        Ok(output)
+    }
+}
+
+/// 命名的操作序列
+#[derive(PartialEq, Eq, Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Recipe {
+    pub name: String,
+    pub operations: Vec<Operation>,
+}
+
+/// 依次执行操作，返回每一步的输出，出错后不再执行后面的操作
+pub fn run_pipeline(input: &[u8], operations: &[Operation]) -> Vec<Result<Vec<u8>, PipelineError>> {
+    let mut results = vec![];
+    let mut current = input.to_vec();
+    for operation in operations {
+        match operation.apply(&current) {
+            Ok(output) => {
+                current = output.clone();
+                results.push(Ok(output));
+            }
+            Err(e) => {
+                results.push(Err(e));
+                break;
+            }
+        }
+    }
+    results
+}
+
+/// 后台执行的流水线
+pub struct PipelineHandle {
+    receiver: Receiver<Vec<Result<Vec<u8>, PipelineError>>>,
+}
+
+impl PipelineHandle {
+    /// 执行完成时返回每一步的输出，不阻塞
+    pub fn poll_result(&self) -> Option<Vec<Result<Vec<u8>, PipelineError>>> {
+        self.receiver.try_recv().ok()
+    }
+}
+
+/// 在后台线程执行流水线，高压缩级别可能需要较长时间，完成后调用 on_update
+pub fn run_pipeline_in_background(input: Vec<u8>, operations: Vec<Operation>, on_update: impl FnOnce() + Send + 'static) -> PipelineHandle {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        //界面已关闭时接收端不存在，忽略发送失败
+        let _ = sender.send(run_pipeline(&input, &operations));
+        on_update();
+    });
+    PipelineHandle { receiver }
+}
+
+fn as_text(bytes: &[u8]) -> Result<&str, PipelineError> {
+    std::str::from_utf8(bytes).map_err(|_| PipelineError::NotText)
+}
+
+/// 保存配方为 json 文件
+pub fn save_recipe(path: &Path, recipe: &Recipe) -> Result<(), RecipeError> {
+    write_json(path, recipe)
+}
+
+pub fn load_recipe(path: &Path) -> Result<Recipe, RecipeError> {
+    read_json(path)
+}
+
+/// 读取保存的配方列表，文件不存在时为空
+pub fn load_recipe_library(path: &Path) -> Result<Vec<Recipe>, RecipeError> {
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+    read_json(path)
+}
+
+/// 保存配方列表，目录不存在时创建
+pub fn save_recipe_library(path: &Path, recipes: &[Recipe]) -> Result<(), RecipeError> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|source| RecipeError::Io {
+            path: parent.to_path_buf(),
+            source,
+        })?;
+    }
+    write_json(path, recipes)
+}
+
+fn write_json<T: Serialize + ?Sized>(path: &Path, value: &T) -> Result<(), RecipeError> {
+    let json = serde_json::to_string_pretty(value).map_err(|source| RecipeError::Format {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    fs::write(path, json).map_err(|source| RecipeError::Io {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+fn read_json<T: DeserializeOwned>(path: &Path) -> Result<T, RecipeError> {
+    let json = fs::read_to_string(path).map_err(|source| RecipeError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    serde_json::from_str(&json).map_err(|source| RecipeError::Format {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run_to_text(input: &str, operations: &[Operation]) -> String {
+        let results = run_pipeline(input.as_bytes(), operations);
+        assert_eq!(results.len(), operations.len());
+        String::from_utf8(results.last().unwrap().as_ref().unwrap().clone()).unwrap()
+    }
+
+    #[test]
+    fn url_decode_base64_gunzip_and_format_json() {
+        let payload = encode_bytes(&compress(br#"{"a":[1]}"#, CompressionCodec::Gzip, 6).unwrap(), TextEncoding::Base64);
+        let url_encoded = percent_encode(payload.as_bytes(), URL_COMPONENT).to_string();
+        let operations = [
+            Operation::UrlDecode,
+            Operation::Decode(TextEncoding::Base64),
+            Operation::Decompress(CompressionCodec::Gzip),
+            Operation::JsonFormat { compact: false },
+        ];
+        assert_eq!(run_to_text(&url_encoded, &operations), "{\n  \"a\": [\n    1\n  ]\n}");
+    }
+
+    #[test]
+    fn encode_operations_reverse_decode_operations() {
+        let operations = [
+            Operation::EncodeCharset("gbk".to_string()),
+            Operation::Compress { codec: CompressionCodec::Zstd, level: 3 },
+            Operation::Encode(TextEncoding::Base64UrlSafe),
+            Operation::UrlEncode,
+            Operation::UrlDecode,
+            Operation::Decode(TextEncoding::Base64UrlSafe),
+            Operation::Decompress(CompressionCodec::Zstd),
+            Operation::DecodeCharset("GBK".to_string()),
+        ];
+        assert_eq!(run_to_text("中文 a+b", &operations), "中文 a+b");
+    }
+
+    #[test]
+    fn hash_outputs_hex_text() {
+        assert_eq!(run_to_text("abc", &[Operation::Hash(HashAlgorithm::Md5)]), "900150983cd24fb0d6963f7d28e17f72");
+        assert_eq!(run_to_text("abc", &[Operation::Hash(HashAlgorithm::Sha1)]), "a9993e364706816aba3e25717850c26c9cd0d89d");
+    }
+
+    #[test]
+    fn pipeline_stops_at_first_error() {
+        let operations = [
+            Operation::Decode(TextEncoding::Hex),
+            Operation::JsonFormat { compact: true },
+            Operation::UrlEncode,
+        ];
+        let results = run_pipeline(b"ff00", &operations);
+        assert_eq!(results.len(), 2);
+        assert!(matches!(results[1], Err(PipelineError::NotText)));
+    }
+
+    #[test]
+    fn recipe_round_trips_through_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.recipe.json");
+        let recipe = Recipe {
+            name: "解码".to_string(),
+            operations: Operation::templates(),
+        };
+        save_recipe(&path, &recipe).unwrap();
+        assert_eq!(load_recipe(&path).unwrap(), recipe);
+        assert!(matches!(load_recipe(&dir.path().join("missing.json")), Err(RecipeError::Io { .. })));
+    }
+
+    #[test]
+    fn recipe_library_round_trips_through_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config").join("recipes.json");
+        assert!(load_recipe_library(&path).unwrap().is_empty());
+        let recipes = vec![Recipe {
+            name: "解码".to_string(),
+            operations: Operation::templates(),
+        }];
+        save_recipe_library(&path, &recipes).unwrap();
+        assert_eq!(load_recipe_library(&path).unwrap(), recipes);
+    }
+
+    #[test]
+    fn background_pipeline_returns_every_step() {
+        let handle = run_pipeline_in_background(b"aGk=".to_vec(), vec![Operation::Decode(TextEncoding::Base64), Operation::JsonFormat { compact: true }], || {});
+        let results = loop {
+            if let Some(results) = handle.poll_result() {
+                break results;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        };
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].as_ref().unwrap(), b"hi");
+        assert!(results[1].is_err());
+    }
+}
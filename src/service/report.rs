@@ -0,0 +1,228 @@
+use std::cmp::Reverse;
+use std::time::{Duration, SystemTime};
+
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use thiserror::Error;
+
+use crate::service::folder_scan::FileNode;
+
+/// 默认的临时文件规则，匹配文件名
+pub const DEFAULT_TEMP_PATTERNS: [&str; 9] = ["*.tmp", "*.temp", "*~", "*.bak", "*.swp", "~$*", ".DS_Store", "Thumbs.db", "desktop.ini"];
+
+/// 清理报告错误
+#[derive(Error, Debug)]
+pub enum ReportError {
+    #[error("临时文件规则:{pattern} 格式错误:{source}")]
+    InvalidPattern {
+        pattern: String,
+        source: globset::Error,
+    },
+}
+
+/// 清理报告类型
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum ReportKind {
+    /// 所有子孙文件中最大的 N 个
+    Largest,
+    /// 超过 N 天没有修改的文件
+    Old,
+    /// 空文件和空文件夹
+    Empty,
+    /// 大小为 0 或文件名匹配临时文件规则的文件
+    Temp,
+}
+
+impl ReportKind {
+    pub fn iter() -> impl Iterator<Item = ReportKind> {
+        [ReportKind::Largest, ReportKind::Old, ReportKind::Empty, ReportKind::Temp].into_iter()
+    }
+
+    pub fn as_label(&self) -> &str {
+        match self {
+            ReportKind::Largest => "最大的文件",
+            ReportKind::Old => "长期未修改",
+            ReportKind::Empty => "空文件、空文件夹",
+            ReportKind::Temp => "临时文件",
+        }
+    }
+}
+
+/// 报告参数
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct ReportOptions {
+    /// 最大的文件报告的数量
+    pub top_n: usize,
+    /// 超过多少天未修改
+    pub older_than_days: u64,
+    /// 临时文件规则，匹配文件名
+    pub temp_patterns: Vec<String>,
+}
+
+impl Default for ReportOptions {
+    fn default() -> Self {
+        Self {
+            top_n: 100,
+            older_than_days: 365,
+            temp_patterns: DEFAULT_TEMP_PATTERNS.iter().map(|pattern| pattern.to_string()).collect(),
+        }
+    }
+}
+
+/// 在扫描结果中查找符合报告条件的文件、文件夹，返回不含子节点的节点
+///
+/// 最大的文件按大小倒序，长期未修改的文件按修改时间正序，其他按路径排序；空文件夹只返回最上层的
+pub fn build_report(root: &FileNode, kind: ReportKind, options: &ReportOptions, now: SystemTime) -> Result<Vec<FileNode>, ReportError> {
+    let mut nodes = vec![];
+    match kind {
+        ReportKind::Largest => {
+            collect(root, &mut nodes, &|node| node.is_file);
+            nodes.sort_by_key(|node| Reverse(node.size));
+            nodes.truncate(options.top_n);
+        }
+        ReportKind::Old => {
+            let threshold = now.checked_sub(Duration::from_secs(options.older_than_days * 24 * 60 * 60)).unwrap_or(SystemTime::UNIX_EPOCH);
+            collect(root, &mut nodes, &|node| node.is_file && node.modified.is_some_and(|modified| modified < threshold));
+            nodes.sort_by_key(|node| node.modified);
+        }
+        ReportKind::Empty => {
+            collect_empty(root, &mut nodes);
+            nodes.sort_by(|a, b| a.path.cmp(&b.path));
+        }
+        ReportKind::Temp => {
+            let patterns = temp_pattern_set(&options.temp_patterns)?;
+            collect(root, &mut nodes, &|node| node.is_file && (node.size == 0 || patterns.is_match(&node.name)));
+            nodes.sort_by(|a, b| a.path.cmp(&b.path));
+        }
+    }
+    Ok(nodes.into_iter().map(without_children).collect())
+}
+
+fn temp_pattern_set(patterns: &[String]) -> Result<GlobSet, ReportError> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob = Glob::new(pattern).map_err(|source| ReportError::InvalidPattern {
+            pattern: pattern.clone(),
+            source,
+        })?;
+        builder.add(glob);
+    }
+    builder.build().map_err(|source| ReportError::InvalidPattern {
+        pattern: patterns.join(" "),
+        source,
+    })
+}
+
+/// 收集所有满足条件的子孙节点
+fn collect<'a>(node: &'a FileNode, nodes: &mut Vec<&'a FileNode>, matches: &dyn Fn(&FileNode) -> bool) {
+    for child in &node.children {
+        if matches(child) {
+            nodes.push(child);
+        }
+        collect(child, nodes, matches);
+    }
+}
+
+/// 收集空文件和最上层的空文件夹
+fn collect_empty<'a>(node: &'a FileNode, nodes: &mut Vec<&'a FileNode>) {
+    for child in &node.children {
+        if child.is_file {
+            if child.size == 0 {
+                nodes.push(child);
+            }
+        } else if is_empty_dir(child) {
+            nodes.push(child);
+        } else {
+            collect_empty(child, nodes);
+        }
+    }
+}
+
+/// 没有文件、只包含空文件夹的文件夹，有被排除的内容时不算空
+fn is_empty_dir(node: &FileNode) -> bool {
+    !node.is_file && node.excluded_size == 0 && node.children.iter().all(is_empty_dir)
+}
+
+fn without_children(node: &FileNode) -> FileNode {
+    FileNode {
+        name: node.name.clone(),
+        path: node.path.clone(),
+        size: node.size,
+        allocated_size: node.allocated_size,
+        excluded_size: node.excluded_size,
+        modified: node.modified,
+        is_file: node.is_file,
+        children: vec![],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use super::*;
+
+    fn file(path: &str, size: u64, age_days: u64, now: SystemTime) -> FileNode {
+        let path = PathBuf::from(path);
+        FileNode {
+            name: path.file_name().unwrap().to_string_lossy().to_string(),
+            size,
+            allocated_size: size,
+            excluded_size: 0,
+            modified: Some(now - Duration::from_secs(age_days * 24 * 60 * 60)),
+            is_file: true,
+            children: vec![],
+            path,
+        }
+    }
+
+    fn test_tree(now: SystemTime) -> FileNode {
+        let mut root = FileNode::new_dir(Path::new("/data"));
+        let mut logs = FileNode::new_dir(Path::new("/data/logs"));
+        logs.add_child(file("/data/logs/a.log", 500, 400, now));
+        logs.add_child(file("/data/logs/b.log.tmp", 20, 1, now));
+        let mut cache = FileNode::new_dir(Path::new("/data/cache"));
+        cache.add_child(FileNode::new_dir(Path::new("/data/cache/empty")));
+        root.add_child(logs);
+        root.add_child(cache);
+        root.add_child(file("/data/big.iso", 1000, 10, now));
+        root.add_child(file("/data/empty.txt", 0, 800, now));
+        root
+    }
+
+    fn paths(nodes: &[FileNode]) -> Vec<&str> {
+        nodes.iter().map(|node| node.path.to_str().unwrap()).collect()
+    }
+
+    #[test]
+    fn largest_files_are_searched_in_all_folders() {
+        let now = SystemTime::now();
+        let options = ReportOptions { top_n: 2, ..ReportOptions::default() };
+        let report = build_report(&test_tree(now), ReportKind::Largest, &options, now).unwrap();
+        assert_eq!(paths(&report), vec!["/data/big.iso", "/data/logs/a.log"]);
+    }
+
+    #[test]
+    fn old_files_are_sorted_oldest_first() {
+        let now = SystemTime::now();
+        let report = build_report(&test_tree(now), ReportKind::Old, &ReportOptions::default(), now).unwrap();
+        assert_eq!(paths(&report), vec!["/data/empty.txt", "/data/logs/a.log"]);
+    }
+
+    #[test]
+    fn empty_report_returns_topmost_empty_folder() {
+        let now = SystemTime::now();
+        let report = build_report(&test_tree(now), ReportKind::Empty, &ReportOptions::default(), now).unwrap();
+        assert_eq!(paths(&report), vec!["/data/cache", "/data/empty.txt"]);
+        assert!(report[0].children.is_empty());
+    }
+
+    #[test]
+    fn temp_report_matches_patterns_and_zero_byte_files() {
+        let now = SystemTime::now();
+        let report = build_report(&test_tree(now), ReportKind::Temp, &ReportOptions::default(), now).unwrap();
+        assert_eq!(paths(&report), vec!["/data/empty.txt", "/data/logs/b.log.tmp"]);
+
+        let options = ReportOptions { temp_patterns: vec!["a[".to_string()], ..ReportOptions::default() };
+        assert!(build_report(&test_tree(now), ReportKind::Temp, &options, now).is_err());
+    }
+}
@@ -0,0 +1,255 @@
+use std::fs::{self, Metadata};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use globset::{Glob, GlobBuilder, GlobSet, GlobSetBuilder};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use log::error;
+use serde::{Deserialize, Serialize};
+
+use crate::service::folder_scan::ScanError;
+
+/// 会被读取的忽略规则文件
+const IGNORE_FILE_NAMES: [&str; 2] = [".gitignore", ".ignore"];
+
+/// 扫描选项，决定哪些文件、文件夹不统计
+#[derive(PartialEq, Eq, Clone, Default, Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct ScanOptions {
+    /// 排除的 glob，不含 / 时匹配文件名，含 / 时匹配相对扫描文件夹的路径，例如 `target`、`*.log`、`web/node_modules`
+    pub exclude_patterns: Vec<String>,
+    /// 遵守 .gitignore、.ignore 文件，同时跳过 .git 文件夹
+    pub respect_ignore_files: bool,
+    /// 不进入挂载在其他文件系统上的文件夹，只在类 unix 系统上生效
+    pub same_file_system: bool,
+}
+
+impl ScanOptions {
+    /// 是否会排除任何文件
+    pub fn is_empty(&self) -> bool {
+        self.exclude_patterns.is_empty() && !self.respect_ignore_files && !self.same_file_system
+    }
+
+    /// 从空格、逗号或换行分隔的文本解析排除规则
+    pub fn parse_patterns(text: &str) -> Vec<String> {
+        text.split(|c: char| c.is_whitespace() || c == ',' || c == '，')
+            .filter(|pattern| !pattern.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+}
+
+/// 被排除的原因
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum ExcludeReason {
+    Pattern,
+    IgnoreFile,
+    /// 其他文件系统，不统计大小
+    OtherFileSystem,
+}
+
+/// 从扫描文件夹到当前文件夹路径上所有的忽略规则，内层在后
+#[derive(Clone, Default)]
+pub struct IgnoreRules {
+    rules: Vec<Arc<Gitignore>>,
+}
+
+/// 根据 [`ScanOptions`] 判断扫描到的文件是否需要排除
+pub struct ScanFilter {
+    root: PathBuf,
+    /// 匹配文件名
+    name_globs: GlobSet,
+    /// 匹配相对路径
+    path_globs: GlobSet,
+    respect_ignore_files: bool,
+    root_device: Option<u64>,
+}
+
+impl ScanFilter {
+    pub fn new(root: &Path, options: &ScanOptions) -> Result<Self, ScanError> {
+        let mut name_globs = GlobSetBuilder::new();
+        let mut path_globs = GlobSetBuilder::new();
+        for pattern in &options.exclude_patterns {
+            let invalid_pattern = |source| ScanError::InvalidPattern {
+                pattern: pattern.clone(),
+                source,
+            };
+            let trimmed = pattern.trim_end_matches('/');
+            if trimmed.contains('/') {
+                let glob = GlobBuilder::new(trimmed.trim_start_matches('/')).literal_separator(true).build().map_err(invalid_pattern)?;
+                path_globs.add(glob);
+            } else {
+                name_globs.add(Glob::new(trimmed).map_err(invalid_pattern)?);
+            }
+        }
+        let build_error = |source| ScanError::InvalidPattern {
+            pattern: options.exclude_patterns.join(" "),
+            source,
+        };
+        Ok(Self {
+            root: root.to_path_buf(),
+            name_globs: name_globs.build().map_err(build_error)?,
+            path_globs: path_globs.build().map_err(build_error)?,
+            respect_ignore_files: options.respect_ignore_files,
+            root_device: if options.same_file_system { device_id(root) } else { None },
+        })
+    }
+
+    /// 进入文件夹时读取其中的忽略规则文件
+    pub fn rules_for_dir(&self, dir: &Path, parent_rules: &IgnoreRules) -> IgnoreRules {
+        if !self.respect_ignore_files {
+            return parent_rules.clone();
+        }
+        let mut builder = GitignoreBuilder::new(dir);
+        let mut found = false;
+        for file_name in IGNORE_FILE_NAMES {
+            let ignore_file = dir.join(file_name);
+            if !ignore_file.is_file() {
+                continue;
+            }
+            found = true;
+            if let Some(e) = builder.add(&ignore_file) {
+                error!("读取忽略规则:{ignore_file:?} 失败:{e}");
+            }
+        }
+        if !found {
+            return parent_rules.clone();
+        }
+        let mut rules = parent_rules.clone();
+        match builder.build() {
+            Ok(gitignore) => rules.rules.push(Arc::new(gitignore)),
+            Err(e) => error!("解析忽略规则:{dir:?} 失败:{e}"),
+        }
+        rules
+    }
+
+    /// 从扫描文件夹开始依次读取上级文件夹中的忽略规则，得到判断 path 是否排除时使用的规则
+    ///
+    /// path 不在扫描文件夹下、是扫描文件夹本身或上级文件夹被排除时返回 None
+    pub fn rules_for_entry(&self, path: &Path) -> Option<IgnoreRules> {
+        let relative_path = path.strip_prefix(&self.root).ok()?;
+        let mut rules = self.rules_for_dir(&self.root, &IgnoreRules::default());
+        let mut dir = self.root.clone();
+        for component in relative_path.parent()?.components() {
+            dir.push(component);
+            let metadata = fs::symlink_metadata(&dir).ok()?;
+            if self.exclusion(&dir, &metadata, &rules).is_some() {
+                return None;
+            }
+            rules = self.rules_for_dir(&dir, &rules);
+        }
+        Some(rules)
+    }
+
+    /// 文件、文件夹需要排除时返回原因
+    pub fn exclusion(&self, path: &Path, metadata: &Metadata, rules: &IgnoreRules) -> Option<ExcludeReason> {
+        let is_dir = metadata.is_dir();
+        if let Some(name) = path.file_name() {
+            if self.name_globs.is_match(name) {
+                return Some(ExcludeReason::Pattern);
+            }
+            if self.respect_ignore_files && is_dir && name == ".git" {
+                return Some(ExcludeReason::IgnoreFile);
+            }
+        }
+        if let Ok(relative_path) = path.strip_prefix(&self.root) {
+            if self.path_globs.is_match(relative_path) {
+                return Some(ExcludeReason::Pattern);
+            }
+        }
+        //内层规则优先，! 开头的规则可以取消外层的忽略
+        for gitignore in rules.rules.iter().rev() {
+            let matched = gitignore.matched(path, is_dir);
+            if matched.is_ignore() {
+                return Some(ExcludeReason::IgnoreFile);
+            }
+            if matched.is_whitelist() {
+                break;
+            }
+        }
+        if is_dir && self.root_device.is_some() && metadata_device_id(metadata) != self.root_device {
+            return Some(ExcludeReason::OtherFileSystem);
+        }
+        None
+    }
+}
+
+fn device_id(path: &Path) -> Option<u64> {
+    fs::metadata(path).ok().and_then(|metadata| metadata_device_id(&metadata))
+}
+
+#[cfg(unix)]
+fn metadata_device_id(metadata: &Metadata) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+    Some(metadata.dev())
+}
+
+#[cfg(not(unix))]
+fn metadata_device_id(_metadata: &Metadata) -> Option<u64> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exclusion(filter: &ScanFilter, path: &Path, rules: &IgnoreRules) -> Option<ExcludeReason> {
+        filter.exclusion(path, &fs::symlink_metadata(path).unwrap(), rules)
+    }
+
+    #[test]
+    fn patterns_match_names_and_relative_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("web/node_modules")).unwrap();
+        fs::create_dir_all(dir.path().join("node_modules")).unwrap();
+        fs::create_dir_all(dir.path().join("target")).unwrap();
+        fs::write(dir.path().join("app.log"), "").unwrap();
+        fs::write(dir.path().join("app.txt"), "").unwrap();
+        let options = ScanOptions {
+            exclude_patterns: ScanOptions::parse_patterns("target/, *.log\nweb/node_modules"),
+            ..ScanOptions::default()
+        };
+        let filter = ScanFilter::new(dir.path(), &options).unwrap();
+        let rules = IgnoreRules::default();
+        assert_eq!(exclusion(&filter, &dir.path().join("target"), &rules), Some(ExcludeReason::Pattern));
+        assert_eq!(exclusion(&filter, &dir.path().join("app.log"), &rules), Some(ExcludeReason::Pattern));
+        assert_eq!(exclusion(&filter, &dir.path().join("web/node_modules"), &rules), Some(ExcludeReason::Pattern));
+        assert_eq!(exclusion(&filter, &dir.path().join("node_modules"), &rules), None);
+        assert_eq!(exclusion(&filter, &dir.path().join("app.txt"), &rules), None);
+    }
+
+    #[test]
+    fn invalid_pattern_is_reported() {
+        let options = ScanOptions {
+            exclude_patterns: vec!["a[".to_string()],
+            ..ScanOptions::default()
+        };
+        let result = ScanFilter::new(Path::new("/"), &options);
+        assert!(matches!(result, Err(ScanError::InvalidPattern { .. })));
+    }
+
+    #[test]
+    fn ignore_files_are_respected_per_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("sub/build")).unwrap();
+        fs::create_dir_all(dir.path().join(".git")).unwrap();
+        fs::write(dir.path().join(".gitignore"), "*.tmp\n").unwrap();
+        fs::write(dir.path().join("sub/.ignore"), "build/\n!keep.tmp\n").unwrap();
+        fs::write(dir.path().join("a.tmp"), "").unwrap();
+        fs::write(dir.path().join("sub/keep.tmp"), "").unwrap();
+        fs::write(dir.path().join("sub/other.tmp"), "").unwrap();
+
+        let options = ScanOptions {
+            respect_ignore_files: true,
+            ..ScanOptions::default()
+        };
+        let filter = ScanFilter::new(dir.path(), &options).unwrap();
+        let root_rules = filter.rules_for_dir(dir.path(), &IgnoreRules::default());
+        let sub_rules = filter.rules_for_dir(&dir.path().join("sub"), &root_rules);
+        assert_eq!(exclusion(&filter, &dir.path().join("a.tmp"), &root_rules), Some(ExcludeReason::IgnoreFile));
+        assert_eq!(exclusion(&filter, &dir.path().join(".git"), &root_rules), Some(ExcludeReason::IgnoreFile));
+        assert_eq!(exclusion(&filter, &dir.path().join("sub/build"), &sub_rules), Some(ExcludeReason::IgnoreFile));
+        assert_eq!(exclusion(&filter, &dir.path().join("sub/other.tmp"), &sub_rules), Some(ExcludeReason::IgnoreFile));
+        assert_eq!(exclusion(&filter, &dir.path().join("sub/keep.tmp"), &sub_rules), None);
+    }
+}
@@ -0,0 +1,291 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::service::folder_scan::{format_time, FileNode};
+
+/// 快照文件格式版本，格式不兼容时增加
+const SNAPSHOT_VERSION: u32 = 1;
+
+/// 快照读写错误
+#[derive(Error, Debug)]
+pub enum SnapshotError {
+    #[error("读写快照文件:{path:?} 失败:{source}")]
+    Io {
+        path: PathBuf,
+        source: io::Error,
+    },
+    #[error("快照文件:{path:?} 格式错误:{source}")]
+    Format {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+    #[error("快照文件版本:{0} 不支持")]
+    Version(u32),
+}
+
+/// 一次扫描结果的快照，保存所有文件、文件夹的相对路径、大小和修改时间
+#[derive(PartialEq, Clone, Serialize, Deserialize, Debug)]
+pub struct Snapshot {
+    pub version: u32,
+    /// 扫描的文件夹
+    pub root: PathBuf,
+    /// 创建时间，格式为 yyyy-MM-dd HH:mm:ss
+    pub created: String,
+    pub entries: Vec<SnapshotEntry>,
+}
+
+#[derive(PartialEq, Clone, Serialize, Deserialize, Debug)]
+pub struct SnapshotEntry {
+    /// 相对扫描文件夹的路径，使用 / 分隔，不同系统之间也能对比
+    pub path: String,
+    pub size: u64,
+    /// 修改时间，距 1970-01-01 的秒数
+    pub modified: Option<u64>,
+    pub is_file: bool,
+}
+
+impl Snapshot {
+    pub fn from_tree(root: &FileNode) -> Self {
+        let mut entries = vec![];
+        collect_entries(root, &root.path, &mut entries);
+        Self {
+            version: SNAPSHOT_VERSION,
+            root: root.path.clone(),
+            created: format_time(Some(SystemTime::now())),
+            entries,
+        }
+    }
+
+    /// 所有文件的总大小
+    pub fn total_size(&self) -> u64 {
+        self.entries.iter().filter(|entry| entry.is_file).map(|entry| entry.size).sum()
+    }
+}
+
+fn collect_entries(node: &FileNode, root: &Path, entries: &mut Vec<SnapshotEntry>) {
+    for child in &node.children {
+        let Ok(relative_path) = child.path.strip_prefix(root) else {
+            continue;
+        };
+        let path: Vec<String> = relative_path.components().map(|component| component.as_os_str().to_string_lossy().to_string()).collect();
+        entries.push(SnapshotEntry {
+            path: path.join("/"),
+            size: child.size,
+            modified: child.modified.and_then(|time| time.duration_since(UNIX_EPOCH).ok()).map(|duration| duration.as_secs()),
+            is_file: child.is_file,
+        });
+        if !child.is_file {
+            collect_entries(child, root, entries);
+        }
+    }
+}
+
+/// 保存快照为 json 文件
+pub fn save_snapshot(path: &Path, snapshot: &Snapshot) -> Result<(), SnapshotError> {
+    let json = serde_json::to_string(snapshot).map_err(|source| SnapshotError::Format {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    fs::write(path, json).map_err(|source| SnapshotError::Io {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+pub fn load_snapshot(path: &Path) -> Result<Snapshot, SnapshotError> {
+    let json = fs::read_to_string(path).map_err(|source| SnapshotError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    let snapshot: Snapshot = serde_json::from_str(&json).map_err(|source| SnapshotError::Format {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    if snapshot.version != SNAPSHOT_VERSION {
+        return Err(SnapshotError::Version(snapshot.version));
+    }
+    Ok(snapshot)
+}
+
+/// 变化类型
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum DiffKind {
+    Added,
+    Removed,
+    Grown,
+    Shrunk,
+}
+
+impl DiffKind {
+    pub fn iter() -> impl Iterator<Item = DiffKind> {
+        [DiffKind::Added, DiffKind::Removed, DiffKind::Grown, DiffKind::Shrunk].into_iter()
+    }
+
+    pub fn as_label(&self) -> &str {
+        match self {
+            DiffKind::Added => "新增",
+            DiffKind::Removed => "删除",
+            DiffKind::Grown => "增大",
+            DiffKind::Shrunk => "减小",
+        }
+    }
+}
+
+/// 两次快照之间一个文件或文件夹的变化，大小不变的条目不会出现
+#[derive(PartialEq, Clone, Debug)]
+pub struct SnapshotDiff {
+    pub path: String,
+    pub kind: DiffKind,
+    pub is_file: bool,
+    pub old_size: Option<u64>,
+    pub new_size: Option<u64>,
+}
+
+impl SnapshotDiff {
+    /// 大小变化，增大为正数
+    pub fn delta(&self) -> i64 {
+        self.new_size.unwrap_or(0) as i64 - self.old_size.unwrap_or(0) as i64
+    }
+}
+
+/// 对比两次快照，结果按变化量的绝对值倒序排列
+pub fn diff_snapshots(old: &Snapshot, new: &Snapshot) -> Vec<SnapshotDiff> {
+    let old_entries: HashMap<&str, &SnapshotEntry> = old.entries.iter().map(|entry| (entry.path.as_str(), entry)).collect();
+    let new_entries: HashMap<&str, &SnapshotEntry> = new.entries.iter().map(|entry| (entry.path.as_str(), entry)).collect();
+
+    let mut diffs = vec![];
+    for new_entry in &new.entries {
+        let old_entry = old_entries.get(new_entry.path.as_str());
+        let kind = match old_entry {
+            None => DiffKind::Added,
+            Some(old_entry) if new_entry.size > old_entry.size => DiffKind::Grown,
+            Some(old_entry) if new_entry.size < old_entry.size => DiffKind::Shrunk,
+            Some(_) => continue,
+        };
+        diffs.push(SnapshotDiff {
+            path: new_entry.path.clone(),
+            kind,
+            is_file: new_entry.is_file,
+            old_size: old_entry.map(|entry| entry.size),
+            new_size: Some(new_entry.size),
+        });
+    }
+    for old_entry in &old.entries {
+        if new_entries.contains_key(old_entry.path.as_str()) {
+            continue;
+        }
+        diffs.push(SnapshotDiff {
+            path: old_entry.path.clone(),
+            kind: DiffKind::Removed,
+            is_file: old_entry.is_file,
+            old_size: Some(old_entry.size),
+            new_size: None,
+        });
+    }
+    sort_diffs(&mut diffs, DiffSortColumn::Change, true);
+    diffs
+}
+
+/// 对比结果的排序列
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum DiffSortColumn {
+    Path,
+    /// 变化量的绝对值
+    Change,
+}
+
+/// 排序对比结果，排序值相同时按路径正序
+pub fn sort_diffs(diffs: &mut [SnapshotDiff], column: DiffSortColumn, descending: bool) {
+    diffs.sort_by(|a, b| {
+        let ordering = match column {
+            DiffSortColumn::Path => a.path.cmp(&b.path),
+            DiffSortColumn::Change => a.delta().unsigned_abs().cmp(&b.delta().unsigned_abs()),
+        };
+        let ordering = if descending { ordering.reverse() } else { ordering };
+        ordering.then_with(|| a.path.cmp(&b.path))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(path: &str, size: u64, is_file: bool) -> SnapshotEntry {
+        SnapshotEntry {
+            path: path.to_string(),
+            size,
+            modified: None,
+            is_file,
+        }
+    }
+
+    fn snapshot(entries: Vec<SnapshotEntry>) -> Snapshot {
+        Snapshot {
+            version: SNAPSHOT_VERSION,
+            root: PathBuf::from("/data"),
+            created: "2024-01-01 00:00:00".to_string(),
+            entries,
+        }
+    }
+
+    #[test]
+    fn diff_reports_added_removed_grown_and_shrunk() {
+        let old = snapshot(vec![
+            entry("logs", 100, false),
+            entry("logs/a.log", 100, true),
+            entry("old.bin", 50, true),
+            entry("same.txt", 10, true),
+            entry("cache.db", 80, true),
+        ]);
+        let new = snapshot(vec![
+            entry("logs", 400, false),
+            entry("logs/a.log", 100, true),
+            entry("logs/b.log", 300, true),
+            entry("same.txt", 10, true),
+            entry("cache.db", 20, true),
+        ]);
+        let diffs = diff_snapshots(&old, &new);
+        let summary: Vec<(&str, DiffKind, i64)> = diffs.iter().map(|diff| (diff.path.as_str(), diff.kind, diff.delta())).collect();
+        assert_eq!(summary, vec![
+            ("logs", DiffKind::Grown, 300),
+            ("logs/b.log", DiffKind::Added, 300),
+            ("cache.db", DiffKind::Shrunk, -60),
+            ("old.bin", DiffKind::Removed, -50),
+        ]);
+    }
+
+    #[test]
+    fn sort_diffs_by_path() {
+        let old = snapshot(vec![entry("b", 1, true), entry("a", 5, true)]);
+        let new = snapshot(vec![]);
+        let mut diffs = diff_snapshots(&old, &new);
+        sort_diffs(&mut diffs, DiffSortColumn::Path, false);
+        let paths: Vec<&str> = diffs.iter().map(|diff| diff.path.as_str()).collect();
+        assert_eq!(paths, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn snapshot_from_tree_round_trips_through_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let data = dir.path().join("data");
+        fs::create_dir_all(data.join("sub")).unwrap();
+        fs::write(data.join("sub/a.txt"), "12345").unwrap();
+        let tree = crate::service::folder_scan::scan_tree(&data, &Default::default()).unwrap();
+
+        let snapshot = Snapshot::from_tree(&tree);
+        let paths: Vec<&str> = snapshot.entries.iter().map(|entry| entry.path.as_str()).collect();
+        assert_eq!(paths, vec!["sub", "sub/a.txt"]);
+        assert_eq!(snapshot.total_size(), 5);
+
+        let file = dir.path().join("data.snapshot.json");
+        save_snapshot(&file, &snapshot).unwrap();
+        assert_eq!(load_snapshot(&file).unwrap(), snapshot);
+        assert!(matches!(load_snapshot(&dir.path().join("missing.json")), Err(SnapshotError::Io { .. })));
+    }
+}
@@ -0,0 +1,134 @@
+/// 矩形区域，与界面库无关
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct LayoutRect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl LayoutRect {
+    pub fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Self { x, y, width, height }
+    }
+
+    pub fn area(&self) -> f32 {
+        self.width * self.height
+    }
+}
+
+/// 使用 squarified 算法计算矩形树图布局，矩形面积与 sizes 成正比，尽量接近正方形
+///
+/// 返回结果与 sizes 一一对应，sizes 按从大到小排列时效果最好，大小为 0 的项得到面积为 0 的矩形
+pub fn squarify(sizes: &[u64], bounds: LayoutRect) -> Vec<LayoutRect> {
+    let mut rects = vec![LayoutRect::new(bounds.x, bounds.y, 0.0, 0.0); sizes.len()];
+    let total: u64 = sizes.iter().sum();
+    if total == 0 || bounds.area() <= 0.0 {
+        return rects;
+    }
+    let scale = bounds.area() as f64 / total as f64;
+    let areas: Vec<f64> = sizes.iter().map(|size| *size as f64 * scale).collect();
+
+    let mut remaining = bounds;
+    let mut row: Vec<usize> = vec![];
+    for (index, area) in areas.iter().enumerate() {
+        if *area <= 0.0 {
+            continue;
+        }
+        let side = remaining.width.min(remaining.height) as f64;
+        let mut candidate = row.clone();
+        candidate.push(index);
+        if row.is_empty() || worst_ratio(&candidate, &areas, side) <= worst_ratio(&row, &areas, side) {
+            row = candidate;
+            continue;
+        }
+        remaining = layout_row(&row, &areas, remaining, &mut rects);
+        row = vec![index];
+    }
+    if !row.is_empty() {
+        layout_row(&row, &areas, remaining, &mut rects);
+    }
+    rects
+}
+
+/// 一行中最差的长宽比，越接近 1 越好
+fn worst_ratio(row: &[usize], areas: &[f64], side: f64) -> f64 {
+    let sum: f64 = row.iter().map(|index| areas[*index]).sum();
+    if sum <= 0.0 || side <= 0.0 {
+        return f64::MAX;
+    }
+    let max = row.iter().map(|index| areas[*index]).fold(f64::MIN, f64::max);
+    let min = row.iter().map(|index| areas[*index]).fold(f64::MAX, f64::min);
+    let side_squared = side * side;
+    let sum_squared = sum * sum;
+    (side_squared * max / sum_squared).max(sum_squared / (side_squared * min))
+}
+
+/// 沿较短边排列一行，返回剩余区域
+fn layout_row(row: &[usize], areas: &[f64], remaining: LayoutRect, rects: &mut [LayoutRect]) -> LayoutRect {
+    let row_area: f64 = row.iter().map(|index| areas[*index]).sum();
+    if remaining.width >= remaining.height {
+        //竖着排在左侧
+        let width = (row_area / remaining.height as f64) as f32;
+        let mut y = remaining.y;
+        for index in row {
+            let height = (areas[*index] / width as f64) as f32;
+            rects[*index] = LayoutRect::new(remaining.x, y, width, height);
+            y += height;
+        }
+        LayoutRect::new(remaining.x + width, remaining.y, (remaining.width - width).max(0.0), remaining.height)
+    } else {
+        //横着排在顶部
+        let height = (row_area / remaining.width as f64) as f32;
+        let mut x = remaining.x;
+        for index in row {
+            let width = (areas[*index] / height as f64) as f32;
+            rects[*index] = LayoutRect::new(x, remaining.y, width, height);
+            x += width;
+        }
+        LayoutRect::new(remaining.x, remaining.y + height, remaining.width, (remaining.height - height).max(0.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: f32 = 0.01;
+
+    #[test]
+    fn squarify_areas_are_proportional_to_sizes() {
+        let bounds = LayoutRect::new(0.0, 0.0, 600.0, 400.0);
+        let sizes = [6, 6, 4, 3, 2, 2, 1];
+        let rects = squarify(&sizes, bounds);
+        let total: u64 = sizes.iter().sum();
+        for (size, rect) in sizes.iter().zip(&rects) {
+            let expected = bounds.area() * *size as f32 / total as f32;
+            assert!((rect.area() - expected).abs() < 1.0, "{rect:?} expected area {expected}");
+        }
+    }
+
+    #[test]
+    fn squarify_rects_stay_inside_bounds() {
+        let bounds = LayoutRect::new(10.0, 20.0, 300.0, 100.0);
+        for rect in squarify(&[50, 30, 10, 5, 5], bounds) {
+            assert!(rect.x >= bounds.x - EPSILON && rect.y >= bounds.y - EPSILON);
+            assert!(rect.x + rect.width <= bounds.x + bounds.width + EPSILON);
+            assert!(rect.y + rect.height <= bounds.y + bounds.height + EPSILON);
+        }
+    }
+
+    #[test]
+    fn squarify_single_item_fills_bounds() {
+        let bounds = LayoutRect::new(0.0, 0.0, 200.0, 100.0);
+        assert_eq!(squarify(&[42], bounds), vec![bounds]);
+    }
+
+    #[test]
+    fn squarify_zero_sizes_get_empty_rects() {
+        let rects = squarify(&[10, 0], LayoutRect::new(0.0, 0.0, 100.0, 100.0));
+        assert_eq!(rects[1].area(), 0.0);
+        assert!((rects[0].area() - 10_000.0).abs() < EPSILON);
+        assert!(squarify(&[0, 0], LayoutRect::new(0.0, 0.0, 100.0, 100.0)).iter().all(|rect| rect.area() == 0.0));
+    }
+}
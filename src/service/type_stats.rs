@@ -0,0 +1,215 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Arc;
+use std::thread;
+
+use log::{error, info};
+
+use crate::service::file_list::file_extension;
+
+/// 每种类型保留的最大文件数
+pub const LARGEST_FILES_PER_TYPE: usize = 5;
+/// 没有扩展名、无法识别类型的文件分组名
+pub const UNKNOWN_TYPE: &str = "(无)";
+
+/// 统计分组方式
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum TypeGrouping {
+    /// 按扩展名，只看文件名
+    Extension,
+    /// 按文件头识别的 MIME 类型，识别不出时按扩展名推测
+    Mime,
+}
+
+impl TypeGrouping {
+    pub fn as_label(&self) -> &str {
+        match self {
+            TypeGrouping::Extension => "扩展名",
+            TypeGrouping::Mime => "MIME 类型",
+        }
+    }
+}
+
+/// 同一类型文件的统计
+#[derive(PartialEq, Clone, Debug)]
+pub struct TypeStats {
+    /// 扩展名或 MIME 类型
+    pub key: String,
+    pub count: u64,
+    pub total_size: u64,
+    /// 最大的几个文件，按大小倒序
+    pub largest_files: Vec<(PathBuf, u64)>,
+}
+
+impl TypeStats {
+    fn new(key: String) -> Self {
+        Self {
+            key,
+            count: 0,
+            total_size: 0,
+            largest_files: vec![],
+        }
+    }
+
+    fn add(&mut self, path: PathBuf, size: u64) {
+        self.count += 1;
+        self.total_size += size;
+        if self.largest_files.len() == LARGEST_FILES_PER_TYPE
+            && self.largest_files.last().is_some_and(|(_, smallest)| *smallest >= size) {
+            return;
+        }
+        let index = self.largest_files.partition_point(|(_, largest)| *largest >= size);
+        self.largest_files.insert(index, (path, size));
+        self.largest_files.truncate(LARGEST_FILES_PER_TYPE);
+    }
+}
+
+/// 统计进度，工作线程更新，界面线程读取
+#[derive(Default, Debug)]
+pub struct TypeStatsProgress {
+    files_visited: AtomicU64,
+    cancelled: AtomicBool,
+}
+
+impl TypeStatsProgress {
+    pub fn files_visited(&self) -> u64 {
+        self.files_visited.load(Ordering::Relaxed)
+    }
+
+    /// 请求取消统计，工作线程会尽快退出
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// 后台统计的句柄
+pub struct TypeStatsHandle {
+    receiver: Receiver<Vec<TypeStats>>,
+    progress: Arc<TypeStatsProgress>,
+}
+
+impl TypeStatsHandle {
+    pub fn progress(&self) -> &TypeStatsProgress {
+        &self.progress
+    }
+
+    pub fn cancel(&self) {
+        self.progress.cancel();
+    }
+
+    /// 统计完成时返回结果，不阻塞
+    pub fn poll_result(&self) -> Option<Vec<TypeStats>> {
+        self.receiver.try_recv().ok()
+    }
+}
+
+/// 在后台线程统计，按 MIME 统计需要读取文件头，文件多时比较慢
+pub fn type_stats_in_background(files: Vec<(PathBuf, u64)>, grouping: TypeGrouping, on_update: impl Fn() + Send + 'static) -> TypeStatsHandle {
+    let (sender, receiver) = mpsc::channel();
+    let progress = Arc::new(TypeStatsProgress::default());
+    let worker_progress = progress.clone();
+    thread::spawn(move || {
+        info!("开始按{}统计 {} 个文件", grouping.as_label(), files.len());
+        let stats = type_stats(files, grouping, &worker_progress);
+        if worker_progress.is_cancelled() {
+            return;
+        }
+        //界面已关闭时接收端不存在，忽略发送失败
+        let _ = sender.send(stats);
+        on_update();
+    });
+    TypeStatsHandle {
+        receiver,
+        progress,
+    }
+}
+
+/// 按类型统计文件数量、总大小和最大的文件，结果按总大小倒序排列
+pub fn type_stats(files: Vec<(PathBuf, u64)>, grouping: TypeGrouping, progress: &TypeStatsProgress) -> Vec<TypeStats> {
+    let mut by_type: HashMap<String, TypeStats> = HashMap::new();
+    for (path, size) in files {
+        if progress.is_cancelled() {
+            return vec![];
+        }
+        progress.files_visited.fetch_add(1, Ordering::Relaxed);
+        let key = match grouping {
+            TypeGrouping::Extension => extension_key(&path),
+            TypeGrouping::Mime => mime_key(&path),
+        };
+        by_type.entry(key.clone()).or_insert_with(|| TypeStats::new(key)).add(path, size);
+    }
+    let mut stats: Vec<TypeStats> = by_type.into_values().collect();
+    stats.sort_by(|a, b| b.total_size.cmp(&a.total_size).then_with(|| a.key.cmp(&b.key)));
+    stats
+}
+
+fn extension_key(path: &Path) -> String {
+    let name = path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+    file_extension(&name).map_or_else(|| UNKNOWN_TYPE.to_string(), |extension| format!(".{extension}"))
+}
+
+/// 优先根据文件头识别，文本等没有特征的文件按扩展名推测
+fn mime_key(path: &Path) -> String {
+    match infer::get_from_path(path) {
+        Ok(Some(kind)) => return kind.mime_type().to_string(),
+        Ok(None) => {}
+        Err(e) => error!("读取文件:{path:?} 失败:{e}"),
+    }
+    mime_guess::from_path(path).first().map_or_else(|| UNKNOWN_TYPE.to_string(), |mime| mime.essence_str().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    fn file(path: &str, size: u64) -> (PathBuf, u64) {
+        (PathBuf::from(path), size)
+    }
+
+    #[test]
+    fn type_stats_groups_by_extension() {
+        let files = vec![
+            file("/data/a.log", 10),
+            file("/data/b.LOG", 30),
+            file("/data/c.png", 100),
+            file("/data/Makefile", 1),
+        ];
+        let stats = type_stats(files, TypeGrouping::Extension, &TypeStatsProgress::default());
+        let keys: Vec<&str> = stats.iter().map(|stats| stats.key.as_str()).collect();
+        assert_eq!(keys, vec![".png", ".log", UNKNOWN_TYPE]);
+        assert_eq!(stats[1].count, 2);
+        assert_eq!(stats[1].total_size, 40);
+        assert_eq!(stats[1].largest_files, vec![file("/data/b.LOG", 30), file("/data/a.log", 10)]);
+    }
+
+    #[test]
+    fn type_stats_keeps_largest_files() {
+        let files: Vec<(PathBuf, u64)> = (1..=8).map(|size| file(&format!("/data/{size}.bin"), size)).collect();
+        let stats = type_stats(files, TypeGrouping::Extension, &TypeStatsProgress::default());
+        let sizes: Vec<u64> = stats[0].largest_files.iter().map(|(_, size)| *size).collect();
+        assert_eq!(sizes, vec![8, 7, 6, 5, 4]);
+        assert_eq!(stats[0].count, 8);
+    }
+
+    #[test]
+    fn type_stats_detects_mime_from_content() {
+        let dir = tempfile::tempdir().unwrap();
+        //扩展名不对，按文件头识别为 png
+        let png = dir.path().join("image.dat");
+        fs::write(&png, [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, 0, 0, 0, 0]).unwrap();
+        let text = dir.path().join("notes.txt");
+        fs::write(&text, "hello").unwrap();
+        let files = vec![(png, 12), (text, 5)];
+        let stats = type_stats(files, TypeGrouping::Mime, &TypeStatsProgress::default());
+        let keys: Vec<&str> = stats.iter().map(|stats| stats.key.as_str()).collect();
+        assert_eq!(keys, vec!["image/png", "text/plain"]);
+    }
+}
@@ -0,0 +1,26 @@
+use egui::Ui;
+use serde::{Deserialize, Serialize};
+
+/// 应用设置，随工作区一起保存
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct AppSettings {
+    /// 退出时是否保存工具中的输入内容，处理敏感数据时可以关闭
+    pub persist_tool_state: bool,
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            persist_tool_state: true,
+        }
+    }
+}
+
+impl AppSettings {
+    /// 绘制设置菜单
+    pub fn show(&mut self, ui: &mut Ui) {
+        ui.checkbox(&mut self.persist_tool_state, "退出时保存工具内容")
+            .on_hover_text("关闭后只保存打开的标签，不保存输入的文本、选择的文件夹等内容");
+    }
+}
@@ -1,22 +1,57 @@
-use std::io::{Read, Write};
-use std::time::Duration;
+use std::path::{Path, PathBuf};
 
-use base64::Engine;
-use base64::engine::general_purpose;
-use eframe::emath::Align;
-use egui::{Button, Context, Layout, TextEdit, Ui};
-use flate2::{Compression, write::GzEncoder};
-use log::{debug, info};
+use egui::{Button, Context, TextEdit, Ui};
+use log::{debug, error};
+use serde::{Deserialize, Serialize};
 
-use crate::tool::get_seconds_since_epoch;
+use crate::notification;
+use crate::service::charset::{decode_charset, COMMON_CHARSETS};
+use crate::service::codec::{decode_compressed_bytes, encode_bytes, encode_compressed_bytes, encode_compressed_text, CompressionCodec, TextEncoding};
+use crate::service::codec_detect::{auto_decode, format_chain, AutoDecoded};
+use crate::tool::hex_view::{save_bytes_to_file, show_hex_view};
+use crate::tool::{Tool, ToolCategory};
+use crate::util::file_util::read_dropped_file;
 
 #[derive(PartialEq, Clone)]
 pub struct DecompressCompressStrTool {
     pub source_text: String,
     pub result_text: String,
-    pub error_msg: String,
-    pub error_start: u64,
     pub show_count: u64,
+    /// 压缩后数据的文本编码
+    encoding: TextEncoding,
+    /// 压缩算法
+    codec: CompressionCodec,
+    /// 压缩级别，只在压缩时使用
+    level: u32,
+    /// 解压时自动识别编码和压缩格式
+    auto_detect: bool,
+    /// 上次自动识别出的编码、压缩链
+    detected_chain: Option<String>,
+    /// 解压结果不是 UTF-8 文本时的原始字节，以 hex 显示
+    result_bytes: Option<Vec<u8>>,
+    /// 把二进制结果按该字符集转换为文本
+    charset: String,
+    /// 二进制结果按字符集转换后的文本，与原始字节一起显示，原始字节不变
+    charset_text: Option<String>,
+}
+
+/// 退出时保存的工具状态
+#[derive(Serialize, Deserialize)]
+struct DecompressCompressStrState {
+    source_text: String,
+    result_text: String,
+    #[serde(default)]
+    encoding: TextEncoding,
+    #[serde(default)]
+    codec: CompressionCodec,
+    #[serde(default = "default_level")]
+    level: u32,
+    #[serde(default)]
+    auto_detect: bool,
+}
+
+fn default_level() -> u32 {
+    CompressionCodec::default().default_level()
 }
 
 impl DecompressCompressStrTool {
@@ -24,131 +59,293 @@ impl DecompressCompressStrTool {
         Self {
             source_text: "".to_string(),
             result_text: "".to_string(),
-            error_msg: "".to_string(),
-            error_start: 1,
             show_count: 0,
+            encoding: TextEncoding::default(),
+            codec: CompressionCodec::default(),
+            level: default_level(),
+            auto_detect: false,
+            detected_chain: None,
+            result_bytes: None,
+            charset: "GBK".to_string(),
+            charset_text: None,
         }
     }
 
-    pub fn show(&mut self, ctx: &egui::Context, tool_main_ui: &mut Ui) {
-        self.show_count = self.show_count + 1;
-        debug!("DecompressCompressStrTool show:{}", self.show_count);
-        tool_main_ui.horizontal_top(|tool_main_ui| {
-            let available_width = tool_main_ui.available_width();
-            // 使用相同的尺寸配置两个 TextEdit 控件
-            let half_width = (available_width - 40.0) * 0.5;
-            let text_edit_size = egui::vec2(half_width, tool_main_ui.available_height());
+    /// 读取拖入的文件，自动识别编码和压缩格式后解压，左边为原始内容，二进制内容按当前编码转换为文本；
+    /// 无法识别的文本文件作为待压缩的文本，超过大小上限的文件不读取
+    fn load_file(&mut self, ctx: &Context, path: &Path) {
+        let bytes = match read_dropped_file(path) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                notification::error(ctx, format!("读取文件:{} 失败:{e}", path.display()));
+                return;
+            }
+        };
+        if let Ok(decoded) = auto_decode(&bytes) {
+            self.source_text = match String::from_utf8(bytes) {
+                Ok(text) => text,
+                Err(e) => encode_bytes(e.as_bytes(), self.encoding),
+            };
+            self.apply_detected(decoded);
+            return;
+        }
+        match String::from_utf8(bytes) {
+            Ok(text) => {
+                self.source_text.clear();
+                self.result_text = text;
+                self.result_bytes = None;
+                self.detected_chain = None;
+            }
+            Err(_) => notification::error(ctx, format!("文件:{} 不是压缩文件或文本文件", path.display())),
+        }
+    }
 
+    /// 显示自动识别的结果，并把选择的编码、算法切换为识别出的第一个，方便重新压缩
+    fn apply_detected(&mut self, decoded: AutoDecoded) {
+        if let Some(encoding) = decoded.encoding() {
+            self.encoding = encoding;
+        }
+        if let Some(codec) = decoded.codec() {
+            self.set_codec(codec);
+        }
+        self.detected_chain = Some(format_chain(&decoded.layers));
+        self.set_result(decoded.bytes);
+    }
 
-            egui::ScrollArea::vertical().id_source("DecompressCompressStrTool compressed ScrollArea").max_height(tool_main_ui.available_height()).show(tool_main_ui, |tool_main_ui| {
-                tool_main_ui.add_sized(text_edit_size, TextEdit::multiline(&mut self.source_text).hint_text("输入原始字符串"));
-            });
+    /// 解压结果是 UTF-8 文本时显示为文本，否则保留原始字节
+    fn set_result(&mut self, bytes: Vec<u8>) {
+        match String::from_utf8(bytes) {
+            Ok(text) => {
+                self.result_text = text;
+                self.result_bytes = None;
+            }
+            Err(e) => {
+                self.result_text.clear();
+                self.result_bytes = Some(e.into_bytes());
+                self.charset_text = None;
+            }
+        }
+    }
 
-            //展示错误消息
-            self.show_error_msg(ctx);
-            //显示转换按钮
-            self.show_transfer_button(ctx, tool_main_ui);
+    /// 解压结果的原始字节，文本结果按 UTF-8
+    fn result_as_bytes(&self) -> &[u8] {
+        match &self.result_bytes {
+            Some(bytes) => bytes,
+            None => self.result_text.as_bytes(),
+        }
+    }
 
-            egui::ScrollArea::vertical().id_source("DecompressCompressStrTool origin_info ScrollArea").max_height(tool_main_ui.available_height()).show(tool_main_ui, |tool_main_ui| {
-                tool_main_ui.add_sized(text_edit_size, TextEdit::multiline(&mut self.result_text).hint_text("显示解压缩后的字符串"));
-            });
+    /// 解码后解压，自动识别时按识别出的编码、压缩链解开
+    fn decompress(&mut self, ctx: &Context) {
+        if self.auto_detect {
+            match auto_decode(self.source_text.as_bytes()) {
+                Ok(decoded) => self.apply_detected(decoded),
+                Err(e) => {
+                    self.detected_chain = None;
+                    notification::error(ctx, format!("解压失败:{e}"));
+                }
+            }
+            return;
+        }
+        match decode_compressed_bytes(&self.source_text, self.encoding, self.codec) {
+            Ok(bytes) => self.set_result(bytes),
+            Err(e) => notification::error(ctx, format!("解压失败:{e}")),
+        }
+    }
+
+    /// 切换算法，级别重置为该算法的默认值
+    fn set_codec(&mut self, codec: CompressionCodec) {
+        if self.codec != codec {
+            self.codec = codec;
+            self.level = codec.default_level();
+        }
+    }
+
+    /// 文本编码、压缩算法和级别选择
+    fn show_codec_options(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.auto_detect, "自动识别").on_hover_text("解压时根据内容识别编码和压缩格式，支持多层嵌套；deflate、brotli 没有特征，无法识别");
+            if let Some(chain) = &self.detected_chain {
+                ui.label(format!("识别为: {chain}"));
+            }
+            ui.separator();
+            ui.label("编码:");
+            egui::ComboBox::from_id_source("DecompressCompressStrTool encoding")
+                .selected_text(self.encoding.as_label())
+                .show_ui(ui, |ui| {
+                    for item in TextEncoding::iter() {
+                        ui.selectable_value(&mut self.encoding, item, item.as_label());
+                    }
+                })
+                .response
+                .on_hover_text("解码时忽略空白、换行和 data URI 前缀");
+            ui.separator();
+            ui.label("算法:");
+            let mut codec = self.codec;
+            egui::ComboBox::from_id_source("DecompressCompressStrTool codec")
+                .selected_text(codec.as_label())
+                .show_ui(ui, |ui| {
+                    for item in CompressionCodec::iter() {
+                        ui.selectable_value(&mut codec, item, item.as_label());
+                    }
+                });
+            self.set_codec(codec);
+            match self.codec.level_range() {
+                Some(range) => {
+                    ui.label("压缩级别:");
+                    ui.add(egui::DragValue::new(&mut self.level).range(range)).on_hover_text("只在压缩时使用，越大压缩率越高、速度越慢");
+                }
+                None => {
+                    ui.label("不支持调整压缩级别");
+                }
+            }
+        });
+    }
+
+    /// 解压结果的保存按钮，二进制结果可以按其他字符集查看，保存、压缩时仍使用原始字节
+    fn show_result_bar(&mut self, ctx: &Context, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            if ui.button("💾 保存到文件…").on_hover_text("保存解压后的原始字节").clicked() {
+                save_bytes_to_file(ctx, self.result_as_bytes(), "decompressed.bin");
+            }
+            let Some(bytes) = &self.result_bytes else {
+                return;
+            };
+            ui.label(format!("二进制数据 {} 字节", bytes.len()));
+            ui.separator();
+            let mut charset_changed = false;
+            egui::ComboBox::from_id_source("DecompressCompressStrTool charset")
+                .selected_text(self.charset.as_str())
+                .show_ui(ui, |ui| {
+                    for item in COMMON_CHARSETS {
+                        charset_changed |= ui.selectable_value(&mut self.charset, item.to_string(), item).changed();
+                    }
+                });
+            //已经在查看时切换字符集直接重新转换
+            let viewing = self.charset_text.is_some();
+            if ui.button("按字符集查看文本").clicked() || (viewing && charset_changed) {
+                match decode_charset(bytes, &self.charset) {
+                    Ok(text) => self.charset_text = Some(text),
+                    Err(e) => notification::error(ctx, e.to_string()),
+                }
+            }
+            if viewing && ui.button("隐藏文本").clicked() {
+                self.charset_text = None;
+            }
         });
     }
 
     /// 显示转换按钮
     fn show_transfer_button(&mut self, ctx: &Context, tool_main_ui: &mut Ui) {
         tool_main_ui.vertical(|ui| {
-            if ui.add(Button::new(">>")).clicked() {
-                let decompress_result = self.decompress(self.source_text.clone());
-                if decompress_result.is_err() {
-                    self.error_msg = format!("解压失败:{}", decompress_result.unwrap_err());
-                    self.error_start = get_seconds_since_epoch();
-                    ctx.request_repaint_after(Duration::from_secs(1));
-                } else {
-                    self.result_text = decompress_result.unwrap();
-                }
+            if ui.add(Button::new(">>")).on_hover_text("解码后解压").clicked() {
+                self.decompress(ctx);
             }
             ui.add_space(10.0);
-            if ui.add(Button::new("<<")).clicked() {
-                let compress_result = self.compress(self.result_text.clone());
-                if compress_result.is_err() {
-                    self.error_msg = format!("压缩失败:{}", compress_result.unwrap_err());
-                    self.error_start = get_seconds_since_epoch();
-                    ctx.request_repaint_after(Duration::from_secs(1));
-                } else {
-                    self.source_text = compress_result.unwrap();
+            if ui.add(Button::new("<<")).on_hover_text("压缩后编码").clicked() {
+                let encoded = match &self.result_bytes {
+                    Some(bytes) => encode_compressed_bytes(bytes, self.encoding, self.codec, self.level),
+                    None => encode_compressed_text(&self.result_text, self.encoding, self.codec, self.level),
+                };
+                match encoded {
+                    Ok(compress_str) => self.source_text = compress_str,
+                    Err(e) => notification::error(ctx, format!("压缩失败:{e}")),
                 }
             }
         });
     }
+}
 
-    /// 暂时错误信息
-    /// TODO 消息弹窗需要实现全局
-    fn show_error_msg(&mut self, ctx: &Context) {
-        if get_seconds_since_epoch() - self.error_start > 5 && !self.error_msg.is_empty() {
-            info!("关闭弹窗消息");
-            self.error_msg = "".to_string();  // 设置关闭行为
-        }
+impl Tool for DecompressCompressStrTool {
+    fn label(&self) -> &str {
+        "解压、压缩字符串"
+    }
 
-        if self.error_msg.is_empty() {
-            return;
-        }
+    fn icon(&self) -> &str {
+        "🗜"
+    }
 
-        let screen_rect = ctx.available_rect(); // 获取可用屏幕区域
-        let window_size = egui::vec2(300.0, 200.0); // 指定窗口大小
-
-        // 计算居中位置
-        let position = egui::pos2(
-            screen_rect.center().x - window_size.x / 2.0,
-            screen_rect.center().y - window_size.y / 2.0,
-        );
-
-        //等一秒再刷新一次
-        ctx.request_repaint_after(Duration::from_secs(1));
-        egui::Window::new("")
-            .collapsible(false)
-            .fixed_pos(position) // 设置窗口位置
-            .fixed_size(window_size) // 设置窗口大小
-            .title_bar(false)  // 关闭默认标题栏，以便自定义
-            .show(ctx, |ui| {
-                // 自定义标题栏，包含关闭按钮
-                ui.horizontal(|ui| {
-                    ui.with_layout(Layout::left_to_right(Align::Max), |ui| {
-                        ui.add_space(10.0); // 左边距，调整以美观
-                    });
-                    ui.with_layout(Layout::centered_and_justified(egui::Direction::LeftToRight), |ui| {
-                        ui.label("错误");  // 标题内容，居中显示
-                    });
-                    ui.with_layout(Layout::right_to_left(Align::Max), |ui| {
-                        if ui.button("✖").clicked() {
-                            self.error_msg = "".to_string();  // 设置关闭行为
-                        }
-                    });
-                });
+    fn category(&self) -> ToolCategory {
+        ToolCategory::Text
+    }
 
-                // 窗口的主体内容
-                ui.separator();
-                ui.label(&self.error_msg);
-            });
+    fn save(&self) -> Option<String> {
+        let state = DecompressCompressStrState {
+            source_text: self.source_text.clone(),
+            result_text: self.result_text.clone(),
+            encoding: self.encoding,
+            codec: self.codec,
+            level: self.level,
+            auto_detect: self.auto_detect,
+        };
+        serde_json::to_string(&state).ok()
     }
 
+    fn restore(&mut self, state: &str) {
+        match serde_json::from_str::<DecompressCompressStrState>(state) {
+            Ok(state) => {
+                self.source_text = state.source_text;
+                self.result_text = state.result_text;
+                self.encoding = state.encoding;
+                self.codec = state.codec;
+                self.level = state.codec.clamp_level(state.level);
+                self.auto_detect = state.auto_detect;
+            }
+            Err(e) => error!("恢复解压、压缩字符串工具状态失败:{e}"),
+        }
+    }
 
-    pub fn decompress(&mut self, str: String) -> Result<String, Box<dyn std::error::Error>> {
-        let bytes = general_purpose::STANDARD
-            .decode(str)?;
-        let mut decompress_decoder = flate2::read::GzDecoder::new(bytes.as_slice());
-        let mut decompress_str = String::new();
-        decompress_decoder.read_to_string(&mut decompress_str)?;
-        return Ok(decompress_str);
+    fn open_dropped(&mut self, ctx: &egui::Context, paths: &[PathBuf]) -> bool {
+        let Some(path) = paths.iter().find(|path| path.is_file()) else {
+            return false;
+        };
+        self.load_file(ctx, path);
+        true
     }
 
-    pub fn compress(&mut self, str: String) -> Result<String, Box<dyn std::error::Error>> {
-        // 创建一个 GzEncoder，用于压缩数据
-        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
-        encoder.write_all(str.as_bytes())?;
-        let compressed_bytes = encoder.finish()?;
+    fn show(&mut self, ctx: &egui::Context, tool_main_ui: &mut Ui, _frame: &mut eframe::Frame) {
+        self.show_count = self.show_count + 1;
+        debug!("DecompressCompressStrTool show:{}", self.show_count);
+        self.show_codec_options(tool_main_ui);
+        tool_main_ui.horizontal_top(|tool_main_ui| {
+            let available_width = tool_main_ui.available_width();
+            // 使用相同的尺寸配置两个 TextEdit 控件
+            let half_width = (available_width - 40.0) * 0.5;
+            let text_edit_size = egui::vec2(half_width, tool_main_ui.available_height());
+
 
-        // 将压缩后的数据进行 Base64 编码
-        return Ok(general_purpose::STANDARD.encode(compressed_bytes));
+            egui::ScrollArea::vertical().id_source("DecompressCompressStrTool compressed ScrollArea").max_height(tool_main_ui.available_height()).show(tool_main_ui, |tool_main_ui| {
+                tool_main_ui.add_sized(text_edit_size, TextEdit::multiline(&mut self.source_text).hint_text("输入原始字符串"));
+            });
+
+            //显示转换按钮
+            self.show_transfer_button(ctx, tool_main_ui);
+
+            tool_main_ui.vertical(|tool_main_ui| {
+                tool_main_ui.set_width(half_width);
+                self.show_result_bar(ctx, tool_main_ui);
+                if let Some(bytes) = &self.result_bytes {
+                    let Some(text) = &self.charset_text else {
+                        show_hex_view(tool_main_ui, bytes);
+                        return;
+                    };
+                    //上半部分显示原始字节，下半部分显示按字符集转换的文本
+                    let hex_size = egui::vec2(half_width, tool_main_ui.available_height() * 0.5);
+                    tool_main_ui.allocate_ui(hex_size, |ui| show_hex_view(ui, bytes));
+                    tool_main_ui.separator();
+                    tool_main_ui.label(format!("按 {} 转换的文本", self.charset));
+                    //只读，不复制文本
+                    let mut text = text.as_str();
+                    egui::ScrollArea::vertical().id_source("DecompressCompressStrTool charset_text ScrollArea").show(tool_main_ui, |tool_main_ui| {
+                        tool_main_ui.add_sized(egui::vec2(half_width, tool_main_ui.available_height()), TextEdit::multiline(&mut text));
+                    });
+                    return;
+                }
+                let text_edit_size = egui::vec2(half_width, tool_main_ui.available_height());
+                egui::ScrollArea::vertical().id_source("DecompressCompressStrTool origin_info ScrollArea").max_height(tool_main_ui.available_height()).show(tool_main_ui, |tool_main_ui| {
+                    tool_main_ui.add_sized(text_edit_size, TextEdit::multiline(&mut self.result_text).hint_text("显示解压缩后的字符串"));
+                });
+            });
+        });
     }
-}
\ No newline at end of file
+}
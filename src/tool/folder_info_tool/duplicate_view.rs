@@ -0,0 +1,334 @@
+use std::path::PathBuf;
+
+use egui::{Context, RichText, Ui};
+
+use crate::notification;
+use crate::service::duplicate::{find_duplicates_in_background, resolve_duplicates_in_background, DuplicateGroup, DuplicateSearchHandle, ResolveHandle, ResolveKind};
+use crate::service::folder_scan::FileNode;
+use crate::util::file_util::{human_readable_size, open_in_file_manager};
+
+/// 等待确认的处理操作
+#[derive(Clone)]
+struct PendingResolve {
+    kind: ResolveKind,
+    /// 保留的文件
+    kept: PathBuf,
+    duplicates: Vec<PathBuf>,
+    size: u64,
+}
+
+/// 已处理的重复文件，由调用方同步更新文件树
+pub struct ResolvedDuplicates {
+    pub kind: ResolveKind,
+    pub paths: Vec<PathBuf>,
+}
+
+/// 列表上的操作，绘制完成后统一处理
+enum DuplicateAction {
+    /// 选择组内保留的文件
+    Keep { group: usize, file: usize },
+    Reveal(PathBuf),
+    /// 需要确认的删除、替换操作
+    Resolve(PendingResolve),
+}
+
+/// 一组重复文件和其中要保留的文件
+#[derive(Clone)]
+struct GroupState {
+    group: DuplicateGroup,
+    kept: usize,
+}
+
+impl GroupState {
+    fn kept_path(&self) -> &PathBuf {
+        &self.group.files[self.kept]
+    }
+
+    fn others(&self) -> Vec<PathBuf> {
+        self.group.files.iter()
+            .enumerate()
+            .filter(|(index, _)| *index != self.kept)
+            .map(|(_, path)| path.clone())
+            .collect()
+    }
+}
+
+/// 重复文件查找，基于已扫描的文件树，只对大小相同的文件计算哈希
+#[derive(Default)]
+pub struct DuplicateView {
+    /// 查找结果，None 表示还没有查找
+    groups: Option<Vec<GroupState>>,
+    search: Option<DuplicateSearchHandle>,
+    pending: Option<PendingResolve>,
+    /// 后台进行中的删除、替换
+    resolving: Option<(PendingResolve, ResolveHandle)>,
+}
+
+impl Clone for DuplicateView {
+    /// 查找句柄不能共享，复制时只复制已有结果
+    fn clone(&self) -> Self {
+        Self {
+            groups: self.groups.clone(),
+            search: None,
+            pending: None,
+            resolving: None,
+        }
+    }
+}
+
+impl DuplicateView {
+    /// 扫描新文件夹时清空结果
+    pub fn reset(&mut self) {
+        self.cancel();
+        self.groups = None;
+        self.pending = None;
+    }
+
+    /// 取消查找和处理，已处理的文件不会恢复
+    pub fn cancel(&mut self) {
+        if let Some(search) = self.search.take() {
+            search.cancel();
+        }
+        if let Some((_, resolving)) = self.resolving.take() {
+            resolving.cancel();
+        }
+    }
+
+    /// 在后台查找 root 下的重复文件
+    fn start_search(&mut self, ctx: &Context, root: &FileNode) {
+        self.cancel();
+        let repaint_ctx = ctx.clone();
+        self.search = Some(find_duplicates_in_background(root.collect_files(), move || repaint_ctx.request_repaint()));
+    }
+
+    fn poll_search(&mut self, ctx: &Context) {
+        let Some(search) = &self.search else {
+            return;
+        };
+        let Some(groups) = search.poll_result() else {
+            //还在查找，刷新进度
+            ctx.request_repaint_after(std::time::Duration::from_millis(200));
+            return;
+        };
+        if groups.is_empty() {
+            notification::info(ctx, "没有找到重复文件");
+        }
+        self.groups = Some(groups.into_iter().map(|group| GroupState { group, kept: 0 }).collect());
+        self.search = None;
+    }
+
+    /// scanning 为 true 时文件树还不完整，不允许查找，后台处理完成时返回已处理的文件
+    pub fn show(&mut self, ctx: &Context, ui: &mut Ui, root: &FileNode, scanning: bool) -> Option<ResolvedDuplicates> {
+        self.poll_search(ctx);
+        let resolved = self.poll_resolve(ctx);
+        self.show_toolbar(ctx, ui, root, scanning);
+        ui.separator();
+
+        let mut actions = vec![];
+        if let Some(groups) = &self.groups {
+            egui::ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
+                for (index, group) in groups.iter().enumerate() {
+                    show_group(ui, index, group, &mut actions);
+                }
+            });
+        }
+        self.apply_actions(ctx, actions);
+        self.show_confirm_window(ctx, ui);
+        resolved
+    }
+
+    fn show_toolbar(&mut self, ctx: &Context, ui: &mut Ui, root: &FileNode, scanning: bool) {
+        ui.horizontal(|ui| {
+            if let Some(search) = &self.search {
+                let progress = search.progress();
+                ui.spinner();
+                ui.label(format!("正在比较文件内容：{} 个大小相同的文件，已计算 {} 次哈希", progress.candidates(), progress.hashed()));
+                if ui.button("取消").clicked() {
+                    self.cancel();
+                }
+                return;
+            }
+            if let Some((pending, resolving)) = &self.resolving {
+                ui.spinner();
+                ui.label(format!("正在{}：已处理 {}/{} 个文件", pending.kind.as_label(), resolving.progress().processed(), pending.duplicates.len()));
+                if ui.button("取消").clicked() {
+                    resolving.cancel();
+                }
+                return;
+            }
+            let search_button = ui.add_enabled(!scanning, egui::Button::new("🔍 查找重复文件"));
+            if search_button.clicked() {
+                self.start_search(ctx, root);
+            }
+            if scanning {
+                search_button.on_disabled_hover_text("等待扫描完成");
+            }
+            let Some(groups) = &self.groups else {
+                ui.label("按大小、部分内容、完整内容依次比较，找出内容完全相同的文件");
+                return;
+            };
+            let reclaimable: u64 = groups.iter().map(|group| group.group.reclaimable()).sum();
+            let files: usize = groups.iter().map(|group| group.group.files.len()).sum();
+            ui.label(format!("共 {} 组、{} 个重复文件，可释放 {}", groups.len(), files, human_readable_size(reclaimable)));
+        });
+    }
+
+    fn apply_actions(&mut self, ctx: &Context, actions: Vec<DuplicateAction>) {
+        for action in actions {
+            match action {
+                DuplicateAction::Keep { group, file } => {
+                    if let Some(group) = self.groups.as_mut().and_then(|groups| groups.get_mut(group)) {
+                        group.kept = file;
+                    }
+                }
+                DuplicateAction::Reveal(path) => {
+                    if let Err(e) = open_in_file_manager(&path) {
+                        notification::error(ctx, format!("打开文件夹:{path:?} 失败:{e}"));
+                    }
+                }
+                DuplicateAction::Resolve(pending) => {
+                    if self.resolving.is_none() {
+                        self.pending = Some(pending);
+                    }
+                }
+            }
+        }
+    }
+
+    /// 删除、替换前的确认窗口
+    fn show_confirm_window(&mut self, ctx: &Context, ui: &Ui) {
+        let Some(pending) = &self.pending else {
+            return;
+        };
+        let mut confirmed = false;
+        let mut cancelled = false;
+        egui::Window::new(format!("确认{}", pending.kind.as_label()))
+            .id(ui.id().with("duplicate_confirm"))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label(format!("保留: {}", pending.kept.display()));
+                ui.label(format!(
+                    "{} 以下 {} 个文件，释放 {}：",
+                    pending.kind.as_label(),
+                    pending.duplicates.len(),
+                    human_readable_size(pending.size * pending.duplicates.len() as u64)
+                ));
+                for path in &pending.duplicates {
+                    ui.label(RichText::new(path.display().to_string()).weak());
+                }
+                if pending.kind == ResolveKind::Trash {
+                    ui.label(RichText::new("可以点击“撤销删除”或在系统回收站中恢复").weak());
+                }
+                ui.horizontal(|ui| {
+                    if ui.button(pending.kind.as_label()).clicked() {
+                        confirmed = true;
+                    }
+                    if ui.button("取消").clicked() {
+                        cancelled = true;
+                    }
+                });
+            });
+        if cancelled {
+            self.pending = None;
+        }
+        if confirmed {
+            if let Some(pending) = self.pending.take() {
+                self.resolve(ctx, pending);
+            }
+        }
+    }
+
+    /// 在后台确认内容并执行删除或替换
+    fn resolve(&mut self, ctx: &Context, pending: PendingResolve) {
+        let repaint_ctx = ctx.clone();
+        let handle = resolve_duplicates_in_background(pending.kind, pending.kept.clone(), pending.duplicates.clone(), move || repaint_ctx.request_repaint());
+        self.resolving = Some((pending, handle));
+    }
+
+    /// 接收处理结果，成功处理的文件从结果中移除
+    fn poll_resolve(&mut self, ctx: &Context) -> Option<ResolvedDuplicates> {
+        let (pending, resolving) = self.resolving.as_ref()?;
+        let Some(outcome) = resolving.poll_result() else {
+            //还在处理，刷新进度
+            ctx.request_repaint_after(std::time::Duration::from_millis(200));
+            return None;
+        };
+        let pending = pending.clone();
+        self.resolving = None;
+        for e in &outcome.errors {
+            notification::error(ctx, e.to_string());
+        }
+        let resolved = outcome.resolved;
+        if resolved.is_empty() {
+            return None;
+        }
+        notification::success(ctx, format!(
+            "已{} {} 个文件，释放 {}",
+            pending.kind.as_label(),
+            resolved.len(),
+            human_readable_size(pending.size * resolved.len() as u64)
+        ));
+        if let Some(groups) = &mut self.groups {
+            for group in groups.iter_mut() {
+                let kept_path = group.kept_path().clone();
+                group.group.files.retain(|path| !resolved.contains(path));
+                group.kept = group.group.files.iter().position(|path| *path == kept_path).unwrap_or(0);
+            }
+            groups.retain(|group| group.group.files.len() > 1);
+        }
+        Some(ResolvedDuplicates {
+            kind: pending.kind,
+            paths: resolved,
+        })
+    }
+}
+
+fn show_group(ui: &mut Ui, index: usize, group: &GroupState, actions: &mut Vec<DuplicateAction>) {
+    let title = format!(
+        "{} 个相同文件，每个 {}，可释放 {}",
+        group.group.files.len(),
+        human_readable_size(group.group.size),
+        human_readable_size(group.group.reclaimable())
+    );
+    egui::CollapsingHeader::new(title)
+        .id_source(&group.group.hash)
+        .default_open(true)
+        .show(ui, |ui| {
+            for (file_index, path) in group.group.files.iter().enumerate() {
+                let kept = file_index == group.kept;
+                ui.horizontal(|ui| {
+                    if ui.radio(kept, "保留").clicked() {
+                        actions.push(DuplicateAction::Keep { group: index, file: file_index });
+                    }
+                    if ui.small_button("📂").on_hover_text("打开所在文件夹").clicked() {
+                        actions.push(DuplicateAction::Reveal(path.clone()));
+                    }
+                    for kind in [ResolveKind::Trash, ResolveKind::HardLink] {
+                        if ui.add_enabled(!kept, egui::Button::new(kind.as_label()).small()).clicked() {
+                            actions.push(DuplicateAction::Resolve(PendingResolve {
+                                kind,
+                                kept: group.kept_path().clone(),
+                                duplicates: vec![path.clone()],
+                                size: group.group.size,
+                            }));
+                        }
+                    }
+                    ui.add(egui::Label::new(path.display().to_string()).truncate());
+                });
+            }
+            ui.horizontal(|ui| {
+                for kind in [ResolveKind::Trash, ResolveKind::HardLink] {
+                    if ui.button(format!("其他文件全部{}", kind.as_label())).clicked() {
+                        actions.push(DuplicateAction::Resolve(PendingResolve {
+                            kind,
+                            kept: group.kept_path().clone(),
+                            duplicates: group.others(),
+                            size: group.group.size,
+                        }));
+                    }
+                }
+            });
+        });
+}
@@ -0,0 +1,398 @@
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+use egui::{Context, RichText, Ui};
+
+use crate::notification;
+use crate::service::file_ops::{hash_file_in_background, move_all_to_trash_in_background, rename_entry, restore_all_from_trash_in_background, FileHashes, HashHandle, TrashHandle};
+use crate::service::folder_scan::FileNode;
+use crate::util::file_util::human_readable_size;
+
+/// 右键菜单、批量操作中需要输入或确认的文件操作
+pub enum FileRequest {
+    Rename(PathBuf),
+    /// 移到回收站，可以一次移动多个
+    Trash(Vec<PathBuf>),
+    Hash(PathBuf),
+}
+
+/// 正在重命名的文件
+#[derive(Clone)]
+struct RenameState {
+    path: PathBuf,
+    name: String,
+}
+
+/// 等待确认移到回收站的文件
+#[derive(Clone)]
+struct PendingTrash {
+    paths: Vec<PathBuf>,
+    /// 所有文件的总大小
+    size: u64,
+    /// 是否包含文件夹
+    has_dirs: bool,
+}
+
+/// 计算哈希的文件和结果
+struct HashState {
+    path: PathBuf,
+    size: u64,
+    task: Option<HashHandle>,
+    hashes: Option<FileHashes>,
+}
+
+/// 后台进行中的移到回收站或撤销，文件多时逐个处理会很慢
+enum TrashTask {
+    Trash(TrashHandle),
+    /// 撤销删除，恢复后把节点放回文件树
+    Restore {
+        nodes: Vec<FileNode>,
+        handle: TrashHandle,
+    },
+}
+
+/// 重命名、移到回收站、计算哈希，修改文件后同步更新扫描结果，不需要重新扫描
+#[derive(Default)]
+pub struct FileOpsView {
+    rename: Option<RenameState>,
+    pending_trash: Option<PendingTrash>,
+    hash: Option<HashState>,
+    /// 已移到回收站、可以撤销的节点，每次确认删除的为一组，最后删除的在末尾
+    trashed: Vec<Vec<FileNode>>,
+    undo_requested: bool,
+    trash_task: Option<TrashTask>,
+}
+
+impl Clone for FileOpsView {
+    /// 哈希计算句柄不能共享，复制时不复制进行中的操作
+    fn clone(&self) -> Self {
+        Self {
+            rename: None,
+            pending_trash: None,
+            hash: None,
+            trashed: self.trashed.clone(),
+            undo_requested: false,
+            trash_task: None,
+        }
+    }
+}
+
+impl FileOpsView {
+    /// 扫描新文件夹时清空状态，已删除的文件不能再撤销
+    pub fn reset(&mut self) {
+        self.cancel();
+        self.rename = None;
+        self.pending_trash = None;
+        self.hash = None;
+        self.trashed.clear();
+    }
+
+    pub fn cancel(&mut self) {
+        if let Some(task) = self.hash.as_mut().and_then(|hash| hash.task.take()) {
+            task.cancel();
+        }
+    }
+
+    /// 打开右键菜单选择的操作对应的窗口
+    pub fn request(&mut self, ctx: &Context, request: FileRequest, root: &FileNode) {
+        match request {
+            FileRequest::Rename(path) => {
+                let name = path.file_name().map_or_else(String::new, |name| name.to_string_lossy().to_string());
+                self.rename = Some(RenameState { path, name });
+            }
+            FileRequest::Trash(paths) => {
+                let nodes: Vec<&FileNode> = paths.iter().filter_map(|path| root.find(path)).collect();
+                if nodes.is_empty() {
+                    return;
+                }
+                self.pending_trash = Some(PendingTrash {
+                    size: nodes.iter().map(|node| node.size).sum(),
+                    has_dirs: nodes.iter().any(|node| !node.is_file),
+                    paths: nodes.iter().map(|node| node.path.clone()).collect(),
+                });
+            }
+            FileRequest::Hash(path) => {
+                self.cancel();
+                let size = root.find(&path).map_or(0, |node| node.size);
+                let repaint_ctx = ctx.clone();
+                let task = hash_file_in_background(path.clone(), move || repaint_ctx.request_repaint());
+                self.hash = Some(HashState {
+                    path,
+                    size,
+                    task: Some(task),
+                    hashes: None,
+                });
+            }
+        }
+    }
+
+    /// 撤销最后一次删除的按钮，没有可撤销的删除时不显示，后台处理时显示进度
+    pub fn show_undo_button(&mut self, ui: &mut Ui) {
+        if let Some(task) = &self.trash_task {
+            ui.spinner();
+            ui.label(match task {
+                TrashTask::Trash(_) => "正在移到回收站...",
+                TrashTask::Restore { .. } => "正在从回收站恢复...",
+            });
+            return;
+        }
+        let Some(last) = self.trashed.last() else {
+            return;
+        };
+        let hover_text = match last.as_slice() {
+            [node] => format!("从回收站恢复 {}", node.path.display()),
+            nodes => format!("从回收站恢复最后删除的 {} 项", nodes.len()),
+        };
+        let button = ui.button("↶ 撤销删除").on_hover_text(hover_text);
+        if button.clicked() {
+            self.undo_requested = true;
+        }
+    }
+
+    /// 显示各操作的窗口，执行后更新文件树，文件树有变化时返回 true
+    pub fn show(&mut self, ctx: &Context, ui: &Ui, tree: Option<&mut FileNode>) -> bool {
+        let Some(tree) = tree else {
+            return false;
+        };
+        let mut changed = self.poll_trash_task(ctx, tree);
+        if self.undo_requested {
+            self.undo_requested = false;
+            self.undo_trash(ctx);
+        }
+        changed |= self.show_rename_window(ctx, ui, tree);
+        self.show_trash_window(ctx, ui);
+        self.show_hash_window(ctx, ui);
+        changed
+    }
+
+    fn show_rename_window(&mut self, ctx: &Context, ui: &Ui, tree: &mut FileNode) -> bool {
+        let Some(rename) = &mut self.rename else {
+            return false;
+        };
+        let mut confirmed = false;
+        let mut cancelled = false;
+        egui::Window::new("重命名")
+            .id(ui.id().with("rename"))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label(RichText::new(rename.path.display().to_string()).weak());
+                let input = ui.add(egui::TextEdit::singleline(&mut rename.name).desired_width(320.0));
+                if !input.has_focus() && !input.lost_focus() {
+                    input.request_focus();
+                }
+                if input.lost_focus() && ui.input(|input| input.key_pressed(egui::Key::Enter)) {
+                    confirmed = true;
+                }
+                ui.horizontal(|ui| {
+                    if ui.button("确定").clicked() {
+                        confirmed = true;
+                    }
+                    if ui.button("取消").clicked() || ui.input(|input| input.key_pressed(egui::Key::Escape)) {
+                        cancelled = true;
+                    }
+                });
+            });
+        if cancelled {
+            self.rename = None;
+            return false;
+        }
+        if !confirmed {
+            return false;
+        }
+        let new_path = match rename_entry(&rename.path, &rename.name) {
+            Ok(new_path) => new_path,
+            Err(e) => {
+                //保留窗口，方便修改后重试
+                notification::error(ctx, e.to_string());
+                return false;
+            }
+        };
+        if let Some(mut node) = tree.remove(&rename.path) {
+            node.set_path(new_path.clone());
+            tree.insert(node);
+        }
+        notification::success(ctx, format!("已重命名为 {}", new_path.display()));
+        self.rename = None;
+        true
+    }
+
+    /// 移到回收站前的确认窗口，确认后在后台移动
+    fn show_trash_window(&mut self, ctx: &Context, ui: &Ui) {
+        let Some(pending) = &self.pending_trash else {
+            return;
+        };
+        let busy = self.trash_task.is_some();
+        let mut confirmed = false;
+        let mut cancelled = false;
+        egui::Window::new("确认移到回收站")
+            .id(ui.id().with("trash_confirm"))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label(format!("将以下 {} 项移到回收站，释放 {}：", pending.paths.len(), human_readable_size(pending.size)));
+                egui::ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+                    for path in &pending.paths {
+                        ui.label(RichText::new(path.display().to_string()).weak());
+                    }
+                });
+                if pending.has_dirs {
+                    ui.label(RichText::new("文件夹中的所有文件会一起移到回收站").color(ui.visuals().warn_fg_color));
+                }
+                ui.label(RichText::new("可以点击“撤销删除”或在系统回收站中恢复").weak());
+                ui.horizontal(|ui| {
+                    let button = ui.add_enabled(!busy, egui::Button::new("移到回收站"));
+                    if button.on_disabled_hover_text("等待上一次操作完成").clicked() {
+                        confirmed = true;
+                    }
+                    if ui.button("取消").clicked() {
+                        cancelled = true;
+                    }
+                });
+            });
+        if cancelled {
+            self.pending_trash = None;
+        }
+        if !confirmed {
+            return;
+        }
+        let Some(pending) = self.pending_trash.take() else {
+            return;
+        };
+        let repaint_ctx = ctx.clone();
+        let handle = move_all_to_trash_in_background(pending.paths, move || repaint_ctx.request_repaint());
+        self.trash_task = Some(TrashTask::Trash(handle));
+    }
+
+    /// 记录其他视图中移到回收站的文件，从文件树中移除并加入撤销列表
+    pub fn add_trashed(&mut self, tree: &mut FileNode, paths: &[PathBuf]) {
+        let trashed: Vec<FileNode> = paths.iter().filter_map(|path| tree.remove(path)).collect();
+        if !trashed.is_empty() {
+            self.trashed.push(trashed);
+        }
+    }
+
+    /// 在后台从回收站恢复最后一次删除的文件
+    fn undo_trash(&mut self, ctx: &Context) {
+        if self.trash_task.is_some() {
+            return;
+        }
+        let Some(nodes) = self.trashed.pop() else {
+            return;
+        };
+        let paths = nodes.iter().map(|node| node.path.clone()).collect();
+        let repaint_ctx = ctx.clone();
+        let handle = restore_all_from_trash_in_background(paths, move || repaint_ctx.request_repaint());
+        self.trash_task = Some(TrashTask::Restore { nodes, handle });
+    }
+
+    /// 接收后台处理结果并更新文件树，文件树有变化时返回 true
+    fn poll_trash_task(&mut self, ctx: &Context, tree: &mut FileNode) -> bool {
+        let outcome = match &self.trash_task {
+            Some(TrashTask::Trash(handle) | TrashTask::Restore { handle, .. }) => handle.poll_result(),
+            None => None,
+        };
+        let Some(outcome) = outcome else {
+            return false;
+        };
+        for e in &outcome.errors {
+            notification::error(ctx, e.to_string());
+        }
+        match self.trash_task.take() {
+            Some(TrashTask::Trash(_)) => {
+                //上级文件夹已经移走时这里找不到
+                let trashed: Vec<FileNode> = outcome.done.iter().filter_map(|path| tree.remove(path)).collect();
+                if trashed.is_empty() {
+                    return false;
+                }
+                let size: u64 = trashed.iter().map(|node| node.size).sum();
+                notification::success(ctx, format!("已将 {} 项移到回收站，释放 {}", trashed.len(), human_readable_size(size)));
+                self.trashed.push(trashed);
+                true
+            }
+            Some(TrashTask::Restore { nodes, .. }) => {
+                let done: HashSet<&PathBuf> = outcome.done.iter().collect();
+                let retry: HashSet<&PathBuf> = outcome.retry.iter().collect();
+                let mut restored = 0;
+                let mut remaining = vec![];
+                for node in nodes {
+                    if done.contains(&node.path) {
+                        tree.insert(node);
+                        restored += 1;
+                    } else if retry.contains(&node.path) {
+                        remaining.push(node);
+                    }
+                }
+                //仍在回收站中的保留在撤销列表，处理冲突后可以再次撤销
+                if !remaining.is_empty() {
+                    self.trashed.push(remaining);
+                }
+                if restored > 0 {
+                    notification::success(ctx, format!("已恢复 {restored} 项"));
+                }
+                restored > 0
+            }
+            None => false,
+        }
+    }
+
+    fn show_hash_window(&mut self, ctx: &Context, ui: &Ui) {
+        let Some(hash) = &mut self.hash else {
+            return;
+        };
+        if let Some(task) = &hash.task {
+            match task.poll_result() {
+                Some(Ok(hashes)) => {
+                    hash.hashes = Some(hashes);
+                    hash.task = None;
+                }
+                Some(Err(e)) => {
+                    notification::error(ctx, e.to_string());
+                    self.hash = None;
+                    return;
+                }
+                //还在计算，刷新进度
+                None => ctx.request_repaint_after(std::time::Duration::from_millis(200)),
+            }
+        }
+        let mut open = true;
+        egui::Window::new("文件哈希")
+            .id(ui.id().with("file_hash"))
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label(RichText::new(hash.path.display().to_string()).weak());
+                if let Some(task) = &hash.task {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label(format!(
+                            "正在计算：{} / {}",
+                            human_readable_size(task.progress().bytes_read()),
+                            human_readable_size(hash.size)
+                        ));
+                    });
+                    return;
+                }
+                let Some(hashes) = &hash.hashes else {
+                    return;
+                };
+                egui::Grid::new("file_hash_grid").num_columns(3).show(ui, |ui| {
+                    for (algorithm, value) in hashes.entries() {
+                        ui.label(algorithm);
+                        ui.monospace(value);
+                        if ui.small_button("复制").clicked() {
+                            ctx.copy_text(value.to_string());
+                            notification::success(ctx, format!("已复制 {algorithm}"));
+                        }
+                        ui.end_row();
+                    }
+                });
+            });
+        if !open {
+            self.cancel();
+            self.hash = None;
+        }
+    }
+}
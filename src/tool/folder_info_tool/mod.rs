@@ -0,0 +1,623 @@
+use std::fs;
+use std::path::PathBuf;
+
+use egui::{Context, Ui};
+use log::error;
+use rfd::FileDialog;
+use serde::{Deserialize, Serialize};
+
+use crate::notification;
+use crate::service::duplicate::ResolveKind;
+use crate::service::export::{export_files, ExportFormat};
+use crate::service::folder_scan::{scan_folder_in_background, FileInfo, FileNode, HardLinks, ScanEvent, ScanHandle, ScanIssue};
+use crate::service::folder_watch::{watch_folder, FolderWatcher, WatchChange, WatchError};
+use crate::service::scan_filter::ScanOptions;
+use crate::tool::{Tool, ToolCategory};
+use crate::util::file_util::human_readable_size;
+use duplicate_view::{DuplicateView, ResolvedDuplicates};
+use file_ops_view::FileOpsView;
+use report_view::ReportView;
+use snapshot_view::SnapshotView;
+use stats_view::StatsView;
+use tree_view::TreeView;
+
+mod duplicate_view;
+mod file_ops_view;
+mod report_view;
+mod snapshot_view;
+mod stats_view;
+mod tree_view;
+mod treemap_view;
+
+/// 扫描结果的展示方式
+#[derive(PartialEq, Eq, Clone, Copy)]
+enum ViewMode {
+    /// 可展开的列表
+    Tree,
+    /// 矩形树图
+    Treemap,
+    /// 按类型统计
+    Stats,
+    /// 重复文件
+    Duplicates,
+    /// 清理报告
+    Reports,
+    /// 快照对比
+    Snapshot,
+}
+
+/// 文件夹处理
+pub struct FolderInfoTool {
+    pub folder_path: String,
+    /// 扫描结果，扫描过程中逐步补充子节点
+    pub tree: Option<FileNode>,
+    /// 文件树每次变化时增加，用于判断报告等结果是否需要重新生成
+    tree_revision: u64,
+    pub loading: bool,
+    /// 排除规则等扫描选项，下次扫描时生效
+    scan_options: ScanOptions,
+    /// 正在编辑的排除规则，扫描时解析到 scan_options
+    exclude_text: String,
+    /// 扫描中遇到的读取错误，对应的文件、文件夹没有统计
+    scan_issues: Vec<ScanIssue>,
+    show_issues: bool,
+    /// 树形列表的展开、下钻状态
+    tree_view: TreeView,
+    stats_view: StatsView,
+    duplicate_view: DuplicateView,
+    snapshot_view: SnapshotView,
+    report_view: ReportView,
+    /// 右键菜单中的重命名、删除等操作
+    file_ops: FileOpsView,
+    view_mode: ViewMode,
+    /// 进行中的后台扫描
+    scan: Option<ScanHandle>,
+    /// 需要在下一帧重新扫描 folder_path，恢复状态时拿不到 ctx，无法立即开始扫描
+    rescan_requested: bool,
+    /// 扫描完成后监控文件变化，自动更新列表
+    watch_enabled: bool,
+    watcher: Option<FolderWatcher>,
+    /// 扫描时已统计的硬链接，监控到变化后重新统计时使用
+    hard_links: HardLinks,
+}
+
+impl Clone for FolderInfoTool {
+    /// 扫描句柄不能共享，复制时如果还在扫描，新实例会重新扫描
+    fn clone(&self) -> Self {
+        Self {
+            folder_path: self.folder_path.clone(),
+            tree: self.tree.clone(),
+            tree_revision: self.tree_revision,
+            loading: false,
+            scan_options: self.scan_options.clone(),
+            exclude_text: self.exclude_text.clone(),
+            scan_issues: self.scan_issues.clone(),
+            show_issues: false,
+            tree_view: self.tree_view.clone(),
+            stats_view: self.stats_view.clone(),
+            duplicate_view: self.duplicate_view.clone(),
+            snapshot_view: self.snapshot_view.clone(),
+            report_view: self.report_view.clone(),
+            file_ops: self.file_ops.clone(),
+            view_mode: self.view_mode,
+            scan: None,
+            rescan_requested: self.loading || self.rescan_requested,
+            //下一帧重新开始监控
+            watch_enabled: self.watch_enabled,
+            watcher: None,
+            hard_links: self.hard_links.clone(),
+        }
+    }
+}
+
+/// 退出时保存的工具状态
+#[derive(Serialize, Deserialize)]
+struct FolderInfoState {
+    folder_path: String,
+    #[serde(default)]
+    scan_options: ScanOptions,
+    #[serde(default)]
+    watch_enabled: bool,
+}
+
+impl FolderInfoTool {
+    pub fn new() -> Self {
+        Self {
+            folder_path: "".to_string(),
+            tree: None,
+            tree_revision: 0,
+            loading: false,
+            scan_options: ScanOptions::default(),
+            exclude_text: "".to_string(),
+            scan_issues: vec![],
+            show_issues: false,
+            tree_view: TreeView::default(),
+            stats_view: StatsView::default(),
+            duplicate_view: DuplicateView::default(),
+            snapshot_view: SnapshotView::default(),
+            report_view: ReportView::default(),
+            file_ops: FileOpsView::default(),
+            view_mode: ViewMode::Tree,
+            scan: None,
+            rescan_requested: false,
+            watch_enabled: false,
+            watcher: None,
+            hard_links: HardLinks::default(),
+        }
+    }
+
+    pub fn add_choose_folder_button(&mut self, ctx: &Context, ui: &mut Ui) {
+        if !ui.button("选择文件夹").on_hover_text("也可以把文件夹拖入窗口").clicked() {
+            //未点击
+            return;
+        }
+        let picked_folder = FileDialog::new().pick_folder();
+        //未选择
+        if picked_folder.is_none() {
+            return;
+        }
+        self.load_folder(ctx, picked_folder.unwrap());
+    }
+
+    /// 在后台读取文件夹下的文件信息，正在进行的扫描会被取消
+    pub fn load_folder(&mut self, ctx: &Context, path: PathBuf) {
+        self.cancel_scan();
+        self.watcher = None;
+        self.folder_path = path.display().to_string();
+        self.tree = Some(FileNode::new_dir(&path));
+        self.tree_revision += 1;
+        self.tree_view.reset(&path);
+        self.stats_view.reset();
+        self.duplicate_view.reset();
+        self.report_view.reset();
+        self.file_ops.reset();
+        self.loading = true;
+        self.rescan_requested = false;
+        self.scan_issues.clear();
+        self.hard_links = HardLinks::default();
+        self.scan_options.exclude_patterns = ScanOptions::parse_patterns(&self.exclude_text);
+
+        let repaint_ctx = ctx.clone();
+        let options = self.scan_options.clone();
+        self.scan = Some(scan_folder_in_background(path, options, move || repaint_ctx.request_repaint()));
+    }
+
+    /// 取消正在进行的扫描
+    pub fn cancel_scan(&mut self) {
+        if let Some(scan) = &self.scan {
+            scan.cancel();
+        }
+    }
+
+    /// 接收后台扫描的结果
+    fn poll_scan(&mut self, ctx: &Context) {
+        let Some(scan) = &self.scan else {
+            return;
+        };
+        let mut finished = false;
+        for event in scan.poll_events() {
+            match event {
+                ScanEvent::Entry(node) => {
+                    if let Some(tree) = &mut self.tree {
+                        tree.add_child(node);
+                        self.tree_revision += 1;
+                    }
+                }
+                ScanEvent::Excluded(size) => {
+                    if let Some(tree) = &mut self.tree {
+                        tree.excluded_size += size;
+                    }
+                }
+                ScanEvent::Issue(issue) => self.scan_issues.push(issue),
+                ScanEvent::Finished(hard_links) => {
+                    self.hard_links = hard_links;
+                    finished = true;
+                }
+                ScanEvent::Cancelled => {
+                    notification::warning(ctx, "已取消扫描，列表中只有已统计完成的文件");
+                    finished = true;
+                }
+                ScanEvent::Failed(e) => {
+                    notification::error(ctx, e.to_string());
+                    finished = true;
+                }
+            }
+        }
+        if finished {
+            self.scan = None;
+            self.loading = false;
+            if let Some(tree) = &self.tree {
+                self.snapshot_view.refresh(tree);
+            }
+        }
+    }
+
+    /// 扫描完成后开始监控，关闭监控时停止
+    fn update_watcher(&mut self, ctx: &Context) {
+        if !self.watch_enabled {
+            self.watcher = None;
+            return;
+        }
+        if self.watcher.is_some() || self.loading || self.rescan_requested || self.tree.is_none() {
+            return;
+        }
+        let repaint_ctx = ctx.clone();
+        match watch_folder(PathBuf::from(&self.folder_path), &self.scan_options, self.hard_links.clone(), move || repaint_ctx.request_repaint()) {
+            Ok(watcher) => self.watcher = Some(watcher),
+            Err(e) => self.stop_watching(ctx, &e),
+        }
+    }
+
+    /// 监控失败时关闭实时更新，例如 inotify 监控数量达到上限，不再重试
+    fn stop_watching(&mut self, ctx: &Context, e: &WatchError) {
+        self.watch_enabled = false;
+        self.watcher = None;
+        notification::error(ctx, format!("{e}，可以使用刷新按钮手动更新"));
+    }
+
+    /// 把监控到的变化应用到文件树
+    fn poll_watcher(&mut self, ctx: &Context) {
+        let (Some(watcher), Some(tree)) = (&self.watcher, &mut self.tree) else {
+            return;
+        };
+        let changes = watcher.poll_changes();
+        if changes.is_empty() {
+            return;
+        }
+        let mut failed = None;
+        for change in changes {
+            match change {
+                WatchChange::Updated(node) => {
+                    tree.insert(node);
+                }
+                WatchChange::Removed(path) => {
+                    tree.remove(&path);
+                }
+                WatchChange::RescanNeeded => self.rescan_requested = true,
+                WatchChange::Failed(e) => failed = Some(e),
+            }
+        }
+        self.tree_revision += 1;
+        self.snapshot_view.refresh(tree);
+        if let Some(e) = failed {
+            self.stop_watching(ctx, &e);
+        }
+    }
+
+    /// 扫描进度和取消按钮
+    fn show_scan_progress(&mut self, ui: &mut Ui) {
+        let Some(scan) = &self.scan else {
+            return;
+        };
+        let progress = scan.progress();
+        let mut cancel = false;
+        ui.horizontal(|ui| {
+            ui.spinner();
+            ui.label(format!(
+                "正在扫描：{} 个文件夹，{} 个文件，{}",
+                progress.dirs_visited(),
+                progress.files_visited(),
+                human_readable_size(progress.bytes_counted())
+            ));
+            if progress.entries_excluded() > 0 {
+                ui.label(format!("已排除 {} 项，{}", progress.entries_excluded(), human_readable_size(progress.bytes_excluded())));
+            }
+            if progress.issue_count() > 0 {
+                ui.label(egui::RichText::new(format!("{} 个错误", progress.issue_count())).color(ui.visuals().warn_fg_color));
+            }
+            if progress.is_cancelled() {
+                ui.label("正在取消...");
+            } else if ui.button("取消").clicked() {
+                cancel = true;
+            }
+        });
+        ui.add(egui::Label::new(egui::RichText::new(progress.current_path()).weak()).truncate());
+        if cancel {
+            self.cancel_scan();
+        }
+    }
+
+    /// 排除规则、.gitignore、跨文件系统等扫描选项
+    fn show_scan_options(&mut self, ui: &mut Ui) {
+        let title = if self.scan_options.is_empty() && self.exclude_text.trim().is_empty() { "⚙ 扫描选项" } else { "⚙ 扫描选项 *" };
+        ui.menu_button(title, |ui| {
+            ui.label("排除的文件、文件夹，多个用空格或逗号分隔");
+            ui.add(egui::TextEdit::multiline(&mut self.exclude_text)
+                .hint_text("target node_modules *.log web/dist")
+                .desired_rows(2));
+            ui.checkbox(&mut self.scan_options.respect_ignore_files, "遵守 .gitignore、.ignore，跳过 .git");
+            ui.checkbox(&mut self.scan_options.same_file_system, "不进入其他文件系统上的文件夹")
+                .on_hover_text("只在 Linux、macOS 上生效");
+            ui.label(egui::RichText::new("被排除的大小单独统计，不计入文件夹大小").weak());
+            if ui.add_enabled(!self.folder_path.is_empty(), egui::Button::new("应用并重新扫描")).clicked() {
+                ui.close_menu();
+                self.rescan_requested = true;
+            }
+        });
+    }
+
+    /// 实时更新开关和手动刷新按钮
+    fn show_refresh_controls(&mut self, ui: &mut Ui) {
+        if self.folder_path.is_empty() {
+            return;
+        }
+        if ui.add_enabled(!self.loading, egui::Button::new("🔄 刷新")).on_hover_text("重新扫描当前文件夹").clicked() {
+            self.rescan_requested = true;
+        }
+        ui.checkbox(&mut self.watch_enabled, "实时更新")
+            .on_hover_text("扫描完成后监控文件变化，新增、修改、删除的文件会自动更新到列表中");
+    }
+
+    /// 打开错误列表的按钮
+    fn show_issues_button(&mut self, ui: &mut Ui) {
+        if self.scan_issues.is_empty() {
+            return;
+        }
+        let text = egui::RichText::new(format!("⚠ {} 个错误", self.scan_issues.len())).color(ui.visuals().warn_fg_color);
+        if ui.button(text).on_hover_text("这些文件、文件夹读取失败，没有统计").clicked() {
+            self.show_issues = !self.show_issues;
+        }
+    }
+
+    /// 扫描错误列表
+    fn show_issues_window(&mut self, ctx: &Context, ui: &Ui) {
+        if !self.show_issues {
+            return;
+        }
+        let mut open = self.show_issues;
+        egui::Window::new("扫描错误")
+            .id(ui.id().with("scan_issues"))
+            .open(&mut open)
+            .default_size(egui::vec2(520.0, 300.0))
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(format!("共 {} 个，对应的文件、文件夹没有统计", self.scan_issues.len()));
+                    if ui.button("复制").clicked() {
+                        let text: Vec<String> = self.scan_issues.iter()
+                            .map(|issue| format!("{}\t{}", issue.path.display(), issue.message))
+                            .collect();
+                        ctx.copy_text(text.join("\n"));
+                        notification::success(ctx, "已复制");
+                    }
+                });
+                ui.separator();
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for issue in &self.scan_issues {
+                        ui.horizontal_wrapped(|ui| {
+                            ui.label(issue.path.display().to_string());
+                            ui.label(egui::RichText::new(&issue.message).color(ui.visuals().warn_fg_color));
+                        });
+                    }
+                });
+            });
+        self.show_issues = open;
+    }
+
+    pub fn show_select_file_info(&mut self, ui: &mut Ui) {
+        if self.folder_path.is_empty() {
+            return;
+        }
+        //存在文件再展示
+        ui.label(format!("已选择的文件夹: {}", self.folder_path));
+    }
+
+    pub fn show_sub_file_info(&mut self, ctx: &Context, ui: &mut Ui) {
+        let Some(tree) = &self.tree else {
+            return;
+        };
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.selectable_value(&mut self.view_mode, ViewMode::Tree, "☰ 列表");
+            ui.selectable_value(&mut self.view_mode, ViewMode::Treemap, "▦ 矩形树图");
+            ui.selectable_value(&mut self.view_mode, ViewMode::Stats, "📊 类型统计");
+            ui.selectable_value(&mut self.view_mode, ViewMode::Duplicates, "⧉ 重复文件");
+            ui.selectable_value(&mut self.view_mode, ViewMode::Reports, "🧹 清理报告");
+            ui.selectable_value(&mut self.view_mode, ViewMode::Snapshot, "🕒 快照对比");
+            if self.view_mode == ViewMode::Tree {
+                ui.separator();
+                show_export_menu(ctx, ui, &tree.name, || self.tree_view.visible_files(tree, self.tree_revision));
+            }
+            if matches!(self.view_mode, ViewMode::Tree | ViewMode::Reports | ViewMode::Duplicates) {
+                self.file_ops.show_undo_button(ui);
+            }
+            if !matches!(self.view_mode, ViewMode::Duplicates | ViewMode::Snapshot) {
+                ui.separator();
+                self.tree_view.show_navigation(ui, tree);
+            }
+        });
+        let view_node = self.tree_view.view_node(tree);
+        if view_node.excluded_size > 0 && !matches!(self.view_mode, ViewMode::Duplicates | ViewMode::Snapshot) {
+            ui.label(egui::RichText::new(format!(
+                "合计 {}，另有 {} 被排除，未计入",
+                human_readable_size(view_node.size),
+                human_readable_size(view_node.excluded_size)
+            )).weak());
+        }
+        ui.separator();
+        let mut file_request = None;
+        let mut resolved_duplicates = None;
+        match self.view_mode {
+            ViewMode::Tree => file_request = self.tree_view.show_table(ui, tree, self.tree_revision),
+            ViewMode::Treemap => {
+                let view_node = self.tree_view.view_node(tree);
+                if let Some(path) = treemap_view::show_treemap(ui, view_node) {
+                    self.tree_view.enter(path);
+                }
+            }
+            ViewMode::Stats => {
+                let view_node = self.tree_view.view_node(tree);
+                self.stats_view.show(ctx, ui, view_node, self.tree_revision, self.loading);
+            }
+            ViewMode::Duplicates => resolved_duplicates = self.duplicate_view.show(ctx, ui, tree, self.loading),
+            ViewMode::Reports => {
+                let view_node = self.tree_view.view_node(tree);
+                file_request = self.report_view.show(ctx, ui, view_node, self.tree_revision, self.loading);
+            }
+            ViewMode::Snapshot => self.snapshot_view.show(ctx, ui, tree, self.loading),
+        }
+        if let Some(request) = file_request {
+            self.file_ops.request(ctx, request, tree);
+        }
+        let mut changed = self.file_ops.show(ctx, ui, self.tree.as_mut());
+        if let Some(resolved) = resolved_duplicates {
+            changed |= self.apply_resolved_duplicates(resolved);
+        }
+        if changed {
+            self.tree_revision += 1;
+            if let Some(tree) = &self.tree {
+                self.snapshot_view.refresh(tree);
+            }
+        }
+    }
+
+    /// 把重复文件的处理结果同步到文件树，不需要重新扫描，文件树有变化时返回 true
+    fn apply_resolved_duplicates(&mut self, resolved: ResolvedDuplicates) -> bool {
+        let Some(tree) = &mut self.tree else {
+            return false;
+        };
+        match resolved.kind {
+            ResolveKind::Trash => self.file_ops.add_trashed(tree, &resolved.paths),
+            ResolveKind::HardLink => {
+                for path in &resolved.paths {
+                    //与扫描时一致，同一文件的其他硬链接大小记为 0
+                    if let Some(mut node) = tree.remove(path) {
+                        node.size = 0;
+                        node.allocated_size = 0;
+                        tree.insert(node);
+                    }
+                }
+            }
+        }
+        true
+    }
+}
+
+/// 导出列表，files 在点击时才调用，name 为默认的文件名
+fn show_export_menu(ctx: &Context, ui: &mut Ui, name: &str, mut files: impl FnMut() -> Vec<FileInfo>) {
+    ui.menu_button("📤 导出", |ui| {
+        for format in ExportFormat::iter() {
+            ui.horizontal(|ui| {
+                ui.label(format.as_label());
+                if ui.button("保存为文件").clicked() {
+                    ui.close_menu();
+                    save_export(ctx, &files(), format, name);
+                }
+                if ui.button("复制").clicked() {
+                    ui.close_menu();
+                    let files = files();
+                    match export_files(&files, format) {
+                        Ok(text) => {
+                            ctx.copy_text(text);
+                            notification::success(ctx, format!("已复制 {} 行", files.len()));
+                        }
+                        Err(e) => notification::error(ctx, e.to_string()),
+                    }
+                }
+            });
+        }
+    });
+}
+
+/// 选择保存位置并写入导出内容
+fn save_export(ctx: &Context, files: &[FileInfo], format: ExportFormat, folder_name: &str) {
+    let text = match export_files(files, format) {
+        Ok(text) => text,
+        Err(e) => {
+            notification::error(ctx, e.to_string());
+            return;
+        }
+    };
+    let Some(path) = FileDialog::new()
+        .add_filter(format.as_label(), &[format.extension()])
+        .set_file_name(format!("{folder_name}.{}", format.extension()))
+        .save_file() else {
+        //未选择
+        return;
+    };
+    match fs::write(&path, text) {
+        Ok(()) => notification::success(ctx, format!("已导出到 {}", path.display())),
+        Err(e) => notification::error(ctx, format!("写入文件:{path:?} 失败:{e}")),
+    }
+}
+
+impl Tool for FolderInfoTool {
+    fn label(&self) -> &str {
+        "文件夹信息"
+    }
+
+    fn icon(&self) -> &str {
+        "📁"
+    }
+
+    fn category(&self) -> ToolCategory {
+        ToolCategory::File
+    }
+
+    fn save(&self) -> Option<String> {
+        let mut scan_options = self.scan_options.clone();
+        scan_options.exclude_patterns = ScanOptions::parse_patterns(&self.exclude_text);
+        let state = FolderInfoState {
+            folder_path: self.folder_path.clone(),
+            scan_options,
+            watch_enabled: self.watch_enabled,
+        };
+        serde_json::to_string(&state).ok()
+    }
+
+    fn restore(&mut self, state: &str) {
+        let state = match serde_json::from_str::<FolderInfoState>(state) {
+            Ok(state) => state,
+            Err(e) => {
+                error!("恢复文件夹信息工具状态失败:{e}");
+                return;
+            }
+        };
+        self.exclude_text = state.scan_options.exclude_patterns.join(" ");
+        self.scan_options = state.scan_options;
+        self.watch_enabled = state.watch_enabled;
+        if state.folder_path.is_empty() {
+            return;
+        }
+        //下一帧重新读取上次选择的文件夹
+        self.folder_path = state.folder_path;
+        self.rescan_requested = true;
+    }
+
+    fn open_dropped(&mut self, ctx: &egui::Context, paths: &[PathBuf]) -> bool {
+        //拖入多个时只扫描第一个文件夹
+        let Some(dir) = paths.iter().find(|path| path.is_dir()) else {
+            return false;
+        };
+        self.load_folder(ctx, dir.clone());
+        true
+    }
+
+    fn show(&mut self, ctx: &egui::Context, ui: &mut Ui, _frame: &mut eframe::Frame) {
+        if self.rescan_requested {
+            self.load_folder(ctx, PathBuf::from(&self.folder_path));
+        }
+        self.poll_scan(ctx);
+        self.update_watcher(ctx);
+        self.poll_watcher(ctx);
+        ui.horizontal(|ui| {
+            self.add_choose_folder_button(ctx, ui);
+            self.show_scan_options(ui);
+            self.show_refresh_controls(ui);
+            self.show_select_file_info(ui);
+            self.show_issues_button(ui);
+        });
+        self.show_issues_window(ctx, ui);
+        self.show_scan_progress(ui);
+        self.show_sub_file_info(ctx, ui);
+    }
+}
+
+impl Drop for FolderInfoTool {
+    /// 关闭标签时停止后台扫描
+    fn drop(&mut self) {
+        self.cancel_scan();
+        self.stats_view.cancel();
+        self.duplicate_view.cancel();
+        self.file_ops.cancel();
+    }
+}
@@ -0,0 +1,202 @@
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use egui::{Context, Label, RichText, Separator, Ui};
+
+use crate::notification;
+use crate::service::folder_scan::{format_time, FileInfo, FileNode};
+use crate::service::report::{build_report, ReportKind, ReportOptions};
+use crate::service::scan_filter::ScanOptions;
+use crate::tool::folder_info_tool::file_ops_view::FileRequest;
+use crate::tool::folder_info_tool::show_export_menu;
+use crate::util::file_util::{human_readable_size, open_in_file_manager};
+
+/// 报告结果对应的文件夹、报告类型、参数和文件树版本，变化时重新生成
+type ReportKey = (PathBuf, ReportKind, ReportOptions, u64);
+
+/// 最大的文件、长期未修改、空文件、临时文件等清理报告，可以导出、批量移到回收站
+#[derive(Clone)]
+pub struct ReportView {
+    kind: ReportKind,
+    options: ReportOptions,
+    /// 正在编辑的临时文件规则
+    temp_patterns_text: String,
+    /// 报告结果，规则错误时为错误信息
+    results: Option<Result<Vec<FileNode>, String>>,
+    computed_for: Option<ReportKey>,
+    /// 勾选的文件，用于批量操作
+    selected: HashSet<PathBuf>,
+}
+
+impl Default for ReportView {
+    fn default() -> Self {
+        let options = ReportOptions::default();
+        Self {
+            kind: ReportKind::Largest,
+            temp_patterns_text: options.temp_patterns.join(" "),
+            options,
+            results: None,
+            computed_for: None,
+            selected: HashSet::new(),
+        }
+    }
+}
+
+impl ReportView {
+    /// 扫描新文件夹时清空结果
+    pub fn reset(&mut self) {
+        self.results = None;
+        self.computed_for = None;
+        self.selected.clear();
+    }
+
+    /// revision 在文件树变化时增加，scanning 为 true 时文件树还不完整，等扫描完成后再生成
+    pub fn show(&mut self, ctx: &Context, ui: &mut Ui, view_node: &FileNode, revision: u64, scanning: bool) -> Option<FileRequest> {
+        self.show_options(ui);
+        ui.separator();
+        if scanning {
+            ui.label("等待扫描完成后生成报告");
+            return None;
+        }
+        let current = Some((view_node.path.clone(), self.kind, self.options.clone(), revision));
+        if self.computed_for != current {
+            self.computed_for = current;
+            self.refresh(view_node);
+        }
+        let nodes = match &self.results {
+            Some(Ok(nodes)) => nodes,
+            Some(Err(e)) => {
+                ui.label(RichText::new(e).color(ui.visuals().error_fg_color));
+                return None;
+            }
+            None => return None,
+        };
+        let mut request = None;
+        let mut select_all = None;
+        ui.horizontal(|ui| {
+            let total_size: u64 = nodes.iter().map(|node| node.size).sum();
+            ui.label(format!("共 {} 项，{}", nodes.len(), human_readable_size(total_size)));
+            ui.separator();
+            if ui.button("全选").clicked() {
+                select_all = Some(true);
+            }
+            if ui.button("全不选").clicked() {
+                select_all = Some(false);
+            }
+            let selected_size: u64 = nodes.iter().filter(|node| self.selected.contains(&node.path)).map(|node| node.size).sum();
+            let trash_text = format!("🗑 移到回收站 ({} 项，{})", self.selected.len(), human_readable_size(selected_size));
+            if ui.add_enabled(!self.selected.is_empty(), egui::Button::new(trash_text)).clicked() {
+                let paths = nodes.iter().filter(|node| self.selected.contains(&node.path)).map(|node| node.path.clone()).collect();
+                request = Some(FileRequest::Trash(paths));
+            }
+            ui.separator();
+            let export_name = format!("{}-{}", view_node.name, self.kind.as_label());
+            show_export_menu(ctx, ui, &export_name, || nodes.iter().map(FileNode::to_file_info).collect::<Vec<FileInfo>>());
+        });
+        match select_all {
+            Some(true) => self.selected = nodes.iter().map(|node| node.path.clone()).collect(),
+            Some(false) => self.selected.clear(),
+            None => {}
+        }
+        ui.separator();
+        self.show_table(ctx, ui);
+        request
+    }
+
+    fn refresh(&mut self, view_node: &FileNode) {
+        let results = build_report(view_node, self.kind, &self.options, SystemTime::now()).map_err(|e| e.to_string());
+        if let Ok(nodes) = &results {
+            //去掉已删除或不再符合条件的勾选
+            let paths: HashSet<&PathBuf> = nodes.iter().map(|node| &node.path).collect();
+            self.selected.retain(|path| paths.contains(path));
+        }
+        self.results = Some(results);
+    }
+
+    /// 报告类型和对应参数
+    fn show_options(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            for kind in ReportKind::iter() {
+                ui.selectable_value(&mut self.kind, kind, kind.as_label());
+            }
+            ui.separator();
+            match self.kind {
+                ReportKind::Largest => {
+                    ui.label("数量:");
+                    ui.add(egui::DragValue::new(&mut self.options.top_n).range(1..=100_000));
+                }
+                ReportKind::Old => {
+                    ui.label("超过");
+                    ui.add(egui::DragValue::new(&mut self.options.older_than_days).range(1..=36_500));
+                    ui.label("天未修改");
+                }
+                ReportKind::Empty => {
+                    ui.label("大小为 0 的文件，以及不包含任何文件的文件夹");
+                }
+                ReportKind::Temp => {
+                    ui.label("规则:");
+                    let input = egui::TextEdit::singleline(&mut self.temp_patterns_text)
+                        .hint_text("*.tmp *~ .DS_Store")
+                        .desired_width(320.0);
+                    if ui.add(input).on_hover_text("匹配文件名，多个用空格或逗号分隔，大小为 0 的文件也会列出").changed() {
+                        self.options.temp_patterns = ScanOptions::parse_patterns(&self.temp_patterns_text);
+                    }
+                }
+            }
+        });
+    }
+
+    fn show_table(&mut self, ctx: &Context, ui: &mut Ui) {
+        let Some(Ok(nodes)) = &self.results else {
+            return;
+        };
+        let available_width = ui.available_width();
+        let row_height = ui.spacing().interact_size.y;
+        ui.horizontal(|ui| {
+            ui.add_sized(egui::vec2(available_width * 0.07, row_height), Label::new(""));
+            ui.add(Separator::default().spacing(0.0));
+            ui.add_sized(egui::vec2(available_width * 0.2, row_height), Label::new("文件名"));
+            ui.add(Separator::default().spacing(0.0));
+            ui.add_sized(egui::vec2(available_width * 0.39, row_height), Label::new("文件路径"));
+            ui.add(Separator::default().spacing(0.0));
+            ui.add_sized(egui::vec2(available_width * 0.1, row_height), Label::new("大小"));
+            ui.add(Separator::default().spacing(0.0));
+            ui.add_sized(egui::vec2(available_width * 0.15, row_height), Label::new("修改时间"));
+        });
+        ui.add(Separator::default().spacing(0.0));
+
+        let mut toggled = vec![];
+        egui::ScrollArea::vertical().auto_shrink([false, false]).show_rows(ui, row_height, nodes.len(), |ui, row_range| {
+            for node in &nodes[row_range] {
+                ui.horizontal(|ui| {
+                    let mut checked = self.selected.contains(&node.path);
+                    ui.allocate_ui(egui::vec2(available_width * 0.07, row_height), |ui| {
+                        if ui.checkbox(&mut checked, "").changed() {
+                            toggled.push(node.path.clone());
+                        }
+                        if ui.small_button("📂").on_hover_text("打开所在文件夹").clicked() {
+                            if let Err(e) = open_in_file_manager(&node.path) {
+                                notification::error(ctx, format!("打开文件夹:{:?} 失败:{e}", node.path));
+                            }
+                        }
+                    });
+                    ui.add(Separator::default().spacing(0.0));
+                    let name = if node.is_file { node.name.clone() } else { format!("📁 {}", node.name) };
+                    ui.add_sized(egui::vec2(available_width * 0.2, row_height), Label::new(name).truncate());
+                    ui.add(Separator::default().spacing(0.0));
+                    ui.add_sized(egui::vec2(available_width * 0.39, row_height), Label::new(node.path.to_string_lossy()).truncate());
+                    ui.add(Separator::default().spacing(0.0));
+                    ui.add_sized(egui::vec2(available_width * 0.1, row_height), Label::new(human_readable_size(node.size)));
+                    ui.add(Separator::default().spacing(0.0));
+                    ui.add_sized(egui::vec2(available_width * 0.15, row_height), Label::new(format_time(node.modified)));
+                });
+            }
+        });
+        for path in toggled {
+            if !self.selected.remove(&path) {
+                self.selected.insert(path);
+            }
+        }
+    }
+}
@@ -0,0 +1,235 @@
+use std::path::PathBuf;
+
+use chrono::Local;
+use egui::{Color32, Context, Label, RichText, Separator, Ui};
+use rfd::FileDialog;
+
+use crate::notification;
+use crate::service::folder_scan::FileNode;
+use crate::service::snapshot::{diff_snapshots, load_snapshot, save_snapshot, sort_diffs, DiffKind, DiffSortColumn, Snapshot, SnapshotDiff};
+use crate::util::file_util::human_readable_size;
+
+/// 快照文件扩展名
+const SNAPSHOT_EXTENSION: &str = "json";
+
+/// 与快照的对比结果
+#[derive(Clone)]
+struct Comparison {
+    snapshot_path: PathBuf,
+    snapshot: Snapshot,
+    diffs: Vec<SnapshotDiff>,
+}
+
+/// 保存扫描快照，并与之前保存的快照对比
+#[derive(Clone)]
+pub struct SnapshotView {
+    comparison: Option<Comparison>,
+    /// 只显示某种变化，None 显示全部
+    kind_filter: Option<DiffKind>,
+    /// 是否显示文件夹，文件夹的变化包含子文件的变化
+    show_dirs: bool,
+    sort_column: DiffSortColumn,
+    sort_descending: bool,
+}
+
+impl Default for SnapshotView {
+    fn default() -> Self {
+        Self {
+            comparison: None,
+            kind_filter: None,
+            show_dirs: true,
+            sort_column: DiffSortColumn::Change,
+            sort_descending: true,
+        }
+    }
+}
+
+impl SnapshotView {
+    /// 扫描完成后用新的扫描结果重新对比
+    pub fn refresh(&mut self, root: &FileNode) {
+        if let Some(comparison) = &mut self.comparison {
+            comparison.diffs = diff_snapshots(&comparison.snapshot, &Snapshot::from_tree(root));
+            sort_diffs(&mut comparison.diffs, self.sort_column, self.sort_descending);
+        }
+    }
+
+    /// scanning 为 true 时文件树还不完整，不允许保存、对比
+    pub fn show(&mut self, ctx: &Context, ui: &mut Ui, root: &FileNode, scanning: bool) {
+        ui.horizontal(|ui| {
+            if ui.add_enabled(!scanning, egui::Button::new("💾 保存快照")).clicked() {
+                save_snapshot_file(ctx, root);
+            }
+            if ui.add_enabled(!scanning, egui::Button::new("📂 打开快照对比")).clicked() {
+                self.open_snapshot_file(ctx, root);
+            }
+            if scanning {
+                ui.label("等待扫描完成");
+            }
+        });
+        ui.separator();
+        let Some(comparison) = &self.comparison else {
+            ui.label("保存当前扫描结果为快照，之后重新扫描时打开快照，查看哪些文件新增、删除、变大、变小");
+            return;
+        };
+        show_summary(ui, comparison, root);
+        ui.horizontal(|ui| {
+            ui.selectable_value(&mut self.kind_filter, None, "全部");
+            for kind in DiffKind::iter() {
+                let count = comparison.diffs.iter().filter(|diff| diff.kind == kind).count();
+                ui.selectable_value(&mut self.kind_filter, Some(kind), format!("{} ({count})", kind.as_label()));
+            }
+            ui.separator();
+            ui.checkbox(&mut self.show_dirs, "显示文件夹");
+        });
+        ui.separator();
+        self.show_diff_table(ui);
+    }
+
+    fn open_snapshot_file(&mut self, ctx: &Context, root: &FileNode) {
+        let Some(path) = FileDialog::new().add_filter("快照", &[SNAPSHOT_EXTENSION]).pick_file() else {
+            //未选择
+            return;
+        };
+        let snapshot = match load_snapshot(&path) {
+            Ok(snapshot) => snapshot,
+            Err(e) => {
+                notification::error(ctx, e.to_string());
+                return;
+            }
+        };
+        if snapshot.root != root.path {
+            notification::warning(ctx, format!("快照来自其他文件夹:{}，按相对路径对比", snapshot.root.display()));
+        }
+        self.comparison = Some(Comparison {
+            snapshot_path: path,
+            snapshot,
+            diffs: vec![],
+        });
+        self.refresh(root);
+    }
+
+    fn show_diff_table(&mut self, ui: &mut Ui) {
+        let Some(comparison) = &self.comparison else {
+            return;
+        };
+        let available_width = ui.available_width();
+        let row_height = ui.spacing().interact_size.y;
+        let mut sort_clicked = None;
+        ui.horizontal(|ui| {
+            ui.add_sized(egui::vec2(available_width * 0.08, row_height), Label::new("变化"));
+            ui.add(Separator::default().spacing(0.0));
+            let path_title = self.sort_title("路径", DiffSortColumn::Path);
+            if ui.add_sized(egui::vec2(available_width * 0.5, row_height), egui::Button::new(path_title).frame(false)).clicked() {
+                sort_clicked = Some(DiffSortColumn::Path);
+            }
+            ui.add(Separator::default().spacing(0.0));
+            ui.add_sized(egui::vec2(available_width * 0.12, row_height), Label::new("原大小"));
+            ui.add(Separator::default().spacing(0.0));
+            ui.add_sized(egui::vec2(available_width * 0.12, row_height), Label::new("新大小"));
+            ui.add(Separator::default().spacing(0.0));
+            let change_title = self.sort_title("变化量", DiffSortColumn::Change);
+            if ui.add_sized(egui::vec2(available_width * 0.12, row_height), egui::Button::new(change_title).frame(false)).clicked() {
+                sort_clicked = Some(DiffSortColumn::Change);
+            }
+        });
+        ui.add(Separator::default().spacing(0.0));
+
+        let rows: Vec<&SnapshotDiff> = comparison.diffs.iter()
+            .filter(|diff| self.kind_filter.is_none_or(|kind| diff.kind == kind))
+            .filter(|diff| self.show_dirs || diff.is_file)
+            .collect();
+        egui::ScrollArea::vertical().auto_shrink([false, false]).show_rows(ui, row_height, rows.len(), |ui, row_range| {
+            for diff in &rows[row_range] {
+                show_diff_row(ui, diff, available_width, row_height);
+            }
+        });
+
+        if let Some(column) = sort_clicked {
+            self.toggle_sort(column);
+        }
+    }
+
+    fn sort_title(&self, title: &str, column: DiffSortColumn) -> String {
+        if self.sort_column != column {
+            title.to_string()
+        } else if self.sort_descending {
+            format!("{title} ⏷")
+        } else {
+            format!("{title} ⏶")
+        }
+    }
+
+    /// 点击列头：同一列切换方向，变化量默认从大到小
+    fn toggle_sort(&mut self, column: DiffSortColumn) {
+        if self.sort_column == column {
+            self.sort_descending = !self.sort_descending;
+        } else {
+            self.sort_column = column;
+            self.sort_descending = column == DiffSortColumn::Change;
+        }
+        if let Some(comparison) = &mut self.comparison {
+            sort_diffs(&mut comparison.diffs, self.sort_column, self.sort_descending);
+        }
+    }
+}
+
+/// 选择保存位置，保存当前扫描结果
+fn save_snapshot_file(ctx: &Context, root: &FileNode) {
+    let file_name = format!("{}-{}.snapshot.{SNAPSHOT_EXTENSION}", root.name, Local::now().format("%Y%m%d-%H%M%S"));
+    let Some(path) = FileDialog::new()
+        .add_filter("快照", &[SNAPSHOT_EXTENSION])
+        .set_file_name(file_name)
+        .save_file() else {
+        //未选择
+        return;
+    };
+    match save_snapshot(&path, &Snapshot::from_tree(root)) {
+        Ok(()) => notification::success(ctx, format!("已保存快照到 {}", path.display())),
+        Err(e) => notification::error(ctx, e.to_string()),
+    }
+}
+
+fn show_summary(ui: &mut Ui, comparison: &Comparison, root: &FileNode) {
+    let old_size = comparison.snapshot.total_size();
+    let delta = root.size as i64 - old_size as i64;
+    ui.label(format!("快照: {}，保存于 {}", comparison.snapshot_path.display(), comparison.snapshot.created));
+    ui.label(format!(
+        "总大小 {} → {}，{}，共 {} 处变化",
+        human_readable_size(old_size),
+        human_readable_size(root.size),
+        format_delta(delta),
+        comparison.diffs.len()
+    ));
+}
+
+fn show_diff_row(ui: &mut Ui, diff: &SnapshotDiff, available_width: f32, row_height: f32) {
+    ui.horizontal(|ui| {
+        let kind_column = Label::new(RichText::new(diff.kind.as_label()).color(kind_color(diff.kind)));
+        ui.add_sized(egui::vec2(available_width * 0.08, row_height), kind_column);
+        ui.add(Separator::default().spacing(0.0));
+        let path = if diff.is_file { diff.path.clone() } else { format!("📁 {}", diff.path) };
+        ui.add_sized(egui::vec2(available_width * 0.5, row_height), Label::new(path).truncate());
+        ui.add(Separator::default().spacing(0.0));
+        let old_size = diff.old_size.map_or("--".to_string(), human_readable_size);
+        ui.add_sized(egui::vec2(available_width * 0.12, row_height), Label::new(old_size));
+        ui.add(Separator::default().spacing(0.0));
+        let new_size = diff.new_size.map_or("--".to_string(), human_readable_size);
+        ui.add_sized(egui::vec2(available_width * 0.12, row_height), Label::new(new_size));
+        ui.add(Separator::default().spacing(0.0));
+        let delta_column = Label::new(RichText::new(format_delta(diff.delta())).color(kind_color(diff.kind)));
+        ui.add_sized(egui::vec2(available_width * 0.12, row_height), delta_column);
+    });
+}
+
+/// 带符号的大小变化
+fn format_delta(delta: i64) -> String {
+    let sign = if delta < 0 { "-" } else { "+" };
+    format!("{sign}{}", human_readable_size(delta.unsigned_abs()))
+}
+
+fn kind_color(kind: DiffKind) -> Color32 {
+    match kind {
+        DiffKind::Added | DiffKind::Grown => Color32::from_rgb(230, 120, 100),
+        DiffKind::Removed | DiffKind::Shrunk => Color32::from_rgb(110, 190, 110),
+    }
+}
@@ -0,0 +1,168 @@
+use std::path::PathBuf;
+
+use egui::{Color32, Context, Label, RichText, Sense, Ui};
+
+use crate::service::folder_scan::FileNode;
+use crate::service::type_stats::{type_stats_in_background, TypeGrouping, TypeStats, TypeStatsHandle};
+use crate::util::file_util::human_readable_size;
+
+/// 条形图颜色
+const BAR_COLOR: Color32 = Color32::from_rgb(80, 150, 230);
+
+/// 按扩展名或 MIME 类型汇总当前文件夹下的所有文件
+pub struct StatsView {
+    grouping: TypeGrouping,
+    stats: Option<Vec<TypeStats>>,
+    /// 结果对应的文件夹、分组方式和文件树版本，变化时重新统计
+    computed_for: Option<(PathBuf, TypeGrouping, u64)>,
+    task: Option<TypeStatsHandle>,
+}
+
+impl Default for StatsView {
+    fn default() -> Self {
+        Self {
+            grouping: TypeGrouping::Extension,
+            stats: None,
+            computed_for: None,
+            task: None,
+        }
+    }
+}
+
+impl Clone for StatsView {
+    /// 统计句柄不能共享，复制时只复制已有结果
+    fn clone(&self) -> Self {
+        Self {
+            grouping: self.grouping,
+            stats: self.stats.clone(),
+            computed_for: if self.task.is_some() { None } else { self.computed_for.clone() },
+            task: None,
+        }
+    }
+}
+
+impl StatsView {
+    /// 扫描新文件夹时清空结果
+    pub fn reset(&mut self) {
+        self.cancel();
+        self.stats = None;
+        self.computed_for = None;
+    }
+
+    pub fn cancel(&mut self) {
+        if let Some(task) = self.task.take() {
+            task.cancel();
+        }
+    }
+
+    fn start(&mut self, ctx: &Context, view_node: &FileNode, revision: u64) {
+        self.cancel();
+        let grouping = self.grouping;
+        self.computed_for = Some((view_node.path.clone(), grouping, revision));
+        let repaint_ctx = ctx.clone();
+        self.task = Some(type_stats_in_background(view_node.collect_files(), grouping, move || repaint_ctx.request_repaint()));
+    }
+
+    fn poll_task(&mut self, ctx: &Context) {
+        let Some(task) = &self.task else {
+            return;
+        };
+        let Some(stats) = task.poll_result() else {
+            //还在统计，刷新进度
+            ctx.request_repaint_after(std::time::Duration::from_millis(200));
+            return;
+        };
+        self.stats = Some(stats);
+        self.task = None;
+    }
+
+    /// revision 在文件树变化时增加，scanning 为 true 时文件树还不完整，等扫描完成后再统计
+    pub fn show(&mut self, ctx: &Context, ui: &mut Ui, view_node: &FileNode, revision: u64, scanning: bool) {
+        self.poll_task(ctx);
+        let mut restart = false;
+        ui.horizontal(|ui| {
+            ui.label("统计方式:");
+            ui.selectable_value(&mut self.grouping, TypeGrouping::Extension, TypeGrouping::Extension.as_label());
+            ui.selectable_value(&mut self.grouping, TypeGrouping::Mime, TypeGrouping::Mime.as_label())
+                .on_hover_text("读取文件头识别类型，文件多时较慢");
+            ui.separator();
+            if let Some(task) = &self.task {
+                ui.spinner();
+                ui.label(format!("正在统计，已处理 {} 个文件", task.progress().files_visited()));
+                if ui.button("取消").clicked() {
+                    self.cancel();
+                }
+            } else if ui.add_enabled(!scanning, egui::Button::new("🔄 重新统计")).clicked() {
+                restart = true;
+            }
+        });
+        ui.separator();
+        if scanning {
+            ui.label("等待扫描完成后统计");
+            return;
+        }
+        let current = Some((view_node.path.clone(), self.grouping, revision));
+        if restart || (self.task.is_none() && self.computed_for != current) {
+            //只是文件树有变化时保留旧结果，统计完成后替换，避免实时更新时列表闪烁
+            let same_target = self.computed_for.as_ref().is_some_and(|(path, grouping, _)| *path == view_node.path && *grouping == self.grouping);
+            if !same_target {
+                self.stats = None;
+            }
+            self.start(ctx, view_node, revision);
+        }
+        let Some(stats) = &self.stats else {
+            return;
+        };
+        show_stats_table(ui, stats);
+    }
+}
+
+fn show_stats_table(ui: &mut Ui, stats: &[TypeStats]) {
+    let total_size: u64 = stats.iter().map(|stats| stats.total_size).sum();
+    let total_count: u64 = stats.iter().map(|stats| stats.count).sum();
+    let max_size = stats.first().map_or(0, |stats| stats.total_size);
+    ui.label(format!("共 {} 种类型，{} 个文件，{}", stats.len(), total_count, human_readable_size(total_size)));
+
+    let available_width = ui.available_width();
+    let row_height = ui.spacing().interact_size.y;
+    egui::ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
+        for type_stats in stats {
+            let id = ui.make_persistent_id(&type_stats.key);
+            egui::collapsing_header::CollapsingState::load_with_default_open(ui.ctx(), id, false)
+                .show_header(ui, |ui| {
+                    ui.add_sized(egui::vec2(available_width * 0.15, row_height), Label::new(RichText::new(&type_stats.key).strong()).truncate());
+                    ui.add_sized(egui::vec2(available_width * 0.1, row_height), Label::new(format!("{} 个", type_stats.count)));
+                    ui.add_sized(egui::vec2(available_width * 0.12, row_height), Label::new(human_readable_size(type_stats.total_size)));
+                    show_bar(ui, type_stats.total_size, max_size, total_size, egui::vec2(available_width * 0.5, row_height));
+                })
+                .body(|ui| {
+                    ui.label(RichText::new("最大的文件").weak());
+                    for (path, size) in &type_stats.largest_files {
+                        ui.horizontal(|ui| {
+                            ui.add_sized(egui::vec2(available_width * 0.12, row_height), Label::new(human_readable_size(*size)));
+                            ui.add(Label::new(path.display().to_string()).truncate());
+                        });
+                    }
+                });
+        }
+    });
+}
+
+/// 条形长度相对最大的类型，文字为占总大小的比例
+fn show_bar(ui: &mut Ui, size: u64, max_size: u64, total_size: u64, bar_size: egui::Vec2) {
+    let length = if max_size == 0 { 0.0 } else { size as f32 / max_size as f32 };
+    let percentage = if total_size == 0 { 0.0 } else { size as f32 / total_size as f32 * 100.0 };
+    let (rect, _) = ui.allocate_exact_size(bar_size, Sense::hover());
+    let bar_rect = rect.shrink2(egui::vec2(0.0, 3.0));
+    let mut filled_rect = bar_rect;
+    filled_rect.set_width(bar_rect.width() * length);
+    let painter = ui.painter();
+    painter.rect_filled(filled_rect, 2.0, BAR_COLOR);
+    painter.text(
+        bar_rect.left_center() + egui::vec2(4.0, 0.0),
+        egui::Align2::LEFT_CENTER,
+        format!("{percentage:.1}%"),
+        egui::TextStyle::Small.resolve(ui.style()),
+        ui.visuals().strong_text_color(),
+    );
+}
@@ -0,0 +1,438 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use egui::{Color32, Context, Label, Response, Sense, Separator, Ui};
+
+use crate::notification;
+use crate::service::file_list::{file_type_label, EntryKindFilter, FileFilter, FileSort, SortColumn};
+use crate::service::folder_scan::{format_time, FileInfo, FileNode};
+use crate::tool::folder_info_tool::file_ops_view::FileRequest;
+use crate::util::file_util::{human_readable_size, open_in_file_manager, open_with_default_app};
+
+/// 每层缩进的宽度
+const INDENT_WIDTH: f32 = 16.0;
+
+/// 列头和每列宽度占比
+const COLUMNS: [(SortColumn, &str, f32); 6] = [
+    (SortColumn::Name, "文件名", 0.25),
+    (SortColumn::Path, "文件路径", 0.22),
+    (SortColumn::Type, "类型", 0.07),
+    (SortColumn::Size, "大小", 0.09),
+    (SortColumn::AllocatedSize, "占用空间", 0.09),
+    (SortColumn::ModifiedTime, "修改时间", 0.13),
+];
+/// 占上级比例列的宽度占比
+const PERCENTAGE_COLUMN_WIDTH: f32 = 0.1;
+
+/// 树形列表上的操作，绘制完成后统一处理
+enum TreeAction {
+    /// 展开、收起文件夹
+    ToggleExpand(PathBuf),
+    /// 进入文件夹，只展示它的子节点
+    Enter(PathBuf),
+    /// 点击列头排序
+    Sort(SortColumn),
+    /// 在文件管理器中显示
+    Reveal(PathBuf),
+    /// 使用默认程序打开
+    Open(PathBuf),
+    /// 复制路径或名称
+    Copy(String),
+    /// 交给 FolderInfoTool 处理的文件操作
+    File(FileRequest),
+}
+
+/// 列表中的一行
+struct TreeRow<'a> {
+    node: &'a FileNode,
+    depth: usize,
+    /// 父节点大小，用于计算占比
+    parent_size: u64,
+}
+
+/// 缓存的一行，保存从当前文件夹到节点的子节点下标，文件树不变时可以直接找到节点
+#[derive(Clone)]
+struct CachedRow {
+    indices: Vec<usize>,
+    depth: usize,
+}
+
+/// 行缓存对应的状态，任一变化时重新生成
+#[derive(PartialEq, Clone)]
+struct RowsKey {
+    revision: u64,
+    view_path: PathBuf,
+    sort: FileSort,
+    filter: FileFilter,
+    expanded_version: u64,
+}
+
+/// 可展开、可下钻的文件树列表，所有操作都基于同一次扫描结果，不会重新扫描
+#[derive(Clone)]
+pub struct TreeView {
+    /// 当前所在的文件夹
+    view_path: PathBuf,
+    /// 已展开的文件夹
+    expanded: HashSet<PathBuf>,
+    sort: FileSort,
+    /// 有过滤条件时平铺展示当前文件夹下所有匹配的文件
+    filter: FileFilter,
+    /// 展开状态每次变化时增加
+    expanded_version: u64,
+    /// 排序、过滤后的行，文件多时每帧重新生成会很卡
+    rows: Option<(RowsKey, Vec<CachedRow>)>,
+}
+
+impl Default for TreeView {
+    fn default() -> Self {
+        Self {
+            view_path: PathBuf::new(),
+            expanded: HashSet::new(),
+            sort: FileSort::SIZE_DESC,
+            filter: FileFilter::default(),
+            expanded_version: 0,
+            rows: None,
+        }
+    }
+}
+
+impl TreeView {
+    /// 扫描新文件夹时重置状态
+    pub fn reset(&mut self, root_path: &Path) {
+        self.view_path = root_path.to_path_buf();
+        self.expanded.clear();
+        self.rows = None;
+    }
+
+    /// 进入文件夹
+    pub fn enter(&mut self, path: PathBuf) {
+        self.view_path = path;
+    }
+
+    /// 当前所在的文件夹节点，不存在时（例如重新扫描后被删除）回到根目录
+    pub fn view_node<'a>(&mut self, root: &'a FileNode) -> &'a FileNode {
+        match root.find(&self.view_path) {
+            Some(view_node) => view_node,
+            None => {
+                self.view_path = root.path.clone();
+                root
+            }
+        }
+    }
+
+    /// 面包屑导航和返回上一级按钮
+    pub fn show_navigation(&mut self, ui: &mut Ui, root: &FileNode) {
+        self.view_node(root);
+        let mut actions = vec![];
+        self.show_breadcrumbs(ui, root, &mut actions);
+        self.apply_actions(ui.ctx(), actions);
+    }
+
+    /// 当前文件夹下的文件列表，文件夹可以展开，右键菜单中选择的重命名等操作返回给调用方处理
+    ///
+    /// revision 在文件树变化时增加，用于判断缓存的行是否需要重新生成
+    pub fn show_table(&mut self, ui: &mut Ui, root: &FileNode, revision: u64) -> Option<FileRequest> {
+        let view_node = self.view_node(root);
+        let mut actions = vec![];
+
+        self.show_filter(ui);
+        let available_width = ui.available_width();
+        let row_height = ui.spacing().interact_size.y;
+        self.show_header(ui, available_width, row_height, &mut actions);
+
+        self.update_rows(view_node, revision);
+        let rows = self.cached_rows();
+        if rows.is_empty() && !self.filter.is_empty() {
+            ui.label("没有匹配的文件");
+        }
+        egui::ScrollArea::vertical().auto_shrink([false, false]).show_rows(ui, row_height, rows.len(), |ui, row_range| {
+            for row in &rows[row_range] {
+                if let Some(row) = resolve_row(view_node, row) {
+                    self.show_row(ui, &row, available_width, row_height, &mut actions);
+                }
+            }
+        });
+        self.apply_actions(ui.ctx(), actions)
+    }
+
+    /// 列表中当前显示的所有行，已应用排序、过滤和展开状态
+    pub fn visible_files(&mut self, root: &FileNode, revision: u64) -> Vec<FileInfo> {
+        let view_node = self.view_node(root);
+        self.update_rows(view_node, revision);
+        self.cached_rows().iter()
+            .filter_map(|row| resolve_row(view_node, row))
+            .map(|row| row.node.to_file_info())
+            .collect()
+    }
+
+    /// 排序、过滤、展开状态或文件树有变化时重新生成行
+    fn update_rows(&mut self, view_node: &FileNode, revision: u64) {
+        let key = RowsKey {
+            revision,
+            view_path: self.view_path.clone(),
+            sort: self.sort,
+            filter: self.filter.clone(),
+            expanded_version: self.expanded_version,
+        };
+        if self.rows.as_ref().is_some_and(|(cached_key, _)| *cached_key == key) {
+            return;
+        }
+        let mut rows = vec![];
+        if self.filter.is_empty() {
+            self.collect_rows(view_node, &mut vec![], &mut rows);
+        } else {
+            let mut matched = vec![];
+            self.collect_matched_rows(view_node, &mut vec![], &mut matched);
+            matched.sort_by(|(a, _), (b, _)| self.sort.compare_nodes(a, b));
+            rows = matched.into_iter().map(|(_, row)| row).collect();
+        }
+        self.rows = Some((key, rows));
+    }
+
+    fn cached_rows(&self) -> &[CachedRow] {
+        self.rows.as_ref().map_or(&[], |(_, rows)| rows)
+    }
+
+    fn apply_actions(&mut self, ctx: &Context, actions: Vec<TreeAction>) -> Option<FileRequest> {
+        let mut request = None;
+        for action in actions {
+            match action {
+                TreeAction::ToggleExpand(path) => {
+                    if !self.expanded.remove(&path) {
+                        self.expanded.insert(path);
+                    }
+                    self.expanded_version += 1;
+                }
+                TreeAction::Enter(path) => self.enter(path),
+                TreeAction::Sort(column) => self.sort.toggle(column),
+                TreeAction::Reveal(path) => {
+                    if let Err(e) = open_in_file_manager(&path) {
+                        notification::error(ctx, format!("打开文件夹:{path:?} 失败:{e}"));
+                    }
+                }
+                TreeAction::Open(path) => {
+                    if let Err(e) = open_with_default_app(&path) {
+                        notification::error(ctx, format!("打开:{path:?} 失败:{e}"));
+                    }
+                }
+                TreeAction::Copy(text) => {
+                    ctx.copy_text(text);
+                    notification::success(ctx, "已复制");
+                }
+                TreeAction::File(file_request) => request = Some(file_request),
+            }
+        }
+        request
+    }
+
+    /// 面包屑导航和返回上一级按钮
+    fn show_breadcrumbs(&self, ui: &mut Ui, root: &FileNode, actions: &mut Vec<TreeAction>) {
+        ui.horizontal_wrapped(|ui| {
+            let at_root = self.view_path == root.path;
+            if ui.add_enabled(!at_root, egui::Button::new("⬆ 上一级")).clicked() {
+                if let Some(parent) = self.view_path.parent() {
+                    actions.push(TreeAction::Enter(parent.to_path_buf()));
+                }
+            }
+            ui.separator();
+            if ui.selectable_label(at_root, &root.name).clicked() {
+                actions.push(TreeAction::Enter(root.path.clone()));
+            }
+            let Ok(relative_path) = self.view_path.strip_prefix(&root.path) else {
+                return;
+            };
+            let mut path = root.path.clone();
+            for component in relative_path.components() {
+                path.push(component);
+                ui.label("›");
+                if ui.selectable_label(path == self.view_path, component.as_os_str().to_string_lossy()).clicked() {
+                    actions.push(TreeAction::Enter(path.clone()));
+                }
+            }
+        });
+    }
+
+    /// 文件名、扩展名过滤框和文件、文件夹切换
+    fn show_filter(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.label("过滤:");
+            let filter_input = egui::TextEdit::singleline(&mut self.filter.pattern)
+                .hint_text("文件名或扩展名，如 *.log report");
+            ui.add(filter_input);
+            ui.selectable_value(&mut self.filter.kind, EntryKindFilter::All, "全部");
+            ui.selectable_value(&mut self.filter.kind, EntryKindFilter::FilesOnly, "仅文件");
+            ui.selectable_value(&mut self.filter.kind, EntryKindFilter::DirsOnly, "仅文件夹");
+            if !self.filter.is_empty() && ui.button("清除").clicked() {
+                self.filter = FileFilter::default();
+            }
+        });
+    }
+
+    /// 可点击排序的列头，当前排序列显示方向
+    fn show_header(&self, ui: &mut Ui, available_width: f32, row_height: f32, actions: &mut Vec<TreeAction>) {
+        ui.horizontal(|ui| {
+            for (column, title, width) in COLUMNS {
+                let title = if self.sort.column != column {
+                    title.to_string()
+                } else if self.sort.descending {
+                    format!("{title} ⏷")
+                } else {
+                    format!("{title} ⏶")
+                };
+                let header_button = egui::Button::new(title).frame(false);
+                if ui.add_sized(egui::vec2(available_width * width, row_height), header_button).clicked() {
+                    actions.push(TreeAction::Sort(column));
+                }
+                ui.add(Separator::default().spacing(0.0));
+            }
+            ui.add_sized(egui::vec2(available_width * PERCENTAGE_COLUMN_WIDTH, row_height), Label::new("占上级比例"));
+        });
+        ui.add(Separator::default().spacing(0.0));
+    }
+
+    /// 按展开状态把树拍平成行，每层按当前排序方式排列，indices 为 node 的子节点下标路径
+    fn collect_rows(&self, node: &FileNode, indices: &mut Vec<usize>, rows: &mut Vec<CachedRow>) {
+        let mut children: Vec<(usize, &FileNode)> = node.children.iter().enumerate().collect();
+        children.sort_by(|(_, a), (_, b)| self.sort.compare_nodes(a, b));
+        for (index, child) in children {
+            indices.push(index);
+            rows.push(CachedRow {
+                indices: indices.clone(),
+                depth: indices.len() - 1,
+            });
+            if !child.is_file && self.expanded.contains(&child.path) {
+                self.collect_rows(child, indices, rows);
+            }
+            indices.pop();
+        }
+    }
+
+    /// 递归查找所有匹配过滤条件的节点
+    fn collect_matched_rows<'a>(&self, node: &'a FileNode, indices: &mut Vec<usize>, rows: &mut Vec<(&'a FileNode, CachedRow)>) {
+        for (index, child) in node.children.iter().enumerate() {
+            indices.push(index);
+            if self.filter.matches_node(child) {
+                rows.push((child, CachedRow {
+                    indices: indices.clone(),
+                    depth: 0,
+                }));
+            }
+            if !child.is_file {
+                self.collect_matched_rows(child, indices, rows);
+            }
+            indices.pop();
+        }
+    }
+
+    fn show_row(&self, ui: &mut Ui, row: &TreeRow, available_width: f32, row_height: f32, actions: &mut Vec<TreeAction>) {
+        let node = row.node;
+        //整行响应右键，先注册，行内的按钮、文件名在上层优先响应
+        let row_rect = egui::Rect::from_min_size(ui.cursor().min, egui::vec2(available_width, row_height));
+        let row_response = ui.interact(row_rect, ui.id().with(&node.path), Sense::click());
+        let name_response = ui.horizontal(|ui| {
+            let name_width = available_width * COLUMNS[0].2;
+            let name_response = ui.allocate_ui(egui::vec2(name_width, row_height), |ui| {
+                ui.set_width(name_width);
+                ui.add_space(row.depth as f32 * INDENT_WIDTH);
+                if node.is_file {
+                    ui.add_space(INDENT_WIDTH + ui.spacing().item_spacing.x);
+                } else {
+                    let expanded = self.expanded.contains(&node.path);
+                    let arrow = if expanded { "⏷" } else { "⏵" };
+                    if ui.add(egui::Button::new(arrow).small().frame(false)).clicked() {
+                        actions.push(TreeAction::ToggleExpand(node.path.clone()));
+                    }
+                }
+                let name = if node.is_file { node.name.clone() } else { format!("📁 {}", node.name) };
+                let response = ui.add(Label::new(name).truncate().sense(Sense::click()));
+                if !node.is_file && response.double_clicked() {
+                    actions.push(TreeAction::Enter(node.path.clone()));
+                }
+                if node.is_file { response } else { response.on_hover_text("双击进入文件夹") }
+            }).inner;
+            ui.add(Separator::default().spacing(0.0));
+            let file_path_column = Label::new(node.path.to_string_lossy()).truncate();
+            ui.add_sized(egui::vec2(available_width * COLUMNS[1].2, row_height), file_path_column);
+            ui.add(Separator::default().spacing(0.0));
+            let file_type_column = Label::new(file_type_label(&node.name, node.is_file)).truncate();
+            ui.add_sized(egui::vec2(available_width * COLUMNS[2].2, row_height), file_type_column);
+            ui.add(Separator::default().spacing(0.0));
+            let file_size_column = Label::new(human_readable_size(node.size));
+            ui.add_sized(egui::vec2(available_width * COLUMNS[3].2, row_height), file_size_column);
+            ui.add(Separator::default().spacing(0.0));
+            let allocated_size_column = Label::new(human_readable_size(node.allocated_size));
+            ui.add_sized(egui::vec2(available_width * COLUMNS[4].2, row_height), allocated_size_column);
+            ui.add(Separator::default().spacing(0.0));
+            let update_time_column = Label::new(format_time(node.modified));
+            ui.add_sized(egui::vec2(available_width * COLUMNS[5].2, row_height), update_time_column);
+            ui.add(Separator::default().spacing(0.0));
+            let percentage_size = egui::vec2(available_width * PERCENTAGE_COLUMN_WIDTH, row_height);
+            show_percentage_bar(ui, node.size, row.parent_size, percentage_size);
+            name_response
+        }).inner;
+        for response in [row_response, name_response] {
+            response.context_menu(|ui| show_context_menu(ui, node, actions));
+        }
+    }
+}
+
+/// 根据缓存的下标找到节点和它的父节点，文件树已变化、下标失效时返回 None
+fn resolve_row<'a>(view_node: &'a FileNode, row: &CachedRow) -> Option<TreeRow<'a>> {
+    let mut parent = view_node;
+    let (last, ancestors) = row.indices.split_last()?;
+    for index in ancestors {
+        parent = parent.children.get(*index)?;
+    }
+    Some(TreeRow {
+        node: parent.children.get(*last)?,
+        depth: row.depth,
+        parent_size: parent.size,
+    })
+}
+
+/// 行的右键菜单
+fn show_context_menu(ui: &mut Ui, node: &FileNode, actions: &mut Vec<TreeAction>) {
+    let mut clicked = |ui: &mut Ui, response: Response, action: TreeAction| {
+        if response.clicked() {
+            actions.push(action);
+            ui.close_menu();
+        }
+    };
+    let button = ui.button("📂 在文件管理器中显示");
+    clicked(ui, button, TreeAction::Reveal(node.path.clone()));
+    let button = ui.button("↗ 使用默认程序打开");
+    clicked(ui, button, TreeAction::Open(node.path.clone()));
+    ui.separator();
+    let button = ui.button("复制路径");
+    clicked(ui, button, TreeAction::Copy(node.path.to_string_lossy().to_string()));
+    let button = ui.button("复制名称");
+    clicked(ui, button, TreeAction::Copy(node.name.clone()));
+    ui.separator();
+    let button = ui.button("✏ 重命名...");
+    clicked(ui, button, TreeAction::File(FileRequest::Rename(node.path.clone())));
+    let button = ui.add_enabled(node.is_file, egui::Button::new("# 计算哈希"));
+    clicked(ui, button, TreeAction::File(FileRequest::Hash(node.path.clone())));
+    ui.separator();
+    let button = ui.button("🗑 移到回收站...");
+    clicked(ui, button, TreeAction::File(FileRequest::Trash(vec![node.path.clone()])));
+}
+
+/// 占父节点大小比例的进度条
+fn show_percentage_bar(ui: &mut Ui, size: u64, parent_size: u64, bar_size: egui::Vec2) {
+    let fraction = if parent_size == 0 { 0.0 } else { size as f32 / parent_size as f32 };
+    let (rect, _) = ui.allocate_exact_size(bar_size, Sense::hover());
+    let painter = ui.painter();
+    let bar_rect = rect.shrink2(egui::vec2(0.0, 3.0));
+    painter.rect_filled(bar_rect, 2.0, ui.visuals().extreme_bg_color);
+    let mut filled_rect = bar_rect;
+    filled_rect.set_width(bar_rect.width() * fraction);
+    painter.rect_filled(filled_rect, 2.0, Color32::from_rgb(80, 150, 230));
+    painter.text(
+        bar_rect.center(),
+        egui::Align2::CENTER_CENTER,
+        format!("{:.1}%", fraction * 100.0),
+        egui::TextStyle::Small.resolve(ui.style()),
+        ui.visuals().text_color(),
+    );
+}
+
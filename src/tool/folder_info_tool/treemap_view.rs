@@ -0,0 +1,149 @@
+use std::path::{Path, PathBuf};
+
+use egui::{Align2, Color32, FontId, Pos2, Rect, Sense, Stroke, Ui};
+
+use crate::service::folder_scan::FileNode;
+use crate::service::treemap::{squarify, LayoutRect};
+use crate::util::file_util::human_readable_size;
+
+/// 最多向下绘制的层数
+const MAX_DEPTH: usize = 3;
+/// 小于该尺寸的矩形不再绘制子节点
+const MIN_NESTED_SIZE: f32 = 40.0;
+/// 小于该尺寸的矩形不绘制
+const MIN_RECT_SIZE: f32 = 2.0;
+/// 文件夹标题栏高度
+const HEADER_HEIGHT: f32 = 14.0;
+
+/// 已绘制的矩形，用于查找鼠标所在的节点
+struct PaintedNode<'a> {
+    rect: Rect,
+    node: &'a FileNode,
+}
+
+/// 矩形树图，面积与文件大小成正比，点击文件夹放大
+///
+/// 返回被点击、需要放大的文件夹路径
+pub fn show_treemap(ui: &mut Ui, view_node: &FileNode) -> Option<PathBuf> {
+    let (rect, response) = ui.allocate_exact_size(ui.available_size(), Sense::click());
+    let mut painted = vec![];
+    paint_children(ui, view_node, rect, 0, &mut painted);
+    show_legend(ui, rect);
+
+    //子节点在父节点之后绘制，从后往前找到最深的节点
+    let pointer_pos = response.hover_pos()?;
+    let hovered = painted.iter().rev().find(|painted_node| painted_node.rect.contains(pointer_pos))?;
+    let node = hovered.node;
+    ui.painter().rect_stroke(hovered.rect, 0.0, Stroke::new(2.0, ui.visuals().strong_text_color()));
+    let clicked = response.clicked();
+    response.on_hover_ui_at_pointer(|ui| {
+        ui.label(node.path.to_string_lossy());
+        ui.label(human_readable_size(node.size));
+        if !node.is_file {
+            ui.label(format!("{} 项，点击放大", node.children.len()));
+        }
+    });
+    if !clicked {
+        return None;
+    }
+    if node.is_file {
+        //点击文件时放大到它所在的文件夹
+        return node.path.parent().map(Path::to_path_buf).filter(|parent| *parent != view_node.path);
+    }
+    Some(node.path.clone())
+}
+
+fn paint_children<'a>(ui: &Ui, node: &'a FileNode, rect: Rect, depth: usize, painted: &mut Vec<PaintedNode<'a>>) {
+    let sizes: Vec<u64> = node.children.iter().map(|child| child.size).collect();
+    let bounds = LayoutRect::new(rect.min.x, rect.min.y, rect.width(), rect.height());
+    for (child, layout) in node.children.iter().zip(squarify(&sizes, bounds)) {
+        let child_rect = Rect::from_min_size(Pos2::new(layout.x, layout.y), egui::vec2(layout.width, layout.height));
+        if child_rect.width() < MIN_RECT_SIZE || child_rect.height() < MIN_RECT_SIZE {
+            continue;
+        }
+        paint_node(ui, child, child_rect, depth, painted);
+    }
+}
+
+fn paint_node<'a>(ui: &Ui, node: &'a FileNode, rect: Rect, depth: usize, painted: &mut Vec<PaintedNode<'a>>) {
+    let painter = ui.painter();
+    let fill = if node.is_file { file_type_color(&node.name) } else { dir_color(depth) };
+    painter.rect_filled(rect, 0.0, fill);
+    painter.rect_stroke(rect, 0.0, Stroke::new(1.0, Color32::from_black_alpha(120)));
+    painted.push(PaintedNode { rect, node });
+
+    let font = FontId::proportional(11.0);
+    let can_nest = !node.is_file
+        && depth + 1 < MAX_DEPTH
+        && rect.width() >= MIN_NESTED_SIZE
+        && rect.height() >= MIN_NESTED_SIZE;
+    if can_nest {
+        //文件夹顶部显示名称，下方绘制子节点
+        let header_rect = Rect::from_min_size(rect.min, egui::vec2(rect.width(), HEADER_HEIGHT));
+        paint_label(ui, header_rect, &node.name, font);
+        let inner_rect = Rect::from_min_max(rect.min + egui::vec2(2.0, HEADER_HEIGHT), rect.max - egui::vec2(2.0, 2.0));
+        paint_children(ui, node, inner_rect, depth + 1, painted);
+    } else if rect.width() > 30.0 && rect.height() > 14.0 {
+        paint_label(ui, rect, &node.name, font);
+    }
+}
+
+/// 在矩形左上角绘制名称，超出部分裁剪
+fn paint_label(ui: &Ui, rect: Rect, text: &str, font: FontId) {
+    ui.painter().with_clip_rect(rect).text(
+        rect.min + egui::vec2(3.0, 1.0),
+        Align2::LEFT_TOP,
+        text,
+        font,
+        Color32::from_gray(20),
+    );
+}
+
+fn dir_color(depth: usize) -> Color32 {
+    let gray = 170u8.saturating_sub(depth as u8 * 20);
+    Color32::from_gray(gray)
+}
+
+/// 文件类型分类，决定矩形颜色
+const FILE_TYPE_COLORS: &[(&str, &[&str], Color32)] = &[
+    ("图片", &["png", "jpg", "jpeg", "gif", "bmp", "svg", "webp", "ico", "tif", "tiff"], Color32::from_rgb(120, 200, 120)),
+    ("音视频", &["mp4", "mkv", "avi", "mov", "flv", "mp3", "wav", "flac", "aac", "ogg"], Color32::from_rgb(230, 140, 200)),
+    ("压缩包", &["zip", "gz", "tgz", "tar", "rar", "7z", "xz", "bz2", "zst", "jar", "war"], Color32::from_rgb(230, 120, 100)),
+    ("代码", &["rs", "java", "kt", "js", "ts", "py", "go", "c", "cpp", "h", "cs", "html", "css", "vue", "sql", "sh"], Color32::from_rgb(110, 170, 240)),
+    ("文档", &["txt", "md", "pdf", "doc", "docx", "xls", "xlsx", "ppt", "pptx", "json", "xml", "yaml", "yml", "toml", "csv", "log"], Color32::from_rgb(240, 210, 110)),
+    ("程序", &["exe", "dll", "so", "dylib", "a", "lib", "o", "class", "rlib", "msi", "bin"], Color32::from_rgb(170, 130, 230)),
+];
+
+/// 其他文件的颜色
+const OTHER_FILE_COLOR: Color32 = Color32::from_rgb(150, 200, 210);
+
+fn file_type_color(file_name: &str) -> Color32 {
+    let Some((_, extension)) = file_name.rsplit_once('.') else {
+        return OTHER_FILE_COLOR;
+    };
+    let extension = extension.to_ascii_lowercase();
+    FILE_TYPE_COLORS.iter()
+        .find(|(_, extensions, _)| extensions.contains(&extension.as_str()))
+        .map_or(OTHER_FILE_COLOR, |(_, _, color)| *color)
+}
+
+/// 右下角的颜色图例
+fn show_legend(ui: &Ui, rect: Rect) {
+    let font = FontId::proportional(11.0);
+    let item_height = 14.0;
+    let items: Vec<(&str, Color32)> = FILE_TYPE_COLORS.iter()
+        .map(|(label, _, color)| (*label, *color))
+        .chain([("其他", OTHER_FILE_COLOR), ("文件夹", dir_color(0))])
+        .collect();
+    let legend_rect = Rect::from_min_size(
+        rect.right_bottom() - egui::vec2(80.0, items.len() as f32 * item_height + 8.0),
+        egui::vec2(76.0, items.len() as f32 * item_height + 4.0),
+    );
+    let painter = ui.painter();
+    painter.rect_filled(legend_rect, 3.0, ui.visuals().window_fill().gamma_multiply(0.9));
+    for (index, (label, color)) in items.iter().enumerate() {
+        let top_left = legend_rect.min + egui::vec2(4.0, 2.0 + index as f32 * item_height);
+        painter.rect_filled(Rect::from_min_size(top_left + egui::vec2(0.0, 2.0), egui::vec2(10.0, 10.0)), 1.0, *color);
+        painter.text(top_left + egui::vec2(14.0, 0.0), Align2::LEFT_TOP, label, font.clone(), ui.visuals().text_color());
+    }
+}
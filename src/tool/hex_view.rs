@@ -0,0 +1,34 @@
+use std::fs;
+
+use egui::{Context, RichText, Ui};
+use rfd::FileDialog;
+
+use crate::notification;
+use crate::service::hex_dump::{hex_line, line_count};
+
+/// 按偏移、hex、ASCII 三列显示二进制数据，只绘制可见的行
+pub fn show_hex_view(ui: &mut Ui, bytes: &[u8]) {
+    let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+    egui::ScrollArea::both().auto_shrink([false, false]).show_rows(ui, row_height, line_count(bytes.len()), |ui, row_range| {
+        for line in row_range {
+            let line = hex_line(bytes, line);
+            ui.horizontal(|ui| {
+                ui.label(RichText::new(line.offset).monospace().weak());
+                ui.label(RichText::new(line.hex).monospace());
+                ui.label(RichText::new(line.ascii).monospace());
+            });
+        }
+    });
+}
+
+/// 选择保存位置，把原始字节写入文件
+pub fn save_bytes_to_file(ctx: &Context, bytes: &[u8], file_name: &str) {
+    let Some(path) = FileDialog::new().set_file_name(file_name).save_file() else {
+        //未选择
+        return;
+    };
+    match fs::write(&path, bytes) {
+        Ok(()) => notification::success(ctx, format!("已保存到 {}", path.display())),
+        Err(e) => notification::error(ctx, format!("保存文件:{} 失败:{e}", path.display())),
+    }
+}
@@ -1,19 +1,31 @@
-use std::time::Duration;
+use std::io;
+use std::path::PathBuf;
 
-use eframe::emath::Align;
-use egui::{Context, Layout, TextEdit, Ui};
-use log::{debug, info};
+use egui::{Context, TextEdit, Ui};
+use log::{debug, error};
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
-use crate::tool::get_seconds_since_epoch;
+use crate::notification;
+use crate::service::json::{parse_json, to_json_string};
+use crate::tool::{Tool, ToolCategory};
+use crate::util::file_util::read_dropped_file;
 
 #[derive(PartialEq, Clone)]
 pub struct JsonFormatTool {
     pub json_str: String,
     pub formated_json_str: String,
-    pub error_msg: String,
-    pub error_start: u64,
     pub show_count: u64,
+    /// 上次解析的json字符串，输入未变化时不重复解析
+    parsed_json_str: Option<String>,
+    /// 解析结果，解析失败时为错误信息
+    json_data: Result<Value, String>,
+}
+
+/// 退出时保存的工具状态
+#[derive(Serialize, Deserialize)]
+struct JsonFormatState {
+    json_str: String,
 }
 
 impl JsonFormatTool {
@@ -21,77 +33,48 @@ impl JsonFormatTool {
         Self {
             json_str: "".to_string(),
             formated_json_str: "".to_string(),
-            error_msg: "".to_string(),
-            error_start: 1,
             show_count: 0,
+            parsed_json_str: None,
+            json_data: Ok(Value::Null),
         }
     }
 
-    pub fn show(&mut self, ctx: &egui::Context, tool_main_ui: &mut Ui, _frame: &mut eframe::Frame) {
-        self.show_count = self.show_count + 1;
-        debug!("DecompressCompressStrTool show:{}", self.show_count);
-        tool_main_ui.horizontal_top(|tool_main_ui| {
-            let available_width = tool_main_ui.available_width();
-            // 使用相同的尺寸配置两个 TextEdit 控件
-            let half_width = (available_width - 40.0) * 0.5;
-            let text_edit_size = egui::vec2(half_width, tool_main_ui.available_height());
-            //展示错误消息
-            self.show_error_msg(ctx);
-
-            egui::ScrollArea::vertical().id_source("JsonFormatTool json_str ScrollArea").max_height(tool_main_ui.available_height()).show(tool_main_ui, |tool_main_ui| {
-                tool_main_ui.add_sized(text_edit_size, TextEdit::multiline(&mut self.json_str).hint_text("输入json字符串"));
-            });
-
-            tool_main_ui.separator();
-
-            egui::ScrollArea::vertical().id_source("JsonFormatTool formated_json_str ScrollArea").max_height(tool_main_ui.available_height()).show(tool_main_ui, |tool_main_ui| {
-                let json_data: Result<serde_json::Value, serde_json::Error> = serde_json::from_str(&self.json_str);
-                if json_data.is_err() {
-                    //有错误时
-                    let error = json_data.unwrap_err();
-                    tool_main_ui.add_sized(text_edit_size, TextEdit::multiline(&mut format!("{}", error)).hint_text("输入json字符串"));
-                    return;
-                }
-
-                let json_data = json_data.unwrap();
-                tool_main_ui.vertical(|tool_main_ui| {
-                    tool_main_ui.horizontal_top(|tool_main_ui| {
-                        self.formated_copy(ctx, &json_data, tool_main_ui);
-                        self.compress_copy(ctx, &json_data, tool_main_ui);
-                    });
-                    self.show_json(tool_main_ui, &json_data, "root".to_string());
-                });
-            });
-        });
+    /// 输入变化后重新解析json
+    fn refresh_json_data(&mut self) {
+        if self.parsed_json_str.as_ref() == Some(&self.json_str) {
+            return;
+        }
+        self.json_data = parse_json(&self.json_str).map_err(|e| e.to_string());
+        self.parsed_json_str = Some(self.json_str.clone());
     }
 
-    fn compress_copy(&mut self, ctx: &Context, json_data: &Value, tool_main_ui: &mut Ui) {
+    fn compress_copy(&self, ctx: &Context, json_data: &Value, tool_main_ui: &mut Ui) {
         if !tool_main_ui.button("压缩复制").clicked() {
             return;
         }
-        let json_data = serde_json::to_string(&json_data);
-        if json_data.is_err() {
-            self.error_msg = format!("复制字符串失败:{}", json_data.unwrap_err());
-            self.error_start = get_seconds_since_epoch();
-            return;
+        match to_json_string(json_data, true) {
+            Ok(json_str) => {
+                ctx.copy_text(json_str);
+                notification::success(ctx, "已复制");
+            }
+            Err(e) => notification::error(ctx, format!("复制字符串失败:{e}")),
         }
-        ctx.copy_text(json_data.unwrap());
     }
 
-    fn formated_copy(&mut self, ctx: &Context, json_data: &Value, tool_main_ui: &mut Ui) {
+    fn formated_copy(&self, ctx: &Context, json_data: &Value, tool_main_ui: &mut Ui) {
         if !tool_main_ui.button("格式化复制").clicked() {
             return;
         }
-        let json_data = serde_json::to_string_pretty(&json_data);
-        if json_data.is_err() {
-            self.error_msg = format!("复制字符串失败:{}", json_data.unwrap_err());
-            self.error_start = get_seconds_since_epoch();
-            return;
+        match to_json_string(json_data, false) {
+            Ok(json_str) => {
+                ctx.copy_text(json_str);
+                notification::success(ctx, "已复制");
+            }
+            Err(e) => notification::error(ctx, format!("复制字符串失败:{e}")),
         }
-        ctx.copy_text(json_data.unwrap());
     }
 
-    fn show_json(&mut self, ui: &mut egui::Ui, data: &serde_json::Value, parent: String) {
+    fn show_json(&self, ui: &mut egui::Ui, data: &serde_json::Value, parent: String) {
         match data {
             serde_json::Value::Object(map) => {
                 for (key, value) in map {
@@ -152,53 +135,80 @@ impl JsonFormatTool {
             }
         }
     }
+}
+
+impl Tool for JsonFormatTool {
+    fn label(&self) -> &str {
+        "json格式化"
+    }
 
-    /// 暂时错误信息
-    fn show_error_msg(&mut self, ctx: &Context) {
-        if get_seconds_since_epoch() - self.error_start > 5 && !self.error_msg.is_empty() {
-            info!("关闭弹窗消息");
-            self.error_msg = "".to_string();  // 设置关闭行为
+    fn icon(&self) -> &str {
+        "{}"
+    }
+
+    fn category(&self) -> ToolCategory {
+        ToolCategory::Text
+    }
+
+    fn save(&self) -> Option<String> {
+        let state = JsonFormatState {
+            json_str: self.json_str.clone(),
+        };
+        serde_json::to_string(&state).ok()
+    }
+
+    fn restore(&mut self, state: &str) {
+        match serde_json::from_str::<JsonFormatState>(state) {
+            Ok(state) => self.json_str = state.json_str,
+            Err(e) => error!("恢复json格式化工具状态失败:{e}"),
         }
+    }
 
-        if self.error_msg.is_empty() {
-            return;
+    fn open_dropped(&mut self, ctx: &egui::Context, paths: &[PathBuf]) -> bool {
+        let Some(path) = paths.iter().find(|path| path.is_file()) else {
+            return false;
+        };
+        match read_dropped_file(path).and_then(|bytes| String::from_utf8(bytes).map_err(io::Error::other)) {
+            Ok(text) => self.json_str = text,
+            Err(e) => notification::error(ctx, format!("读取文件:{} 失败:{e}", path.display())),
         }
+        true
+    }
 
-        let screen_rect = ctx.available_rect(); // 获取可用屏幕区域
-        let window_size = egui::vec2(300.0, 200.0); // 指定窗口大小
-
-        // 计算居中位置
-        let position = egui::pos2(
-            screen_rect.center().x - window_size.x / 2.0,
-            screen_rect.center().y - window_size.y / 2.0,
-        );
-
-        //等一秒再刷新一次
-        ctx.request_repaint_after(Duration::from_secs(1));
-        egui::Window::new("")
-            .collapsible(false)
-            .fixed_pos(position) // 设置窗口位置
-            .fixed_size(window_size) // 设置窗口大小
-            .title_bar(false)  // 关闭默认标题栏，以便自定义
-            .show(ctx, |ui| {
-                // 自定义标题栏，包含关闭按钮
-                ui.horizontal(|ui| {
-                    ui.with_layout(Layout::left_to_right(Align::Max), |ui| {
-                        ui.add_space(10.0); // 左边距，调整以美观
-                    });
-                    ui.with_layout(Layout::centered_and_justified(egui::Direction::LeftToRight), |ui| {
-                        ui.label("错误");  // 标题内容，居中显示
-                    });
-                    ui.with_layout(Layout::right_to_left(Align::Max), |ui| {
-                        if ui.button("✖").clicked() {
-                            self.error_msg = "".to_string();  // 设置关闭行为
-                        }
+    fn show(&mut self, ctx: &egui::Context, tool_main_ui: &mut Ui, _frame: &mut eframe::Frame) {
+        self.show_count = self.show_count + 1;
+        debug!("DecompressCompressStrTool show:{}", self.show_count);
+        tool_main_ui.horizontal_top(|tool_main_ui| {
+            let available_width = tool_main_ui.available_width();
+            // 使用相同的尺寸配置两个 TextEdit 控件
+            let half_width = (available_width - 40.0) * 0.5;
+            let text_edit_size = egui::vec2(half_width, tool_main_ui.available_height());
+
+            egui::ScrollArea::vertical().id_source("JsonFormatTool json_str ScrollArea").max_height(tool_main_ui.available_height()).show(tool_main_ui, |tool_main_ui| {
+                tool_main_ui.add_sized(text_edit_size, TextEdit::multiline(&mut self.json_str).hint_text("输入json字符串"));
+            });
+
+            tool_main_ui.separator();
+
+            self.refresh_json_data();
+            egui::ScrollArea::vertical().id_source("JsonFormatTool formated_json_str ScrollArea").max_height(tool_main_ui.available_height()).show(tool_main_ui, |tool_main_ui| {
+                let json_data = match &self.json_data {
+                    Ok(json_data) => json_data,
+                    Err(error) => {
+                        //有错误时
+                        tool_main_ui.add_sized(text_edit_size, TextEdit::multiline(&mut error.as_str()).hint_text("输入json字符串"));
+                        return;
+                    }
+                };
+
+                tool_main_ui.vertical(|tool_main_ui| {
+                    tool_main_ui.horizontal_top(|tool_main_ui| {
+                        self.formated_copy(ctx, json_data, tool_main_ui);
+                        self.compress_copy(ctx, json_data, tool_main_ui);
                     });
+                    self.show_json(tool_main_ui, json_data, "root".to_string());
                 });
-
-                // 窗口的主体内容
-                ui.separator();
-                ui.label(&self.error_msg);
             });
+        });
     }
-}
\ No newline at end of file
+}
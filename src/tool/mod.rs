@@ -1,18 +1,79 @@
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::path::PathBuf;
+
+use egui::Ui;
 
 pub use decompress_compress_str_tool::DecompressCompressStrTool;
 pub use folder_info_tool::FolderInfoTool;
 pub use json_format_tool::JsonFormatTool;
+pub use pipeline_tool::PipelineTool;
+pub use registry::{ToolDescriptor, ToolRegistry};
 
 mod folder_info_tool;
 mod decompress_compress_str_tool;
+mod hex_view;
 mod json_format_tool;
+mod pipeline_tool;
+mod registry;
+
+/// 工具分类，选择工具时按分类分组展示
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum ToolCategory {
+    File,
+    Text,
+    Other,
+}
+
+impl ToolCategory {
+    // 获得枚举的所有值，按展示顺序排列
+    pub fn iter() -> Vec<ToolCategory> {
+        vec![ToolCategory::File, ToolCategory::Text, ToolCategory::Other]
+    }
+
+    // 将枚举转换为字符串表示，用于显示
+    pub fn as_label(&self) -> &str {
+        match self {
+            ToolCategory::File => "文件",
+            ToolCategory::Text => "文本",
+            ToolCategory::Other => "其他",
+        }
+    }
+}
+
+/// 复制工具实例，实现了 [`Clone`] 的工具自动获得该能力，用于复制标签
+pub trait ToolClone {
+    fn clone_tool(&self) -> Box<dyn Tool>;
+}
+
+impl<T: Tool + Clone + 'static> ToolClone for T {
+    fn clone_tool(&self) -> Box<dyn Tool> {
+        Box::new(self.clone())
+    }
+}
+
+/// 工具，每个工具实现该 trait 后注册到 [`ToolRegistry`] 即可在界面中使用
+pub trait Tool: ToolClone {
+    /// 工具名称，用于显示
+    fn label(&self) -> &str;
+
+    /// 工具图标，使用 emoji 字符
+    fn icon(&self) -> &str;
+
+    /// 工具分类
+    fn category(&self) -> ToolCategory;
+
+    /// 绘制工具界面
+    fn show(&mut self, ctx: &egui::Context, ui: &mut Ui, frame: &mut eframe::Frame);
+
+    /// 导出工具状态，返回 None 表示没有需要保存的状态
+    fn save(&self) -> Option<String> {
+        None
+    }
+
+    /// 通过 [`Tool::save`] 导出的状态恢复工具
+    fn restore(&mut self, _state: &str) {}
 
-/// 相对 1970-01-01 00:00:00 UTC 过了多少秒
-pub fn get_seconds_since_epoch() -> u64 {
-    let now = SystemTime::now();  // 获取当前系统时间
-    let since_the_epoch = now.duration_since(UNIX_EPOCH)
-        .expect("Time went backwards");  // 计算从UNIX纪元到现在的时间差
-    let seconds_since_epoch = since_the_epoch.as_secs();
-    seconds_since_epoch
-}
\ No newline at end of file
+    /// 处理拖入窗口的文件、文件夹，工具是当前标签时调用，返回 false 表示不支持
+    fn open_dropped(&mut self, _ctx: &egui::Context, _paths: &[PathBuf]) -> bool {
+        false
+    }
+}
@@ -0,0 +1,515 @@
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use egui::{Context, RichText, TextEdit, Ui};
+use log::error;
+use rfd::FileDialog;
+use serde::{Deserialize, Serialize};
+
+use crate::notification;
+use crate::service::charset::COMMON_CHARSETS;
+use crate::service::codec::{encode_bytes, CompressionCodec, TextEncoding};
+use crate::service::pipeline::{load_recipe, load_recipe_library, run_pipeline_in_background, save_recipe, save_recipe_library, HashAlgorithm, Operation, PipelineHandle, Recipe, RecipeError};
+use crate::tool::hex_view::{save_bytes_to_file, show_hex_view};
+use crate::tool::{Tool, ToolCategory};
+
+/// 预览中最多显示的字符数，完整内容可以复制
+const PREVIEW_CHARS: usize = 2000;
+/// 二进制数据预览的字节数
+const PREVIEW_BYTES: usize = 256;
+/// 已保存的配方，所有标签共用，不受“退出时保存工具内容”设置影响
+const RECIPE_LIBRARY_PATH: &str = "config/recipes.json";
+/// 输入、操作停止变化后多久开始执行
+const RUN_DELAY: Duration = Duration::from_millis(300);
+
+/// 组合多个编码、压缩、哈希等操作依次处理输入，实时显示每一步的结果，操作序列可以保存为配方
+pub struct PipelineTool {
+    input: String,
+    /// 输入每次修改时加一，不需要每帧复制输入来比较
+    input_revision: u64,
+    /// 当前编辑的配方
+    recipe: Recipe,
+    /// 最近一次看到的输入版本和操作，变化时重新计时
+    seen: Option<RunKey>,
+    edited_at: Instant,
+    /// 结果对应的输入版本和操作
+    computed: Option<RunKey>,
+    /// 后台执行中的流水线
+    running: Option<(RunKey, PipelineHandle)>,
+    /// 每一步的输出，出错时为错误信息
+    results: Vec<Result<Vec<u8>, String>>,
+    /// 最后一步输出的文本，二进制数据或出错时为 None
+    output_text: Option<String>,
+}
+
+/// 输入版本和操作，用来判断结果是否过期
+#[derive(Clone, PartialEq)]
+struct RunKey {
+    input_revision: u64,
+    operations: Vec<Operation>,
+}
+
+impl Clone for PipelineTool {
+    /// 执行句柄不能共享，复制时结果未完成的标签重新执行
+    fn clone(&self) -> Self {
+        Self {
+            input: self.input.clone(),
+            input_revision: self.input_revision,
+            recipe: self.recipe.clone(),
+            seen: self.seen.clone(),
+            edited_at: self.edited_at,
+            computed: self.computed.clone(),
+            running: None,
+            results: self.results.clone(),
+            output_text: self.output_text.clone(),
+        }
+    }
+}
+
+/// 退出时保存的工具状态
+#[derive(Serialize, Deserialize)]
+struct PipelineState {
+    input: String,
+    recipe: Recipe,
+}
+
+/// 界面中对操作列表的修改，绘制完成后再应用
+enum StepAction {
+    MoveUp(usize),
+    MoveDown(usize),
+    Remove(usize),
+}
+
+impl PipelineTool {
+    pub fn new() -> Self {
+        Self {
+            input: "".to_string(),
+            input_revision: 0,
+            recipe: Recipe::default(),
+            seen: None,
+            edited_at: Instant::now(),
+            computed: None,
+            running: None,
+            results: vec![],
+            output_text: None,
+        }
+    }
+
+    fn is_current(&self, key: &Option<RunKey>) -> bool {
+        key.as_ref().is_some_and(|key| key.input_revision == self.input_revision && key.operations == self.recipe.operations)
+    }
+
+    fn current_key(&self) -> RunKey {
+        RunKey {
+            input_revision: self.input_revision,
+            operations: self.recipe.operations.clone(),
+        }
+    }
+
+    /// 结果是否还没有跟上输入和操作
+    fn is_pending(&self) -> bool {
+        self.running.is_some() || !self.is_current(&self.computed)
+    }
+
+    /// 输入或操作停止变化一段时间后在后台重新执行，同时只执行一个
+    fn refresh(&mut self, ctx: &Context) {
+        self.poll_running();
+        if !self.is_current(&self.seen) {
+            self.seen = Some(self.current_key());
+            self.edited_at = Instant::now();
+        }
+        if self.running.is_some() || self.is_current(&self.computed) {
+            return;
+        }
+        let elapsed = self.edited_at.elapsed();
+        if elapsed < RUN_DELAY {
+            ctx.request_repaint_after(RUN_DELAY - elapsed);
+            return;
+        }
+        let key = self.current_key();
+        let repaint_ctx = ctx.clone();
+        let handle = run_pipeline_in_background(self.input.as_bytes().to_vec(), key.operations.clone(), move || repaint_ctx.request_repaint());
+        self.running = Some((key, handle));
+    }
+
+    fn poll_running(&mut self) {
+        let Some(results) = self.running.as_ref().and_then(|(_, handle)| handle.poll_result()) else {
+            return;
+        };
+        self.computed = self.running.take().map(|(key, _)| key);
+        self.results = results.into_iter()
+            .map(|result| result.map_err(|e| e.to_string()))
+            .collect();
+        self.output_text = self.output().ok()
+            .and_then(|bytes| std::str::from_utf8(bytes).ok())
+            .map(str::to_string);
+    }
+
+    /// 最后一步的输出，没有操作时为输入
+    fn output(&self) -> Result<&[u8], &str> {
+        match self.results.last() {
+            Some(Ok(bytes)) => Ok(bytes),
+            Some(Err(e)) => Err(e),
+            None => Ok(self.input.as_bytes()),
+        }
+    }
+
+    /// 配方名称、保存、打开、导入导出
+    fn show_recipe_bar(&mut self, ctx: &Context, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.label("配方:");
+            ui.add(TextEdit::singleline(&mut self.recipe.name).hint_text("配方名称").desired_width(160.0));
+            if ui.button("💾 保存").on_hover_text("同名配方会被覆盖").clicked() {
+                self.save_current_recipe(ctx);
+            }
+            self.show_saved_recipes_menu(ctx, ui);
+            if ui.button("🆕 新建").clicked() {
+                self.recipe = Recipe::default();
+            }
+            ui.separator();
+            if ui.button("导入…").clicked() {
+                self.import_recipe(ctx);
+            }
+            if ui.button("导出…").clicked() {
+                self.export_recipe(ctx);
+            }
+        });
+    }
+
+    fn save_current_recipe(&mut self, ctx: &Context) {
+        let name = self.recipe.name.trim().to_string();
+        if name.is_empty() {
+            notification::warning(ctx, "请输入配方名称");
+            return;
+        }
+        self.recipe.name = name.clone();
+        let result = update_recipe_library(ctx, |recipes| match recipes.iter_mut().find(|recipe| recipe.name == name) {
+            Some(saved) => *saved = self.recipe.clone(),
+            None => recipes.push(self.recipe.clone()),
+        });
+        match result {
+            Ok(()) => notification::success(ctx, format!("已保存配方:{name}")),
+            Err(e) => notification::error(ctx, e.to_string()),
+        }
+    }
+
+    fn show_saved_recipes_menu(&mut self, ctx: &Context, ui: &mut Ui) {
+        ui.menu_button("📂 打开", |ui| {
+            let recipes = recipe_library(ctx);
+            if recipes.is_empty() {
+                ui.label("还没有保存的配方");
+                return;
+            }
+            let mut removed = None;
+            for recipe in recipes {
+                ui.horizontal(|ui| {
+                    let text = format!("{} ({} 步)", recipe.name, recipe.operations.len());
+                    if ui.button(text).clicked() {
+                        self.recipe = recipe.clone();
+                        ui.close_menu();
+                    }
+                    if ui.small_button("🗑").on_hover_text("删除配方").clicked() {
+                        removed = Some(recipe.name);
+                    }
+                });
+            }
+            if let Some(name) = removed {
+                match update_recipe_library(ctx, |recipes| recipes.retain(|recipe| recipe.name != name)) {
+                    Ok(()) => notification::info(ctx, format!("已删除配方:{name}")),
+                    Err(e) => notification::error(ctx, e.to_string()),
+                }
+            }
+        });
+    }
+
+    fn import_recipe(&mut self, ctx: &Context) {
+        let Some(path) = FileDialog::new().add_filter("配方", &["json"]).pick_file() else {
+            //未选择
+            return;
+        };
+        match load_recipe(&path) {
+            Ok(recipe) => self.recipe = recipe,
+            Err(e) => notification::error(ctx, e.to_string()),
+        }
+    }
+
+    fn export_recipe(&self, ctx: &Context) {
+        let name = if self.recipe.name.trim().is_empty() { "recipe" } else { self.recipe.name.trim() };
+        let Some(path) = FileDialog::new()
+            .add_filter("配方", &["json"])
+            .set_file_name(format!("{name}.recipe.json"))
+            .save_file() else {
+            //未选择
+            return;
+        };
+        match save_recipe(&path, &self.recipe) {
+            Ok(()) => notification::success(ctx, format!("已导出配方到 {}", path.display())),
+            Err(e) => notification::error(ctx, e.to_string()),
+        }
+    }
+
+    /// 输入和每一步的参数、结果预览
+    fn show_steps(&mut self, ui: &mut Ui) {
+        ui.label("输入");
+        if ui.add(TextEdit::multiline(&mut self.input).hint_text("输入要处理的文本").desired_width(f32::INFINITY).desired_rows(6)).changed() {
+            self.input_revision += 1;
+        }
+        ui.menu_button("➕ 添加操作", |ui| {
+            for operation in Operation::templates() {
+                if ui.button(operation.as_label()).clicked() {
+                    self.recipe.operations.push(operation);
+                    ui.close_menu();
+                }
+            }
+        });
+        let step_count = self.recipe.operations.len();
+        //操作变化后结果可能对应其他步骤，重新执行完成前不显示
+        let results_current = self.is_current(&self.computed);
+        let mut actions = vec![];
+        for (index, operation) in self.recipe.operations.iter_mut().enumerate() {
+            ui.push_id(index, |ui| {
+                egui::Frame::group(ui.style()).show(ui, |ui| {
+                    ui.set_width(ui.available_width());
+                    ui.horizontal(|ui| {
+                        ui.strong(format!("{}. {}", index + 1, operation.as_label()));
+                        show_operation_params(ui, operation);
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            if ui.small_button("✖").on_hover_text("删除").clicked() {
+                                actions.push(StepAction::Remove(index));
+                            }
+                            if ui.add_enabled(index + 1 < step_count, egui::Button::new("⬇").small()).clicked() {
+                                actions.push(StepAction::MoveDown(index));
+                            }
+                            if ui.add_enabled(index > 0, egui::Button::new("⬆").small()).clicked() {
+                                actions.push(StepAction::MoveUp(index));
+                            }
+                        });
+                    });
+                    if !results_current {
+                        ui.horizontal(|ui| {
+                            ui.spinner();
+                            ui.weak("正在执行…");
+                        });
+                        return;
+                    }
+                    match self.results.get(index) {
+                        Some(Ok(bytes)) => {
+                            egui::CollapsingHeader::new(format!("结果 {} 字节", bytes.len())).show(ui, |ui| {
+                                ui.label(RichText::new(preview(bytes)).monospace());
+                            });
+                        }
+                        Some(Err(e)) => {
+                            ui.label(RichText::new(e).color(ui.visuals().error_fg_color));
+                        }
+                        //前面的步骤出错，未执行
+                        None => {
+                            ui.weak("未执行");
+                        }
+                    }
+                });
+            });
+        }
+        for action in actions {
+            let operations = &mut self.recipe.operations;
+            match action {
+                StepAction::MoveUp(index) => operations.swap(index - 1, index),
+                StepAction::MoveDown(index) => operations.swap(index, index + 1),
+                StepAction::Remove(index) => {
+                    operations.remove(index);
+                }
+            }
+        }
+    }
+
+    fn show_output(&self, ctx: &Context, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.label("输出");
+            if self.is_pending() {
+                ui.spinner();
+            }
+            let output = self.output();
+            if ui.add_enabled(output.is_ok(), egui::Button::new("复制")).on_hover_text("二进制数据复制为 hex").clicked() {
+                if let Ok(bytes) = output {
+                    ctx.copy_text(to_copy_text(bytes));
+                    notification::success(ctx, "已复制");
+                }
+            }
+            if ui.add_enabled(output.is_ok(), egui::Button::new("💾 保存到文件…")).clicked() {
+                if let Ok(bytes) = output {
+                    save_bytes_to_file(ctx, bytes, "output.bin");
+                }
+            }
+        });
+        match (self.output(), &self.output_text) {
+            (Ok(_), Some(text)) => {
+                //只读，不复制文本
+                let mut text = text.as_str();
+                egui::ScrollArea::vertical().id_source("PipelineTool output ScrollArea").show(ui, |ui| {
+                    ui.add(TextEdit::multiline(&mut text).desired_width(f32::INFINITY).code_editor());
+                });
+            }
+            (Ok(bytes), None) => {
+                ui.label(format!("二进制数据 {} 字节", bytes.len()));
+                show_hex_view(ui, bytes);
+            }
+            (Err(e), _) => {
+                ui.label(RichText::new(e).color(ui.visuals().error_fg_color));
+            }
+        }
+    }
+}
+
+/// 已保存的配方，第一次使用时从文件加载，之后所有标签共用内存中的列表
+fn recipe_library(ctx: &Context) -> Vec<Recipe> {
+    let id = egui::Id::new(RECIPE_LIBRARY_PATH);
+    if let Some(recipes) = ctx.data(|data| data.get_temp::<Vec<Recipe>>(id)) {
+        return recipes;
+    }
+    let recipes = load_recipe_library(Path::new(RECIPE_LIBRARY_PATH)).unwrap_or_else(|e| {
+        error!("{e}");
+        vec![]
+    });
+    ctx.data_mut(|data| data.insert_temp(id, recipes.clone()));
+    recipes
+}
+
+/// 修改已保存的配方并写入文件，写入失败时不修改
+fn update_recipe_library(ctx: &Context, update: impl FnOnce(&mut Vec<Recipe>)) -> Result<(), RecipeError> {
+    let mut recipes = recipe_library(ctx);
+    update(&mut recipes);
+    save_recipe_library(Path::new(RECIPE_LIBRARY_PATH), &recipes)?;
+    ctx.data_mut(|data| data.insert_temp(egui::Id::new(RECIPE_LIBRARY_PATH), recipes));
+    Ok(())
+}
+
+/// 操作的参数
+fn show_operation_params(ui: &mut Ui, operation: &mut Operation) {
+    match operation {
+        Operation::Decode(encoding) | Operation::Encode(encoding) => {
+            egui::ComboBox::from_id_source("encoding")
+                .selected_text(encoding.as_label())
+                .show_ui(ui, |ui| {
+                    for item in TextEncoding::iter() {
+                        ui.selectable_value(encoding, item, item.as_label());
+                    }
+                });
+        }
+        Operation::Decompress(codec) => {
+            show_codec_combo(ui, codec);
+        }
+        Operation::Compress { codec, level } => {
+            let old_codec = *codec;
+            show_codec_combo(ui, codec);
+            if *codec != old_codec {
+                *level = codec.default_level();
+            }
+            if let Some(range) = codec.level_range() {
+                ui.label("级别:");
+                ui.add(egui::DragValue::new(level).range(range));
+            }
+        }
+        Operation::Hash(algorithm) => {
+            egui::ComboBox::from_id_source("hash")
+                .selected_text(algorithm.as_label())
+                .show_ui(ui, |ui| {
+                    for item in HashAlgorithm::iter() {
+                        ui.selectable_value(algorithm, item, item.as_label());
+                    }
+                });
+        }
+        Operation::JsonFormat { compact } => {
+            ui.checkbox(compact, "压缩为单行");
+        }
+        Operation::DecodeCharset(charset) | Operation::EncodeCharset(charset) => {
+            egui::ComboBox::from_id_source("charset")
+                .selected_text(charset.as_str())
+                .show_ui(ui, |ui| {
+                    for item in COMMON_CHARSETS {
+                        if ui.selectable_label(charset.eq_ignore_ascii_case(item), item).clicked() {
+                            *charset = item.to_string();
+                        }
+                    }
+                });
+        }
+        Operation::UrlDecode | Operation::UrlEncode | Operation::AutoDecode => {}
+    }
+}
+
+fn show_codec_combo(ui: &mut Ui, codec: &mut CompressionCodec) {
+    egui::ComboBox::from_id_source("codec")
+        .selected_text(codec.as_label())
+        .show_ui(ui, |ui| {
+            for item in CompressionCodec::iter() {
+                ui.selectable_value(codec, item, item.as_label());
+            }
+        });
+}
+
+/// 文本直接返回，二进制数据转为 hex
+fn to_copy_text(bytes: &[u8]) -> String {
+    match std::str::from_utf8(bytes) {
+        Ok(text) => text.to_string(),
+        Err(_) => encode_bytes(bytes, TextEncoding::Hex),
+    }
+}
+
+/// 截断后的预览，二进制数据显示开头部分的 hex
+fn preview(bytes: &[u8]) -> String {
+    match std::str::from_utf8(bytes) {
+        Ok(text) => match text.char_indices().nth(PREVIEW_CHARS) {
+            Some((end, _)) => format!("{}…", &text[..end]),
+            None => text.to_string(),
+        },
+        Err(_) => {
+            let shown = &bytes[..bytes.len().min(PREVIEW_BYTES)];
+            let hex: Vec<String> = shown.iter().map(|byte| format!("{byte:02x}")).collect();
+            let more = if shown.len() < bytes.len() { " …" } else { "" };
+            format!("二进制数据: {}{more}", hex.join(" "))
+        }
+    }
+}
+
+impl Tool for PipelineTool {
+    fn label(&self) -> &str {
+        "数据处理流水线"
+    }
+
+    fn icon(&self) -> &str {
+        "⛓"
+    }
+
+    fn category(&self) -> ToolCategory {
+        ToolCategory::Text
+    }
+
+    fn save(&self) -> Option<String> {
+        let state = PipelineState {
+            input: self.input.clone(),
+            recipe: self.recipe.clone(),
+        };
+        serde_json::to_string(&state).ok()
+    }
+
+    fn restore(&mut self, state: &str) {
+        match serde_json::from_str::<PipelineState>(state) {
+            Ok(state) => {
+                self.input = state.input;
+                self.input_revision += 1;
+                self.recipe = state.recipe;
+            }
+            Err(e) => error!("恢复数据处理流水线工具状态失败:{e}"),
+        }
+    }
+
+    fn show(&mut self, ctx: &egui::Context, ui: &mut Ui, _frame: &mut eframe::Frame) {
+        self.refresh(ctx);
+        self.show_recipe_bar(ctx, ui);
+        ui.separator();
+        ui.columns(2, |columns| {
+            egui::ScrollArea::vertical().id_source("PipelineTool steps ScrollArea").show(&mut columns[0], |ui| {
+                self.show_steps(ui);
+            });
+            self.show_output(ctx, &mut columns[1]);
+        });
+    }
+}
@@ -0,0 +1,91 @@
+use log::warn;
+
+use crate::tool::{DecompressCompressStrTool, FolderInfoTool, JsonFormatTool, PipelineTool, Tool, ToolCategory};
+
+/// 已注册工具的描述信息，界面通过它展示可选工具并创建工具实例
+pub struct ToolDescriptor {
+    /// 工具唯一标识
+    pub id: String,
+    pub label: String,
+    pub icon: String,
+    pub category: ToolCategory,
+    factory: Box<dyn Fn() -> Box<dyn Tool>>,
+}
+
+impl ToolDescriptor {
+    /// 创建一个新的工具实例
+    pub fn create(&self) -> Box<dyn Tool> {
+        (self.factory)()
+    }
+
+    /// 带图标的显示名称
+    pub fn display_label(&self) -> String {
+        format!("{} {}", self.icon, self.label)
+    }
+}
+
+/// 工具注册表，[`crate::AppInstance`] 从这里获取所有可用工具
+///
+/// 自定义工具只需要实现 [`Tool`] 并注册，不需要修改界面分发逻辑：
+/// ```ignore
+/// let mut registry = ToolRegistry::with_builtin_tools();
+/// registry.register("my_tool", MyTool::new);
+/// ```
+#[derive(Default)]
+pub struct ToolRegistry {
+    descriptors: Vec<ToolDescriptor>,
+}
+
+impl ToolRegistry {
+    pub fn new() -> Self {
+        Self {
+            descriptors: vec![],
+        }
+    }
+
+    /// 包含所有内置工具的注册表
+    pub fn with_builtin_tools() -> Self {
+        let mut registry = Self::new();
+        registry.register("folder_info", FolderInfoTool::new);
+        registry.register("decompress_compress_str", DecompressCompressStrTool::new);
+        registry.register("json_format", JsonFormatTool::new);
+        registry.register("pipeline", PipelineTool::new);
+        registry
+    }
+
+    /// 注册工具，id 重复时忽略后注册的工具
+    pub fn register<T, F>(&mut self, id: &str, factory: F)
+    where
+        T: Tool + 'static,
+        F: Fn() -> T + 'static,
+    {
+        if self.get(id).is_some() {
+            warn!("工具:{id} 已注册，忽略重复注册");
+            return;
+        }
+        //创建一个实例用于读取展示信息
+        let prototype = factory();
+        self.descriptors.push(ToolDescriptor {
+            id: id.to_string(),
+            label: prototype.label().to_string(),
+            icon: prototype.icon().to_string(),
+            category: prototype.category(),
+            factory: Box::new(move || Box::new(factory())),
+        });
+    }
+
+    /// 根据 id 获取工具
+    pub fn get(&self, id: &str) -> Option<&ToolDescriptor> {
+        self.descriptors.iter().find(|descriptor| descriptor.id == id)
+    }
+
+    /// 所有已注册的工具，按注册顺序排列
+    pub fn iter(&self) -> impl Iterator<Item=&ToolDescriptor> {
+        self.descriptors.iter()
+    }
+
+    /// 某个分类下的工具
+    pub fn by_category(&self, category: ToolCategory) -> impl Iterator<Item=&ToolDescriptor> {
+        self.descriptors.iter().filter(move |descriptor| descriptor.category == category)
+    }
+}
@@ -1,3 +1,10 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// 拖入文本工具的文件大小上限，在界面线程读取和显示过大的文件会卡住界面
+pub const MAX_DROPPED_FILE_SIZE: u64 = 16 * 1024 * 1024;
+
 /// 将字节转换为 人类识别友好
 pub fn human_readable_size(bytes: u64) -> String {
     let sizes = ["B", "KiB", "MiB", "GiB", "TiB", "PiB", "EiB"];
@@ -13,4 +20,28 @@ pub fn human_readable_size(bytes: u64) -> String {
     // 为了准确显示，将最终的size转换为浮点数，并重新计算得到近似的小数形式
     let float_size = bytes as f64 / (1 << (10 * exp)) as f64;
     format!("{:.2} {}", float_size, sizes[exp])
-}
\ No newline at end of file
+}
+
+/// 在系统文件管理器中打开文件所在的文件夹，path 为文件夹时直接打开
+pub fn open_in_file_manager(path: &Path) -> std::io::Result<()> {
+    let folder = if path.is_dir() { path } else { path.parent().unwrap_or(path) };
+    open::that_detached(folder)
+}
+
+/// 使用系统默认程序打开文件，文件夹在文件管理器中打开
+pub fn open_with_default_app(path: &Path) -> std::io::Result<()> {
+    open::that_detached(path)
+}
+
+/// 读取拖入文本工具的文件，超过 [`MAX_DROPPED_FILE_SIZE`] 时不读取并返回错误
+pub fn read_dropped_file(path: &Path) -> io::Result<Vec<u8>> {
+    let size = fs::metadata(path)?.len();
+    if size > MAX_DROPPED_FILE_SIZE {
+        return Err(io::Error::other(format!(
+            "文件大小 {} 超过上限 {}",
+            human_readable_size(size),
+            human_readable_size(MAX_DROPPED_FILE_SIZE)
+        )));
+    }
+    fs::read(path)
+}
@@ -0,0 +1,312 @@
+use egui::{Sense, Ui};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::tool::{Tool, ToolCategory, ToolRegistry};
+
+/// 工作区中打开的一个标签页
+pub struct ToolTab {
+    /// 标签唯一标识，同一个工具打开多次时用来区分界面状态
+    pub id: u64,
+    /// 工具在注册表中的 id
+    pub tool_id: String,
+    /// 标签标题
+    pub title: String,
+    /// 是否保存该标签的工具内容，处理敏感数据时可以单独关闭
+    pub persist: bool,
+    pub tool: Box<dyn Tool>,
+}
+
+/// 工作区保存的状态
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct WorkspaceState {
+    pub tabs: Vec<TabState>,
+    pub active: usize,
+}
+
+/// 标签保存的状态
+#[derive(Serialize, Deserialize)]
+pub struct TabState {
+    pub tool_id: String,
+    pub title: String,
+    #[serde(default = "default_persist")]
+    pub persist: bool,
+    /// [`Tool::save`] 导出的工具状态
+    #[serde(default)]
+    pub tool_state: Option<String>,
+}
+
+fn default_persist() -> bool {
+    true
+}
+
+/// 标签页上可执行的操作，绘制完标签栏后统一处理，避免遍历时修改标签列表
+enum TabAction {
+    Select(usize),
+    Close(usize),
+    CloseOthers(usize),
+    Duplicate(usize),
+    Move { from: usize, to: usize },
+    Open(String),
+}
+
+/// 多标签工作区，每个标签持有独立的工具实例
+#[derive(Default)]
+pub struct Workspace {
+    tabs: Vec<ToolTab>,
+    active: usize,
+    next_tab_id: u64,
+}
+
+impl Workspace {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tabs.is_empty()
+    }
+
+    /// 当前激活的标签
+    pub fn active_tab_mut(&mut self) -> Option<&mut ToolTab> {
+        self.tabs.get_mut(self.active)
+    }
+
+    /// 打开一个新的工具标签并激活
+    pub fn open(&mut self, registry: &ToolRegistry, tool_id: &str) {
+        let Some(descriptor) = registry.get(tool_id) else {
+            warn!("未注册的工具:{tool_id}");
+            return;
+        };
+        let title = self.unique_title(&descriptor.display_label());
+        self.push_tab(tool_id.to_string(), title, descriptor.create());
+    }
+
+    /// 复制标签，新标签带有原标签工具的当前状态
+    pub fn duplicate(&mut self, index: usize) {
+        let Some(tab) = self.tabs.get(index) else {
+            return;
+        };
+        let tool_id = tab.tool_id.clone();
+        let tool = tab.tool.clone_tool();
+        let title = self.unique_title(&format!("{} {}", tool.icon(), tool.label()));
+        self.push_tab(tool_id, title, tool);
+    }
+
+    /// 关闭标签
+    pub fn close(&mut self, index: usize) {
+        if index >= self.tabs.len() {
+            return;
+        }
+        let tab = self.tabs.remove(index);
+        info!("关闭标签:{}", tab.title);
+        if self.active > index || self.active >= self.tabs.len() {
+            self.active = self.active.saturating_sub(1);
+        }
+    }
+
+    /// 只保留指定的标签
+    pub fn close_others(&mut self, index: usize) {
+        if index >= self.tabs.len() {
+            return;
+        }
+        let tab = self.tabs.swap_remove(index);
+        self.tabs = vec![tab];
+        self.active = 0;
+    }
+
+    /// 移动标签位置
+    pub fn move_tab(&mut self, from: usize, to: usize) {
+        if from >= self.tabs.len() || to >= self.tabs.len() || from == to {
+            return;
+        }
+        let active_id = self.tabs[self.active].id;
+        let tab = self.tabs.remove(from);
+        self.tabs.insert(to, tab);
+        self.active = self.tabs.iter().position(|tab| tab.id == active_id).unwrap_or(0);
+    }
+
+    /// 导出工作区状态，persist_tool_state 为 false 时不保存工具内容
+    pub fn save_state(&self, persist_tool_state: bool) -> WorkspaceState {
+        let tabs = self.tabs.iter()
+            .map(|tab| TabState {
+                tool_id: tab.tool_id.clone(),
+                title: tab.title.clone(),
+                persist: tab.persist,
+                tool_state: if persist_tool_state && tab.persist { tab.tool.save() } else { None },
+            })
+            .collect();
+        WorkspaceState {
+            tabs,
+            active: self.active,
+        }
+    }
+
+    /// 从保存的状态恢复标签，已不存在的工具会被忽略
+    pub fn restore_state(&mut self, registry: &ToolRegistry, state: WorkspaceState) {
+        for tab_state in state.tabs {
+            let Some(descriptor) = registry.get(&tab_state.tool_id) else {
+                warn!("恢复标签失败，未注册的工具:{}", tab_state.tool_id);
+                continue;
+            };
+            let mut tool = descriptor.create();
+            if let Some(tool_state) = &tab_state.tool_state {
+                tool.restore(tool_state);
+            }
+            self.push_tab(tab_state.tool_id, tab_state.title, tool);
+            if let Some(tab) = self.tabs.last_mut() {
+                tab.persist = tab_state.persist;
+            }
+        }
+        self.active = state.active.min(self.tabs.len().saturating_sub(1));
+    }
+
+    /// 绘制标签栏，包括新建标签的菜单
+    pub fn show_tab_bar(&mut self, ui: &mut Ui, registry: &ToolRegistry) {
+        let mut actions = vec![];
+        ui.horizontal_wrapped(|ui| {
+            let tab_count = self.tabs.len();
+            for (index, tab) in self.tabs.iter_mut().enumerate() {
+                let response = ui.selectable_label(index == self.active, &tab.title)
+                    .interact(Sense::drag());
+                if response.clicked() {
+                    actions.push(TabAction::Select(index));
+                }
+                //拖动标签到其他标签上调整顺序
+                if response.drag_started() {
+                    response.dnd_set_drag_payload(index);
+                }
+                if let Some(from) = response.dnd_release_payload::<usize>() {
+                    actions.push(TabAction::Move { from: *from, to: index });
+                }
+                response.context_menu(|ui| {
+                    if ui.button("复制标签").clicked() {
+                        actions.push(TabAction::Duplicate(index));
+                        ui.close_menu();
+                    }
+                    if ui.add_enabled(index > 0, egui::Button::new("左移")).clicked() {
+                        actions.push(TabAction::Move { from: index, to: index - 1 });
+                        ui.close_menu();
+                    }
+                    if ui.add_enabled(index + 1 < tab_count, egui::Button::new("右移")).clicked() {
+                        actions.push(TabAction::Move { from: index, to: index + 1 });
+                        ui.close_menu();
+                    }
+                    ui.checkbox(&mut tab.persist, "退出时保存标签内容");
+                    ui.separator();
+                    if ui.button("关闭").clicked() {
+                        actions.push(TabAction::Close(index));
+                        ui.close_menu();
+                    }
+                    if ui.button("关闭其他标签").clicked() {
+                        actions.push(TabAction::CloseOthers(index));
+                        ui.close_menu();
+                    }
+                });
+                if ui.small_button("✖").on_hover_text("关闭标签").clicked() {
+                    actions.push(TabAction::Close(index));
+                }
+                ui.separator();
+            }
+            ui.menu_button("➕", |ui| {
+                if let Some(tool_id) = show_tool_menu(ui, registry) {
+                    actions.push(TabAction::Open(tool_id));
+                    ui.close_menu();
+                }
+            }).response.on_hover_text("打开新工具");
+        });
+
+        for action in actions {
+            match action {
+                TabAction::Select(index) => self.active = index,
+                TabAction::Close(index) => self.close(index),
+                TabAction::CloseOthers(index) => self.close_others(index),
+                TabAction::Duplicate(index) => self.duplicate(index),
+                TabAction::Move { from, to } => self.move_tab(from, to),
+                TabAction::Open(tool_id) => self.open(registry, &tool_id),
+            }
+        }
+    }
+
+    fn push_tab(&mut self, tool_id: String, title: String, tool: Box<dyn Tool>) {
+        info!("打开标签:{title}");
+        self.tabs.push(ToolTab {
+            id: self.next_tab_id,
+            tool_id,
+            title,
+            persist: true,
+            tool,
+        });
+        self.next_tab_id += 1;
+        self.active = self.tabs.len() - 1;
+    }
+
+    /// 同名标签追加序号，方便区分同一工具的多个实例，使用没有被占用的最小序号
+    fn unique_title(&self, title: &str) -> String {
+        let is_used = |candidate: &str| self.tabs.iter().any(|tab| tab.title == candidate);
+        if !is_used(title) {
+            return title.to_string();
+        }
+        (2..)
+            .map(|n| format!("{title} ({n})"))
+            .find(|candidate| !is_used(candidate))
+            .unwrap_or_else(|| title.to_string())
+    }
+}
+
+/// 按分类展示所有工具，返回被点击的工具 id
+pub fn show_tool_menu(ui: &mut Ui, registry: &ToolRegistry) -> Option<String> {
+    let mut selected_tool_id = None;
+    for category in ToolCategory::iter() {
+        let mut descriptors = registry.by_category(category).peekable();
+        if descriptors.peek().is_none() {
+            continue;
+        }
+        ui.label(egui::RichText::new(category.as_label()).weak());
+        for descriptor in descriptors {
+            if ui.selectable_label(false, descriptor.display_label()).clicked() {
+                selected_tool_id = Some(descriptor.id.clone());
+            }
+        }
+    }
+    selected_tool_id
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn titles(workspace: &Workspace) -> Vec<&str> {
+        workspace.tabs.iter().map(|tab| tab.title.as_str()).collect()
+    }
+
+    #[test]
+    fn reopened_tab_gets_smallest_free_number() {
+        let registry = ToolRegistry::with_builtin_tools();
+        let title = registry.get("json_format").unwrap().display_label();
+        let mut workspace = Workspace::new();
+        workspace.open(&registry, "json_format");
+        workspace.open(&registry, "json_format");
+        assert_eq!(titles(&workspace), vec![title.clone(), format!("{title} (2)")]);
+
+        workspace.close(0);
+        workspace.open(&registry, "json_format");
+        assert_eq!(titles(&workspace), vec![format!("{title} (2)"), title.clone()]);
+        workspace.open(&registry, "json_format");
+        assert_eq!(titles(&workspace)[2], format!("{title} (3)"));
+
+        workspace.close(1);
+        workspace.open(&registry, "json_format");
+        assert_eq!(titles(&workspace)[2], title);
+    }
+
+    #[test]
+    fn titles_sharing_a_prefix_are_not_counted() {
+        let registry = ToolRegistry::with_builtin_tools();
+        let title = registry.get("json_format").unwrap().display_label();
+        let mut workspace = Workspace::new();
+        workspace.push_tab("json_format".to_string(), format!("{title} (旧)"), registry.get("json_format").unwrap().create());
+        assert_eq!(workspace.unique_title(&title), title);
+    }
+}